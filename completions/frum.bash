@@ -13,30 +13,72 @@ _frum() {
                 cmd="frum"
                 ;;
             
+            alias)
+                cmd+="__alias"
+                ;;
+            bundle)
+                cmd+="__bundle"
+                ;;
+            cache)
+                cmd+="__cache"
+                ;;
+            clear)
+                cmd+="__clear"
+                ;;
             completions)
                 cmd+="__completions"
                 ;;
+            export)
+                cmd+="__export"
+                ;;
             global)
                 cmd+="__global"
                 ;;
             help)
                 cmd+="__help"
                 ;;
+            import)
+                cmd+="__import"
+                ;;
             init)
                 cmd+="__init"
                 ;;
             install)
                 cmd+="__install"
                 ;;
+            list)
+                cmd+="__list"
+                ;;
             local)
                 cmd+="__local"
                 ;;
+            migrate)
+                cmd+="__migrate"
+                ;;
+            prune)
+                cmd+="__prune"
+                ;;
+            reinstall)
+                cmd+="__reinstall"
+                ;;
+            repair)
+                cmd+="__repair"
+                ;;
+            shell)
+                cmd+="__shell"
+                ;;
             uninstall)
                 cmd+="__uninstall"
                 ;;
+            upgrade)
+                cmd+="__upgrade"
+                ;;
             versions)
                 cmd+="__versions"
                 ;;
+            which)
+                cmd+="__which"
+                ;;
             *)
                 ;;
         esac
@@ -44,7 +86,7 @@ _frum() {
 
     case "${cmd}" in
         frum)
-            opts=" -h -V  --help --version --log-level --ruby-build-mirror --frum-dir   init install uninstall versions local global completions help"
+            opts=" -h -V  --no-color --offline --timings --json --help --version --log-level --ruby-build-mirror --mirror-index --log-file --assume-shell --frum-dir   init install uninstall upgrade versions local global shell which bundle migrate export import repair prune reinstall alias cache completions help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -52,13 +94,25 @@ _frum() {
             case "${prev}" in
                 
                 --log-level)
-                    COMPREPLY=($(compgen -f "${cur}"))
+                    COMPREPLY=($(compgen -W "quiet error info" -- "${cur}"))
                     return 0
                     ;;
                 --ruby-build-mirror)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --mirror-index)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --log-file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --assume-shell)
+                    COMPREPLY=($(compgen -W "bash zsh fish powershell" -- "${cur}"))
+                    return 0
+                    ;;
                 --frum-dir)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -71,8 +125,110 @@ _frum() {
             return 0
             ;;
         
+        frum__alias)
+            opts=" -h -V  --force --help --version --resolve --rename  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                --resolve)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --rename)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__bundle)
+            opts=" -h -V  --auto-install --help --version  <args>... "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__cache)
+            opts=" -h -V  --help --version   list clear help"
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__cache__clear)
+            opts=" -h -V  --help --version --older-than  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                --older-than)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__cache__help)
+            opts=" -h -V  --help --version  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__cache__list)
+            opts=" -h -V  --help --version  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         frum__completions)
-            opts=" -l -h -V -s  --list --help --version --shell  "
+            opts=" -l -h -V -s  --list --check --dynamic-off --help --version --shell --starts-with --output-dir --shell-version  "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -80,10 +236,22 @@ _frum() {
             case "${prev}" in
                 
                 --shell)
-                    COMPREPLY=($(compgen -f "${cur}"))
+                    COMPREPLY=($(compgen -W "auto bash fish zsh powershell elvish" -- "${cur}"))
                     return 0
                     ;;
                     -s)
+                    COMPREPLY=($(compgen -W "auto bash fish zsh powershell elvish" -- "${cur}"))
+                    return 0
+                    ;;
+                --starts-with)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --output-dir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --shell-version)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
@@ -94,8 +262,23 @@ _frum() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        frum__export)
+            opts=" -h -V  --help --version  <file> "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         frum__global)
-            opts=" -h -V  --help --version  $(frum completions --list) "
+            opts=" -h -V  --unset --rollback --help --version  $(frum completions --list --shell bash --starts-with="${cur}") "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -124,8 +307,23 @@ _frum() {
             COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
             return 0
             ;;
+        frum__import)
+            opts=" -h -V  --dry-run --help --version  <file> "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
         frum__init)
-            opts=" -h -V  --help --version  "
+            opts=" -h -V  --path-only --help --version  "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -140,13 +338,77 @@ _frum() {
             return 0
             ;;
         frum__install)
-            opts=" -l -h -V  --list --help --version  $(frum install -l) "
+            opts=" -l -h -V  --list --verify-signature --dry-run --print-url --no-build --only-download --json --force --resume --include-prereleases --dependencies-only --quiet-build --use-ccache --verify-build --help --version --source --from --channel --signature-key --checksum-file --major --minor --filter --limit --openssl-version --post-install --tmpdir --prefix-strip --patch --parallel --also  $(frum install -l) "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
                 
+                --source)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --from)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --channel)
+                    COMPREPLY=($(compgen -W "stable preview dev" -- "${cur}"))
+                    return 0
+                    ;;
+                --signature-key)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --checksum-file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --major)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --minor)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --filter)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --limit)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --openssl-version)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --post-install)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --tmpdir)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --prefix-strip)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --patch)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --parallel)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --also)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
@@ -155,7 +417,90 @@ _frum() {
             return 0
             ;;
         frum__local)
-            opts=" -h -V  --help --version  $(frum completions --list) "
+            opts=" -y -h -V  --install-if-missing --yes --quiet --print --help --version  $(frum completions --list --shell bash --starts-with="${cur}") "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__migrate)
+            opts=" -h -V  --dry-run --help --version --from --root  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                --from)
+                    COMPREPLY=($(compgen -W "rbenv rvm asdf" -- "${cur}"))
+                    return 0
+                    ;;
+                --root)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__prune)
+            opts=" -h -V  --dangling --help --version  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__reinstall)
+            opts=" -h -V  --all --only-source --help --version  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__repair)
+            opts=" -h -V  --help --version  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__shell)
+            opts=" -h -V  --preserve-env --help --version  <version> "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -170,7 +515,22 @@ _frum() {
             return 0
             ;;
         frum__uninstall)
-            opts=" -h -V  --help --version  $(frum completions --list) "
+            opts=" -h -V  --prune-cache --help --version  $(frum completions --list --shell bash --starts-with="${cur}") "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__upgrade)
+            opts=" -h -V  --no-repin --remove-old --help --version  <version> "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -185,7 +545,38 @@ _frum() {
             return 0
             ;;
         frum__versions)
-            opts=" -h -V  --help --version  "
+            opts=" -h -V  --outdated --stable-only --current-only --sizes --count --remote --json --help --version --format --sort --prefix --installed-by  "
+            if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
+                COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+                return 0
+            fi
+            case "${prev}" in
+                
+                --format)
+                    COMPREPLY=($(compgen -W "list table" -- "${cur}"))
+                    return 0
+                    ;;
+                --sort)
+                    COMPREPLY=($(compgen -W "version size" -- "${cur}"))
+                    return 0
+                    ;;
+                --prefix)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --installed-by)
+                    COMPREPLY=($(compgen -W "source prebuilt imported" -- "${cur}"))
+                    return 0
+                    ;;
+                *)
+                    COMPREPLY=()
+                    ;;
+            esac
+            COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
+            return 0
+            ;;
+        frum__which)
+            opts=" -h -V  --all --json --gem --resolve-symlinks --help --version  <binary> "
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
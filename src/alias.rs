@@ -1,6 +1,7 @@
 use crate::config::FrumConfig;
 use crate::symlink::{create_symlink_dir, remove_symlink_dir};
 use crate::version::Version;
+use std::convert::TryInto;
 use std::path::PathBuf;
 
 pub fn create_alias(
@@ -25,6 +26,18 @@ pub fn create_alias(
     Ok(())
 }
 
+/// Lists the aliases whose destination still exists.
+pub fn find_aliases(config: &FrumConfig) -> std::io::Result<Vec<StoredAlias>> {
+    let mut aliases = vec![];
+    for entry in config.aliases_dir().read_dir()? {
+        let path = entry?.path();
+        if let Ok(alias) = path.as_path().try_into() {
+            aliases.push(alias);
+        }
+    }
+    Ok(aliases)
+}
+
 #[derive(Debug)]
 pub struct StoredAlias {
     alias_path: PathBuf,
@@ -35,7 +48,7 @@ impl std::convert::TryInto<StoredAlias> for &std::path::Path {
     type Error = std::io::Error;
 
     fn try_into(self) -> Result<StoredAlias, Self::Error> {
-        let destination_path = std::fs::canonicalize(&self)?;
+        let destination_path = std::fs::canonicalize(self)?;
         Ok(StoredAlias {
             alias_path: PathBuf::from(self),
             destination_path,
@@ -46,8 +59,6 @@ impl std::convert::TryInto<StoredAlias> for &std::path::Path {
 impl StoredAlias {
     pub fn s_ver(&self) -> &str {
         self.destination_path
-            .parent()
-            .unwrap()
             .file_name()
             .expect("must have basename")
             .to_str()
@@ -2,6 +2,7 @@ use std::error::Error as StdError;
 use std::path::Path;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     IoError(std::io::Error),
     ZipError(zip::result::ZipError),
@@ -1,3 +1,4 @@
 pub mod extract;
+pub mod tar_gz;
 pub mod tar_xz;
 pub mod zip;
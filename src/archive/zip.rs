@@ -1,30 +1,30 @@
 use crate::archive::extract::{Error, Extract};
 use log::debug;
-use reqwest::blocking::Response;
 use std::fs;
 use std::io;
+use std::io::Read;
 use std::path::Path;
 use tempfile::tempfile;
 use zip::read::ZipArchive;
 
-pub struct Zip {
-    response: Response,
+pub struct Zip<R: Read> {
+    response: R,
 }
 
-impl Zip {
+impl<R: Read> Zip<R> {
     #[allow(dead_code)]
-    pub fn new(response: Response) -> Self {
+    pub fn new(response: R) -> Self {
         Self { response }
     }
 }
 
-impl Extract for Zip {
+impl<R: Read> Extract for Zip<R> {
     fn extract_into<P: AsRef<Path>>(mut self, path: P) -> Result<(), Error> {
         let path = path.as_ref();
         let mut tmp_zip_file = tempfile().expect("Can't get a temporary file");
 
         debug!("Created a temporary zip file");
-        self.response.copy_to(&mut tmp_zip_file)?;
+        io::copy(&mut self.response, &mut tmp_zip_file)?;
         debug!(
             "Wrote zipfile successfully. Now extracting into {}.",
             path.display()
@@ -43,7 +43,7 @@ impl Extract for Zip {
                 }
             }
 
-            if (&*file.name()).ends_with('/') {
+            if file.name().ends_with('/') {
                 debug!(
                     "File {} extracted to \"{}\"",
                     i,
@@ -59,7 +59,7 @@ impl Extract for Zip {
                 );
                 if let Some(p) = outpath.parent() {
                     if !p.exists() {
-                        fs::create_dir_all(&p)?;
+                        fs::create_dir_all(p)?;
                     }
                 }
                 let mut outfile = fs::File::create(&outpath)?;
@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+const CHECKPOINT_FILE: &str = "checkpoint";
+
+/// The phases of a build that have completed, recorded in its build directory
+/// so that `install --resume` can skip them after a failure.
+///
+/// Each phase is recorded with what it was done with, such as the configure
+/// arguments. A phase only counts as done for the same thing, and redoing a
+/// phase forgets the phases after it.
+#[derive(Debug)]
+pub struct Checkpoint {
+    path: Option<PathBuf>,
+    phases: Vec<(String, String)>,
+}
+
+impl Checkpoint {
+    /// A checkpoint that records nothing, for builds that can't be resumed.
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            phases: vec![],
+        }
+    }
+
+    /// Starts recording the phases of the build in `build_dir`, keeping the
+    /// ones recorded before if `resume` is set.
+    pub fn open(build_dir: &Path, resume: bool) -> std::io::Result<Self> {
+        let path = build_dir.join(CHECKPOINT_FILE);
+        let phases = if resume {
+            std::fs::read_to_string(&path)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| line.split_once('\t'))
+                .map(|(phase, detail)| (phase.to_string(), detail.to_string()))
+                .collect()
+        } else {
+            vec![]
+        };
+        let checkpoint = Self {
+            path: Some(path),
+            phases,
+        };
+        checkpoint.save()?;
+        Ok(checkpoint)
+    }
+
+    /// Whether `phase` was completed with `detail`. A phase recorded with
+    /// anything else is forgotten along with the phases after it.
+    pub fn is_done(&mut self, phase: &str, detail: &str) -> bool {
+        match self.phases.iter().position(|(done, _)| done == phase) {
+            Some(index) if self.phases[index].1 == detail => true,
+            Some(index) => {
+                self.phases.truncate(index);
+                false
+            }
+            None => false,
+        }
+    }
+
+    pub fn complete(&mut self, phase: &str, detail: &str) -> std::io::Result<()> {
+        if let Some(index) = self.phases.iter().position(|(done, _)| done == phase) {
+            self.phases.truncate(index);
+        }
+        self.phases.push((phase.to_string(), detail.to_string()));
+        self.save()
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let lines = self
+            .phases
+            .iter()
+            .map(|(phase, detail)| format!("{}\t{}\n", phase, detail))
+            .collect::<String>();
+        std::fs::write(path, lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resume_only_matching_phases() {
+        let build_dir = tempdir().unwrap();
+        let mut checkpoint = Checkpoint::open(build_dir.path(), false).unwrap();
+        checkpoint
+            .complete("download", "ruby-3.0.0.tar.xz 100")
+            .unwrap();
+        checkpoint.complete("configure", "--prefix=/a").unwrap();
+        checkpoint.complete("make", "").unwrap();
+
+        let mut resumed = Checkpoint::open(build_dir.path(), true).unwrap();
+        assert!(resumed.is_done("download", "ruby-3.0.0.tar.xz 100"));
+        assert!(!resumed.is_done("configure", "--prefix=/b"));
+        assert!(!resumed.is_done("make", ""));
+
+        assert!(!Checkpoint::open(build_dir.path(), false)
+            .unwrap()
+            .is_done("download", "ruby-3.0.0.tar.xz 100"));
+    }
+}
@@ -0,0 +1,80 @@
+use crate::command::ErrorKind;
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Line {line} of {} isn't a SHA-256 checksum followed by a file name", path.display())]
+    InvalidLine { path: PathBuf, line: usize },
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::InvalidLine { .. } => "InvalidLine",
+        }
+    }
+}
+
+/// The SHA-256 checksums of archives by file name, read from a manifest laid out like
+/// the output of `sha256sum`: one `<checksum>  <file>` per line.
+#[derive(Debug, Default)]
+pub struct Checksums {
+    by_file_name: HashMap<String, String>,
+}
+
+impl Checksums {
+    pub fn read(path: &Path) -> Result<Self, Error> {
+        let mut by_file_name = HashMap::new();
+        for (index, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = || Error::InvalidLine {
+                path: path.to_path_buf(),
+                line: index + 1,
+            };
+            let (checksum, file) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            // `sha256sum --binary` marks the file name with `*`
+            let file = file.trim_start().trim_start_matches('*');
+            if checksum.len() != 64 || !checksum.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            // Mirrors list archives with their directory, such as `3.2/ruby-3.2.2.tar.xz`
+            let file_name = file.rsplit('/').next().unwrap_or(file);
+            by_file_name.insert(file_name.to_string(), checksum.to_ascii_lowercase());
+        }
+        Ok(Self { by_file_name })
+    }
+
+    /// The checksum listed for the archive named `file_name`.
+    pub fn get(&self, file_name: &str) -> Option<&str> {
+        self.by_file_name.get(file_name).map(String::as_str)
+    }
+}
+
+/// The SHA-256 checksum of the file at `path`, in lowercase hex like `sha256sum` prints it.
+pub fn sha256(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buffer = [0; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        context.update(&buffer[..read]);
+    }
+    Ok(context
+        .finish()
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
@@ -1,3 +1,7 @@
+use crate::command::Command;
+use crate::commands;
+use crate::input_version::InputVersion;
+use crate::shell::AVAILABLE_SHELLS;
 use clap::{App, AppSettings, Arg, SubCommand};
 
 pub fn build_cli() -> App<'static, 'static> {
@@ -8,15 +12,60 @@ pub fn build_cli() -> App<'static, 'static> {
         .arg(
             Arg::with_name("log-level")
                 .long("log-level")
-                .help("The log level of frum commands [default: info] [possible values: quiet, info, error]")
-                .takes_value(true),
+                .help("The log level of frum commands [default: info]")
+                .takes_value(true)
+                .possible_values(crate::log::LogLevel::NAMES),
         )
         .arg(
             Arg::with_name("ruby-build-mirror")
                 .long("ruby-build-mirror")
-                .help("[default: https://cache.ruby-lang.org/pub/ruby]")
+                .help("[default: https://cache.ruby-lang.org/pub/ruby]. Given several times, installs from the fastest mirror")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1),
+        )
+        .arg(
+            Arg::with_name("mirror-index")
+                .long("mirror-index")
+                .value_name("url")
+                .help("The URL of the list of versions, if it isn't served next to the archives [default: <ruby-build-mirror>/index.txt]")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("log-file")
+                .long("log-file")
+                .value_name("path")
+                .help("Appends all output, with timestamps and whatever the log level, to the file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("no-color")
+                .long("no-color")
+                .help("Prints the output without colors, which are already off when it is piped"),
+        )
+        .arg(
+            Arg::with_name("offline")
+                .long("offline")
+                .help("Uses the cached list of remote Ruby versions instead of fetching it"),
+        )
+        .arg(
+            Arg::with_name("timings")
+                .long("timings")
+                .help("Prints how long the command and each phase of an installation took"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Prints errors as JSON with their kind, such as {\"error\": {\"kind\": \"VersionNotFound\", \"message\": ...}}"),
+        )
+        .arg(
+            Arg::with_name("assume-shell")
+                .long("assume-shell")
+                .takes_value(true)
+                .value_name("shell")
+                .possible_values(AVAILABLE_SHELLS)
+                .help("The shell `init` and `completions` use when they can't infer it, such as in CI [env: FRUM_SHELL]"),
+        )
         .arg(
             Arg::with_name("base-dir")
                 .long("frum-dir")
@@ -24,7 +73,13 @@ pub fn build_cli() -> App<'static, 'static> {
                 .takes_value(true),
         )
         .subcommand(
-            SubCommand::with_name("init").about("Sets environment variables for initializing frum"),
+            SubCommand::with_name("init")
+                .about("Sets environment variables for initializing frum")
+                .arg(
+                    Arg::with_name("path-only")
+                        .long("path-only")
+                        .help("Only adds the default version to PATH, e.g. in a Dockerfile"),
+                ),
         )
         .subcommand(
             SubCommand::with_name("install")
@@ -37,7 +92,207 @@ pub fn build_cli() -> App<'static, 'static> {
                         .long("list")
                         .help("Lists Ruby versions available to install"),
                 )
-                .arg(Arg::with_name("version").index(1))
+                .arg(
+                    Arg::with_name("verify-signature")
+                        .long("verify-signature")
+                        .help("Verifies the GPG signature of the downloaded archive"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Prints the version and URL to be installed without installing it"),
+                )
+                .arg(
+                    Arg::with_name("print-url")
+                        .long("print-url")
+                        .help("Prints only the download URL of the version without installing it"),
+                )
+                .arg(
+                    Arg::with_name("source")
+                        .long("source")
+                        .takes_value(true)
+                        .value_name("url-or-git")
+                        .conflicts_with_all(&["version", "list", "dry-run", "print-url", "only-download"])
+                        .help("Builds Ruby from a tarball URL or a git repository (with an optional #ref) instead of a release"),
+                )
+                .arg(
+                    Arg::with_name("no-build")
+                        .long("no-build")
+                        .requires("from")
+                        .conflicts_with_all(&["list", "source", "dry-run", "print-url", "only-download", "resume"])
+                        .help("Registers a Ruby built outside of frum as the version instead of building it"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .value_name("dir")
+                        .requires("no-build")
+                        .help("The directory of the Ruby installation to register with --no-build"),
+                )
+                .arg(
+                    Arg::with_name("only-download")
+                        .long("only-download")
+                        .help("Downloads the archive into the cache without building it"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints the output as JSON"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .help("Removes and rebuilds the version if it is already installed"),
+                )
+                .arg(
+                    Arg::with_name("resume")
+                        .long("resume")
+                        .conflicts_with_all(&["source", "only-download"])
+                        .help("Continues a failed build, skipping the phases it has already completed"),
+                )
+                .arg(
+                    Arg::with_name("include-prereleases")
+                        .long("include-prereleases")
+                        .help("Includes previews and release candidates when listing or resolving versions"),
+                )
+                .arg(
+                    Arg::with_name("channel")
+                        .long("channel")
+                        .takes_value(true)
+                        .possible_values(crate::version::Channel::NAMES)
+                        .conflicts_with("include-prereleases")
+                        .help("Lists or resolves only stable releases, previews and release candidates, or dev snapshots"),
+                )
+                .arg(
+                    Arg::with_name("signature-key")
+                        .long("signature-key")
+                        .help("The public key used to verify the signature [default: your GPG keyring]")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("checksum-file")
+                        .long("checksum-file")
+                        .takes_value(true)
+                        .value_name("path")
+                        .help("Verifies the archive against its SHA-256 in this list of checksums, laid out like the output of sha256sum"),
+                )
+                .arg(
+                    Arg::with_name("major")
+                        .long("major")
+                        .help("Lists only versions of the specified major series, e.g. 3")
+                        .takes_value(true)
+                        .requires("list"),
+                )
+                .arg(
+                    Arg::with_name("minor")
+                        .long("minor")
+                        .help("Lists only versions of the specified minor series, e.g. 3.1")
+                        .takes_value(true)
+                        .requires("list"),
+                )
+                .arg(
+                    Arg::with_name("filter")
+                        .long("filter")
+                        .help("Lists only versions containing the specified text")
+                        .takes_value(true)
+                        .requires("list"),
+                )
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .alias("tail")
+                        .value_name("N")
+                        .help("Lists only the newest N versions, or all of them with 0 [default: 20 on a terminal, all otherwise]")
+                        .takes_value(true)
+                        .requires("list"),
+                )
+                .arg(
+                    Arg::with_name("openssl-version")
+                        .long("openssl-version")
+                        .help("Builds the specified OpenSSL version and links Ruby against it")
+                        .takes_value(true),
+                )
+                .arg(
+                    Arg::with_name("dependencies-only")
+                        .long("dependencies-only")
+                        .requires("openssl-version")
+                        .conflicts_with_all(&["list", "source", "no-build", "only-download", "dry-run", "print-url"])
+                        .help("Builds only the dependencies given with --openssl-version into the cache, without building Ruby"),
+                )
+                .arg(
+                    Arg::with_name("post-install")
+                        .long("post-install")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("cmd")
+                        .conflicts_with_all(&["list", "dry-run", "print-url", "only-download", "dependencies-only"])
+                        .help("Runs the shell command with the new version after installing it, such as 'gem install bundler'. Can be given several times"),
+                )
+                .arg(
+                    Arg::with_name("quiet-build")
+                        .long("quiet-build")
+                        .help("Keeps the configure and make output in a build log while still printing the progress"),
+                )
+                .arg(
+                    Arg::with_name("use-ccache")
+                        .long("use-ccache")
+                        .help("Compiles through ccache if it is on PATH, so that building a version again is faster"),
+                )
+                .arg(
+                    Arg::with_name("verify-build")
+                        .long("verify-build")
+                        .help("Runs `ruby -v` of the new version and fails unless it reports the requested version"),
+                )
+                .arg(
+                    Arg::with_name("tmpdir")
+                        .long("tmpdir")
+                        .takes_value(true)
+                        .value_name("dir")
+                        .help("Builds in this directory instead of $FRUM_TMPDIR or the versions directory, e.g. on a tmpfs"),
+                )
+                .arg(
+                    Arg::with_name("prefix-strip")
+                        .long("prefix-strip")
+                        .takes_value(true)
+                        .value_name("N")
+                        .help("Builds in the directory N levels down the archive instead of detecting where the source is"),
+                )
+                .arg(
+                    Arg::with_name("patch")
+                        .long("patch")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("file-or-url")
+                        .conflicts_with_all(&["list", "no-build"])
+                        .help("Applies a patch to the source with `patch -p1` before building it, or the patch on standard input with -. Can be given several times"),
+                )
+                .arg(
+                    Arg::with_name("parallel")
+                        .long("parallel")
+                        .help("Builds up to the specified number of versions at a time when several versions are given")
+                        .takes_value(true)
+                        .requires("also"),
+                )
+                .arg(
+                    Arg::with_name("also")
+                        .long("also")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("version")
+                        .validator(|version| {
+                            version
+                                .parse::<InputVersion>()
+                                .map(|_| ())
+                                .map_err(|err| err.to_string())
+                        })
+                        .conflicts_with_all(&["list", "source", "no-build", "dependencies-only"])
+                        .help("Also installs the specified version along with the first one. Can be given several times"),
+                )
+                .arg(version_arg::<commands::install::Install>())
                 .arg(
                     Arg::with_name("configure_opts")
                         .multiple(true)
@@ -47,18 +302,317 @@ pub fn build_cli() -> App<'static, 'static> {
         .subcommand(
             SubCommand::with_name("uninstall")
                 .about("Uninstall a specific Ruby version")
-                .arg(Arg::with_name("version").index(1).required(true)),
+                .arg(
+                    Arg::with_name("prune-cache")
+                        .long("prune-cache")
+                        .help("Also removes the downloaded archive of the version from the cache"),
+                )
+                .arg(version_arg::<commands::uninstall::Uninstall>()),
+        )
+        .subcommand(
+            SubCommand::with_name("upgrade")
+                .about("Upgrades a Ruby version to its newest patch release")
+                .arg(
+                    Arg::with_name("no-repin")
+                        .long("no-repin")
+                        .help("Keeps aliases and .ruby-version pointing at the old version"),
+                )
+                .arg(
+                    Arg::with_name("remove-old")
+                        .long("remove-old")
+                        .help("Uninstalls the old version after upgrading"),
+                )
+                .arg(version_arg::<commands::upgrade::Upgrade>()),
+        )
+        .subcommand(
+            SubCommand::with_name("versions")
+                .about("Lists installed Ruby versions")
+                .arg(
+                    Arg::with_name("outdated")
+                        .long("outdated")
+                        .help("Shows newer patch releases available for installed versions"),
+                )
+                .arg(
+                    Arg::with_name("stable-only")
+                        .long("stable-only")
+                        .help("Hides installed previews and release candidates"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["list", "table"])
+                        .default_value("list")
+                        .help("Shows the versions as a list or as a table with their install date, size, aliases and end of life"),
+                )
+                .arg(
+                    Arg::with_name("current-only")
+                        .long("current-only")
+                        .conflicts_with_all(&["outdated", "stable-only"])
+                        .help("Prints only the current version, or nothing if there is none"),
+                )
+                .arg(
+                    Arg::with_name("sizes")
+                        .long("sizes")
+                        .conflicts_with("current-only")
+                        .help("Shows how much disk space each version takes"),
+                )
+                .arg(
+                    Arg::with_name("sort")
+                        .long("sort")
+                        .takes_value(true)
+                        .possible_values(&["version", "size"])
+                        .default_value("version")
+                        .help("Orders the versions by version, or by size with the biggest first"),
+                )
+                .arg(
+                    Arg::with_name("prefix")
+                        .long("prefix")
+                        .takes_value(true)
+                        .value_name("dir")
+                        .conflicts_with("current-only")
+                        .help("Lists the versions in a directory of another installation, such as ~/.rbenv/versions, instead of the installed ones"),
+                )
+                .arg(
+                    Arg::with_name("installed-by")
+                        .long("installed-by")
+                        .takes_value(true)
+                        .possible_values(&["source", "prebuilt", "imported"])
+                        .conflicts_with("current-only")
+                        .help("Lists only the versions built from source, registered with `install --no-build` or imported by `frum migrate`"),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .conflicts_with_all(&["current-only", "sizes", "outdated"])
+                        .help("Prints only the number of versions that would be listed"),
+                )
+                .arg(
+                    Arg::with_name("remote")
+                        .long("remote")
+                        .requires("count")
+                        .conflicts_with_all(&["prefix", "installed-by"])
+                        .help("Counts the versions available to install instead of the installed ones"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .conflicts_with_all(&["current-only", "count"])
+                        .help("Prints the versions as JSON, with whether they are current and their aliases, instead of the list or the table"),
+                ),
         )
-        .subcommand(SubCommand::with_name("versions").about("Lists installed Ruby versions"))
         .subcommand(
             SubCommand::with_name("local")
                 .about("Sets the current Ruby version")
-                .arg(Arg::with_name("version").index(1)),
+                .arg(
+                    Arg::with_name("install-if-missing")
+                        .long("install-if-missing")
+                        .help("Installs the version first if it isn't installed yet [env: FRUM_INSTALL_IF_MISSING]"),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .short("y")
+                        .long("yes")
+                        .help("Installs a missing version without asking"),
+                )
+                .arg(
+                    Arg::with_name("quiet")
+                        .long("quiet")
+                        .help("Does nothing if neither the directory nor .ruby-version has changed since the last call"),
+                )
+                .arg(
+                    Arg::with_name("print")
+                        .long("print")
+                        .help("Prints the version that would be used and where it is set, without switching to it"),
+                )
+                .arg(version_arg::<commands::local::Local>()),
         )
         .subcommand(
             SubCommand::with_name("global")
                 .about("Sets the global Ruby version")
-                .arg(Arg::with_name("version").index(1).required(true)),
+                .arg(
+                    Arg::with_name("unset")
+                        .long("unset")
+                        .conflicts_with("version")
+                        .help("Removes the global Ruby version, so the system Ruby is used where no local version is set"),
+                )
+                .arg(
+                    Arg::with_name("rollback")
+                        .long("rollback")
+                        .conflicts_with_all(&["version", "unset"])
+                        .help("Selects the global Ruby version selected before the current one again"),
+                )
+                .arg(
+                    version_arg::<commands::global::Global>()
+                        .required_unless_one(&["unset", "rollback"]),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("shell")
+                .about("Starts a new shell using the specified Ruby version until it exits")
+                .arg(
+                    Arg::with_name("preserve-env")
+                        .long("preserve-env")
+                        .help("Keeps the inherited GEM_HOME, GEM_PATH and RUBYOPT instead of scoping them to the version"),
+                )
+                .arg(version_arg::<commands::shell::Shell>()),
+        )
+        .subcommand(
+            SubCommand::with_name("which")
+                .about("Prints the path to a binary of the current Ruby version")
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("Prints the version and the path for every installed version having the binary"),
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Prints the version, the binary and its path as JSON, or the error if it isn't found"),
+                )
+                .arg(
+                    Arg::with_name("gem")
+                        .long("gem")
+                        .help("Also finds executables installed by `gem install` in the gem directories, without a rehash step"),
+                )
+                .arg(
+                    Arg::with_name("resolve-symlinks")
+                        .long("resolve-symlinks")
+                        .help("Prints the real path of the binary, with the symlinks of aliases and linked versions resolved"),
+                )
+                .arg(Arg::with_name("binary").index(1).required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("bundle")
+                .about("Runs Bundler of the Ruby version the project uses")
+                .setting(AppSettings::TrailingVarArg)
+                .arg(
+                    Arg::with_name("auto-install")
+                        .long("auto-install")
+                        .help("Installs Bundler with `gem install bundler` if the version doesn't have it"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .multiple(true)
+                        .allow_hyphen_values(true)
+                        .help("The arguments passed to bundle"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("migrate")
+                .about("Imports the Rubies installed by another version manager")
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&["rbenv", "rvm", "asdf"])
+                        .help("The version manager to import from"),
+                )
+                .arg(
+                    Arg::with_name("root")
+                        .long("root")
+                        .takes_value(true)
+                        .value_name("dir")
+                        .help("The data directory of the version manager, such as ~/.rbenv, if it isn't the default"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Prints the versions that would be imported without importing them"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Writes the installed versions, aliases and global version as JSON")
+                .arg(
+                    Arg::with_name("file")
+                        .index(1)
+                        .help("The file to write instead of printing the version set"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("Installs the versions and recreates the aliases and global version written by `frum export`")
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Prints what would be installed and changed without doing it"),
+                )
+                .arg(Arg::with_name("file").index(1).required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("repair")
+                .about("Removes or re-points aliases whose version no longer exists"),
+        )
+        .subcommand(
+            SubCommand::with_name("prune")
+                .about("Cleans up what frum left behind, without touching installed versions")
+                .arg(
+                    Arg::with_name("dangling")
+                        .long("dangling")
+                        .required(true)
+                        .help("Removes aliases, including the global version, pointing at versions that no longer exist, and the symlinks of shell sessions that have exited"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("reinstall")
+                .about("Installs versions again the way they were installed, such as after upgrading the OS or the compiler")
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .required(true)
+                        .help("Reinstalls every installed version, going on after the ones that fail"),
+                )
+                .arg(
+                    Arg::with_name("only-source")
+                        .long("only-source")
+                        .help("Only rebuilds the versions built from source, leaving the ones registered with --no-build or imported by migrate alone"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("alias")
+                .about("Lists, resolves and renames aliases")
+                .arg(
+                    Arg::with_name("resolve")
+                        .long("resolve")
+                        .help("Prints the version the specified alias resolves to")
+                        .takes_value(true)
+                        .value_name("name"),
+                )
+                .arg(
+                    Arg::with_name("rename")
+                        .long("rename")
+                        .help("Renames an alias, keeping the version it points at")
+                        .number_of_values(2)
+                        .value_names(&["old", "new"])
+                        .conflicts_with("resolve"),
+                )
+                .arg(
+                    Arg::with_name("force")
+                        .long("force")
+                        .requires("rename")
+                        .help("Replaces the alias already having the new name"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cache")
+                .about("Inspects and clears the cached downloads and remote version list")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("list")
+                        .about("Lists the cache entries with their sizes and ages"),
+                )
+                .subcommand(
+                    SubCommand::with_name("clear")
+                        .about("Removes the cache entries")
+                        .arg(
+                            Arg::with_name("older-than")
+                                .long("older-than")
+                                .help("Removes only entries older than the specified duration, e.g. 30m, 12h, 7d or 2w")
+                                .takes_value(true),
+                        ),
+                ),
         )
         .subcommand(
             SubCommand::with_name("completions")
@@ -67,8 +621,9 @@ pub fn build_cli() -> App<'static, 'static> {
                     Arg::with_name("shell")
                         .short("s")
                         .long("shell")
-                        .help("The shell syntax to use")
-                        .takes_value(true),
+                        .help("The shell syntax to use, or auto to infer it from the process tree, $SHELL or FRUM_SHELL [default: auto]")
+                        .takes_value(true)
+                        .possible_values(commands::completions::SHELL_VALUES),
                 )
                 .arg(
                     Arg::with_name("list")
@@ -76,6 +631,49 @@ pub fn build_cli() -> App<'static, 'static> {
                         .long("list")
                         .help("Lists installed Ruby versions")
                         .hidden(true),
+                )
+                .arg(
+                    Arg::with_name("starts-with")
+                        .long("starts-with")
+                        .takes_value(true)
+                        .value_name("prefix")
+                        .allow_hyphen_values(true)
+                        .requires("list")
+                        .help("Lists only the versions starting with the word being completed")
+                        .hidden(true),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Checks that the generated completions still complete Ruby versions"),
+                )
+                .arg(
+                    Arg::with_name("output-dir")
+                        .long("output-dir")
+                        .takes_value(true)
+                        .conflicts_with_all(&["shell", "check"])
+                        .help("Writes the zsh, bash and fish completions into the directory as _frum, frum.bash and frum.fish"),
+                )
+                .arg(
+                    Arg::with_name("dynamic-off")
+                        .long("dynamic-off")
+                        .conflicts_with("check")
+                        .help("Prints clap's completions, which don't call frum to complete installed versions"),
+                )
+                .arg(
+                    Arg::with_name("shell-version")
+                        .long("shell-version")
+                        .takes_value(true)
+                        .value_name("version")
+                        .conflicts_with_all(&["list", "check", "output-dir"])
+                        .help("The version of the shell, such as $BASH_VERSION, for syntax older shells understand [default: modern syntax]"),
                 ),
         )
 }
+
+/// The positional version argument, which a command must be given unless it can infer the version.
+fn version_arg<C: Command>() -> Arg<'static, 'static> {
+    Arg::with_name("version")
+        .index(1)
+        .required(!C::supports_version_inference())
+}
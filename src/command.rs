@@ -1,21 +1,221 @@
 use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::json::JsonEnvelope;
 use crate::outln;
+use crate::version_file::get_user_version_for_directory;
 use colored::Colorize;
+use std::path::Path;
+
+/// A stable name for an error, reported as its `kind` when the top-level `--json` flag prints errors as JSON.
+pub trait ErrorKind {
+    /// The name of the variant, or the kind of the error of another frum module it wraps,
+    /// e.g. `VersionNotFound` for `InstallError(VersionNotFound { .. })`.
+    fn kind(&self) -> &'static str;
+}
+
+impl ErrorKind for std::io::Error {
+    fn kind(&self) -> &'static str {
+        "IoError"
+    }
+}
 
 pub trait Command {
-    type Error: std::error::Error;
+    type Error: std::error::Error + ErrorKind;
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error>;
 
-    fn handle_error(err: Self::Error, config: &FrumConfig) {
-        outln!(config#Error, "{} {}", "error:".red().bold(), format!("{}", err).red());
+    /// Whether the command falls back to the version selected by the current
+    /// directory when none is given. Otherwise the CLI requires a version.
+    fn supports_version_inference() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    fn handle_error(err: Self::Error, config: &FrumConfig)
+    where
+        Self: Sized,
+    {
+        if config.json_errors {
+            let error = JsonEnvelope::new(serde_json::json!({
+                "error": {
+                    "kind": ErrorKind::kind(&err),
+                    "message": err.to_string(),
+                }
+            }));
+            outln!(config#Error, "{}", error);
+        } else {
+            outln!(config#Error, "{} {}", "error:".red().bold(), format!("{}", err).red());
+        }
+        if crate::interrupt::interrupted() {
+            std::process::exit(crate::interrupt::EXIT_CODE);
+        }
         std::process::exit(1);
     }
 
-    fn call(&self, config: &FrumConfig) {
-        match self.apply(&config) {
+    fn call(&self, config: &FrumConfig)
+    where
+        Self: Sized,
+    {
+        let started = std::time::Instant::now();
+        let result = self.apply(config);
+        if config.timings {
+            outln!(config#Error, "{:<12} {:.2}s", "total", started.elapsed().as_secs_f64());
+        }
+        match result {
             Ok(()) => (),
-            Err(err) => Self::handle_error(err, &config),
+            Err(err) => Self::handle_error(err, config),
         }
     }
 }
+
+/// The results of `run_all`, in the order of the commands.
+#[derive(Debug)]
+pub struct BatchResults<E> {
+    pub results: Vec<Result<(), E>>,
+}
+
+impl<E> BatchResults<E> {
+    pub fn failed(&self) -> usize {
+        self.results.iter().filter(|result| result.is_err()).count()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// How many commands succeeded and failed, such as `2 succeeded, 1 failed`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} succeeded, {} failed",
+            self.results.len() - self.failed(),
+            self.failed()
+        )
+    }
+}
+
+/// Runs the commands in turn, going on after the ones that fail.
+pub fn run_all<E: std::error::Error + ErrorKind>(
+    commands: &[Box<dyn Command<Error = E>>],
+    config: &FrumConfig,
+) -> BatchResults<E> {
+    BatchResults {
+        results: commands
+            .iter()
+            .map(|command| command.apply(config))
+            .collect(),
+    }
+}
+
+/// The version given to `C`, or the one `current_dir` selects if `C` can infer it.
+pub fn version_or_inferred<C: Command>(
+    version: Option<InputVersion>,
+    current_dir: &Path,
+) -> Option<InputVersion> {
+    version.or_else(|| {
+        if C::supports_version_inference() {
+            get_user_version_for_directory(current_dir.to_path_buf())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_all, version_or_inferred, Command, ErrorKind};
+    use crate::commands::{global::Global, install::Install, local::Local, uninstall::Uninstall};
+    use crate::config::FrumConfig;
+    use crate::input_version::InputVersion;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_version_inference_per_command() {
+        let project_dir = tempdir().unwrap();
+        std::fs::write(project_dir.path().join(".ruby-version"), "2.7.2\n").unwrap();
+        let explicit = || Some("3.0.0".parse::<InputVersion>().unwrap());
+
+        for inferred in &[
+            version_or_inferred::<Install>(None, project_dir.path()),
+            version_or_inferred::<Local>(None, project_dir.path()),
+        ] {
+            assert_eq!(
+                inferred.as_ref().map(ToString::to_string),
+                Some("2.7.2".into())
+            );
+        }
+        assert!(version_or_inferred::<Global>(None, project_dir.path()).is_none());
+        assert!(version_or_inferred::<Uninstall>(None, project_dir.path()).is_none());
+        assert_eq!(
+            version_or_inferred::<Global>(explicit(), project_dir.path())
+                .map(|version| version.to_string()),
+            Some("3.0.0".into())
+        );
+
+        let cli = crate::cli::build_cli;
+        assert!(cli().get_matches_from_safe(["frum", "local"]).is_ok());
+        assert!(cli().get_matches_from_safe(["frum", "install"]).is_ok());
+        assert!(cli().get_matches_from_safe(["frum", "global"]).is_err());
+        assert!(cli().get_matches_from_safe(["frum", "uninstall"]).is_err());
+        assert!(Local::supports_version_inference());
+        assert!(!Global::supports_version_inference());
+    }
+
+    struct Stub {
+        fails: bool,
+    }
+
+    impl Command for Stub {
+        type Error = std::io::Error;
+
+        fn apply(&self, _: &FrumConfig) -> Result<(), Self::Error> {
+            if self.fails {
+                Err(std::io::Error::other("stub failed"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_all_goes_on_after_failures() {
+        let stub = |fails| -> Box<dyn Command<Error = std::io::Error>> { Box::new(Stub { fails }) };
+        let commands = vec![stub(false), stub(true), stub(false)];
+
+        let batch = run_all(&commands, &FrumConfig::default());
+
+        assert_eq!(
+            batch.results.iter().map(Result::is_ok).collect::<Vec<_>>(),
+            vec![true, false, true]
+        );
+        assert_eq!(
+            batch.results[1].as_ref().unwrap_err().to_string(),
+            "stub failed"
+        );
+        assert!(!batch.is_ok());
+        assert_eq!(batch.summary(), "2 succeeded, 1 failed");
+        assert!(run_all(&commands[..1], &FrumConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_error_kind_of_wrapped_errors() {
+        use crate::commands::install::FrumError as InstallError;
+        use crate::commands::local::FrumError as LocalError;
+        let version = InputVersion::Full(crate::version::Version::parse("3.0.0").unwrap());
+
+        assert_eq!(LocalError::CantInferVersion.kind(), "CantInferVersion");
+        assert_eq!(
+            LocalError::InstallError(InstallError::VersionNotFound { version }).kind(),
+            "VersionNotFound"
+        );
+        assert_eq!(
+            LocalError::IoError(std::io::Error::other("stub failed")).kind(),
+            "IoError"
+        );
+        assert_eq!(
+            InstallError::SidecarError(crate::sidecar::Error::TarIsEmpty).kind(),
+            "TarIsEmpty"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use crate::alias::{find_aliases, StoredAlias};
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::outln;
+use crate::symlink::{create_symlink_dir, remove_symlink_dir};
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't find an alias named {name}")]
+    AliasNotFound { name: String },
+    #[error("The alias {name} points at {destination:?}, which doesn't exist")]
+    DanglingAlias { name: String, destination: PathBuf },
+    #[error("An alias named {name} already exists. Use --force to replace it.")]
+    AliasExists { name: String },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::AliasNotFound { .. } => "AliasNotFound",
+            Self::DanglingAlias { .. } => "DanglingAlias",
+            Self::AliasExists { .. } => "AliasExists",
+        }
+    }
+}
+
+pub struct Alias {
+    pub resolve: Option<String>,
+    /// The current and the new name of an alias to rename.
+    pub rename: Option<(String, String)>,
+    /// Replaces an alias already having the new name.
+    pub force: bool,
+}
+
+impl crate::command::Command for Alias {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if let Some((old_name, new_name)) = &self.rename {
+            rename_alias(config, old_name, new_name, self.force)?;
+            outln!(config#Info, "{} Renamed the alias {} to {}", "==>".green(), old_name, new_name.green());
+            return Ok(());
+        }
+        match &self.resolve {
+            Some(name) => {
+                let alias = resolve_alias(config, name)?;
+                outln!(config#Info, "{}", alias.s_ver());
+            }
+            None => {
+                let mut aliases = find_aliases(config)?;
+                aliases.sort_by(|a, b| a.name().cmp(b.name()));
+                for alias in aliases {
+                    outln!(config#Info, "{} -> {}", alias.name(), alias.s_ver());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Finds the alias named `name`. Its destination is canonicalized, so an alias
+/// pointing at another alias resolves to the version at the end of the chain.
+fn resolve_alias(config: &FrumConfig, name: &str) -> Result<StoredAlias, FrumError> {
+    let alias_path = config.aliases_dir().join(name);
+    if alias_path.symlink_metadata().is_err() {
+        return Err(FrumError::AliasNotFound {
+            name: name.to_string(),
+        });
+    }
+    find_aliases(config)?
+        .into_iter()
+        .find(|alias| alias.name() == name)
+        .ok_or_else(|| FrumError::DanglingAlias {
+            name: name.to_string(),
+            destination: std::fs::read_link(&alias_path).unwrap_or(alias_path),
+        })
+}
+
+/// Moves the alias `old_name` to `new_name`, keeping its destination as it is, even
+/// when that is another alias. An alias already named `new_name` is replaced only if `force` is set.
+fn rename_alias(
+    config: &FrumConfig,
+    old_name: &str,
+    new_name: &str,
+    force: bool,
+) -> Result<(), FrumError> {
+    let old_path = config.aliases_dir().join(old_name);
+    let new_path = config.aliases_dir().join(new_name);
+    if old_path.symlink_metadata().is_err() {
+        return Err(FrumError::AliasNotFound {
+            name: old_name.to_string(),
+        });
+    }
+    if old_name == new_name {
+        return Ok(());
+    }
+    if new_path.symlink_metadata().is_ok() {
+        if !force {
+            return Err(FrumError::AliasExists {
+                name: new_name.to_string(),
+            });
+        }
+        remove_symlink_dir(&new_path)?;
+    }
+    create_symlink_dir(std::fs::read_link(&old_path)?, &new_path)?;
+    remove_symlink_dir(&old_path)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rename_alias, resolve_alias, FrumError};
+    use crate::alias::create_alias;
+    use crate::config::FrumConfig;
+    use crate::symlink::create_symlink_dir;
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_resolve_direct_and_chained_alias() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        std::fs::create_dir_all(config.versions_dir().join("2.7.2")).unwrap();
+        create_alias(&config, "default", &Version::parse("2.7.2").unwrap()).unwrap();
+        create_symlink_dir(
+            config.aliases_dir().join("default"),
+            config.aliases_dir().join("lts"),
+        )
+        .unwrap();
+
+        assert_eq!(resolve_alias(&config, "default").unwrap().s_ver(), "2.7.2");
+        assert_eq!(resolve_alias(&config, "lts").unwrap().s_ver(), "2.7.2");
+    }
+
+    #[test]
+    fn test_resolve_missing_and_dangling_alias() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        create_symlink_dir(
+            config.versions_dir().join("2.6.0"),
+            config.aliases_dir().join("old"),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            resolve_alias(&config, "default"),
+            Err(FrumError::AliasNotFound { .. })
+        ));
+        assert!(matches!(
+            resolve_alias(&config, "old"),
+            Err(FrumError::DanglingAlias { .. })
+        ));
+    }
+
+    #[test]
+    fn test_rename_alias() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.7.2", "3.2.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        create_alias(&config, "lts", &Version::parse("2.7.2").unwrap()).unwrap();
+        create_alias(&config, "latest", &Version::parse("3.2.2").unwrap()).unwrap();
+
+        rename_alias(&config, "lts", "stable", false).unwrap();
+
+        assert_eq!(resolve_alias(&config, "stable").unwrap().s_ver(), "2.7.2");
+        assert!(config.aliases_dir().join("lts").symlink_metadata().is_err());
+        assert!(matches!(
+            rename_alias(&config, "lts", "old", false),
+            Err(FrumError::AliasNotFound { .. })
+        ));
+        assert!(matches!(
+            rename_alias(&config, "stable", "latest", false),
+            Err(FrumError::AliasExists { .. })
+        ));
+        assert_eq!(resolve_alias(&config, "latest").unwrap().s_ver(), "3.2.2");
+
+        rename_alias(&config, "stable", "latest", true).unwrap();
+
+        assert_eq!(resolve_alias(&config, "latest").unwrap().s_ver(), "2.7.2");
+        assert!(config
+            .aliases_dir()
+            .join("stable")
+            .symlink_metadata()
+            .is_err());
+    }
+}
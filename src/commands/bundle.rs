@@ -0,0 +1,177 @@
+use crate::command::ErrorKind;
+use crate::commands::shell::scope_gem_env;
+use crate::commands::which::{binary_path, gem_binary_path};
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{current_version, installed_versions, Version};
+use crate::version_file::{find_up, get_user_version_for_directory};
+use colored::Colorize;
+use log::debug;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error("Can't find the Ruby version of this project. Please add a .ruby-version file or run `frum local` first.")]
+    CantInferVersion,
+    #[error("Requested version {version} is not currently installed")]
+    VersionNotFound { version: InputVersion },
+    #[error(
+        "Bundler isn't installed in Ruby {version}. Run again with --auto-install to install it."
+    )]
+    BundlerNotInstalled { version: Version },
+    #[error("Can't install Bundler in Ruby {version}")]
+    CantInstallBundler { version: Version },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::CantInferVersion => "CantInferVersion",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::BundlerNotInstalled { .. } => "BundlerNotInstalled",
+            Self::CantInstallBundler { .. } => "CantInstallBundler",
+        }
+    }
+}
+
+pub struct Bundle {
+    pub args: Vec<String>,
+    pub auto_install: bool,
+}
+
+impl crate::command::Command for Bundle {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let version = project_version(config, &std::env::current_dir()?)?;
+        // GEM_HOME is scoped to the version when running Bundler, so the variable isn't looked at
+        let bundle = match gem_binary_path(config, &version, "bundle", None) {
+            Some(bundle) => bundle,
+            None if !self.auto_install => return Err(FrumError::BundlerNotInstalled { version }),
+            None => {
+                outln!(config#Info, "{} Installing Bundler in {}", "==>".green(), format!("Ruby {}", version).green());
+                let status = ruby_command(config, &version, binary_path(config, &version, "gem"))
+                    .arg("install")
+                    .arg("bundler")
+                    .status()?;
+                match gem_binary_path(config, &version, "bundle", None) {
+                    Some(bundle) if status.success() => bundle,
+                    _ => return Err(FrumError::CantInstallBundler { version }),
+                }
+            }
+        };
+
+        debug!("{} {}", bundle.display(), self.args.join(" "));
+        let status = ruby_command(config, &version, bundle)
+            .args(&self.args)
+            .status()?;
+        // Pass on how bundler exited, like running it directly would
+        match status.code() {
+            Some(0) => Ok(()),
+            code => std::process::exit(code.unwrap_or(1)),
+        }
+    }
+}
+
+/// The installed version the project in `current_dir` uses, from its `.ruby-version`,
+/// the `ruby` line of its Gemfile, or else the version of this shell.
+fn project_version(config: &FrumConfig, current_dir: &Path) -> Result<Version, FrumError> {
+    let version = get_user_version_for_directory(current_dir.to_path_buf())
+        .or_else(|| find_up(current_dir.to_path_buf(), "Gemfile").and_then(gemfile_version));
+    let version = match version {
+        Some(version) => version,
+        None => current_version(config)
+            .ok()
+            .flatten()
+            .map(InputVersion::Full)
+            .ok_or(FrumError::CantInferVersion)?,
+    };
+    version
+        .to_version(&installed_versions(config)?)
+        .cloned()
+        .ok_or(FrumError::VersionNotFound { version })
+}
+
+/// The version in a Gemfile line such as `ruby "3.2.2"`. Version constraints aren't supported.
+fn gemfile_version(gemfile: PathBuf) -> Option<InputVersion> {
+    let contents = std::fs::read_to_string(gemfile).ok()?;
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with("ruby ") || line.starts_with("ruby("))
+        .find_map(|line| {
+            let quoted = line.split(['"', '\'']).nth(1)?;
+            InputVersion::from_str(quoted).ok()
+        })
+}
+
+/// Prepares `program` of `version` to run with the version first on `PATH` and its own gems.
+pub fn ruby_command(
+    config: &FrumConfig,
+    version: &Version,
+    program: PathBuf,
+) -> std::process::Command {
+    let version_dir = config.versions_dir().join(version.to_string());
+    let binary_dir = if cfg!(windows) {
+        version_dir.clone()
+    } else {
+        version_dir.join("bin")
+    };
+    let path = std::env::join_paths(std::iter::once(binary_dir).chain(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    )))
+    .unwrap_or_default();
+    let mut command = std::process::Command::new(program);
+    command.env("PATH", path);
+    scope_gem_env(&mut command, &version_dir, version);
+    command
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_bundle_of_project_version() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.7.2", "3.2.2"] {
+            let bundle = binary_path(&config, &Version::parse(version).unwrap(), "bundle");
+            std::fs::create_dir_all(bundle.parent().unwrap()).unwrap();
+            std::fs::write(&bundle, "").unwrap();
+        }
+        let project_dir = tempdir().unwrap();
+        std::fs::write(
+            project_dir.path().join("Gemfile"),
+            "source \"https://rubygems.org\"\n\nruby '2.7.2'\ngem \"rails\"\n",
+        )
+        .unwrap();
+
+        let version = project_version(&config, project_dir.path()).unwrap();
+        assert_eq!(version, Version::parse("2.7.2").unwrap());
+        assert_eq!(
+            binary_path(&config, &version, "bundle"),
+            config
+                .versions_dir()
+                .join("2.7.2")
+                .join("bin")
+                .join("bundle")
+        );
+
+        std::fs::write(project_dir.path().join(".ruby-version"), "3.2\n").unwrap();
+        assert_eq!(
+            project_version(&config, project_dir.path()).unwrap(),
+            Version::parse("3.2.2").unwrap()
+        );
+    }
+}
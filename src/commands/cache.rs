@@ -0,0 +1,233 @@
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::outln;
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+        }
+    }
+}
+
+pub enum CacheAction {
+    List,
+    Clear { older_than: Option<Duration> },
+}
+
+pub struct Cache {
+    pub action: CacheAction,
+}
+
+/// A file or directory in the cache directory.
+#[derive(Debug, PartialEq)]
+pub struct CacheEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub age: Duration,
+}
+
+impl crate::command::Command for Cache {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        match self.action {
+            CacheAction::List => {
+                let entries = cache_entries(config)?;
+                for entry in &entries {
+                    outln!(
+                        config#Info,
+                        "{}\t{}\t{} ago",
+                        entry.path.file_name().unwrap_or_default().to_string_lossy(),
+                        format_size(entry.size),
+                        format_duration(entry.age)
+                    );
+                }
+                let total = entries.iter().map(|entry| entry.size).sum();
+                outln!(config#Info, "{} {} reclaimable", "==>".green(), format_size(total));
+            }
+            CacheAction::Clear { older_than } => {
+                let removed = clear_cache(config, older_than)?;
+                let total = removed.iter().map(|entry| entry.size).sum();
+                outln!(config#Info, "{} Removed {} cache entries, freeing {}", "==>".green(), removed.len(), format_size(total));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lists what is in the cache directory, sorted by name.
+pub fn cache_entries(config: &FrumConfig) -> std::io::Result<Vec<CacheEntry>> {
+    let now = SystemTime::now();
+    let mut entries = vec![];
+    for entry in config.cache_dir().read_dir()? {
+        let path = entry?.path();
+        let modified = path.metadata()?.modified()?;
+        entries.push(CacheEntry {
+            size: disk_size(&path)?,
+            age: now.duration_since(modified).unwrap_or_default(),
+            path,
+        });
+    }
+    entries.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(entries)
+}
+
+/// Removes the cache entries, or only the ones last modified before `older_than`, and returns them.
+pub fn clear_cache(
+    config: &FrumConfig,
+    older_than: Option<Duration>,
+) -> std::io::Result<Vec<CacheEntry>> {
+    let mut removed = vec![];
+    for entry in cache_entries(config)? {
+        if older_than.is_some_and(|older_than| entry.age < older_than) {
+            continue;
+        }
+        if entry.path.is_dir() {
+            std::fs::remove_dir_all(&entry.path)?;
+        } else {
+            std::fs::remove_file(&entry.path)?;
+        }
+        removed.push(entry);
+    }
+    Ok(removed)
+}
+
+/// The total size of the files under `path`, or of `path` itself if it isn't a directory.
+pub fn disk_size(path: &Path) -> std::io::Result<u64> {
+    // Walks with a stack instead of recursing, so deeply nested trees can't
+    // overflow it. Symlinks aren't followed, which also rules out cycles.
+    let mut size = 0;
+    let mut pending = vec![path.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let metadata = path.symlink_metadata()?;
+        if !metadata.is_dir() {
+            size += metadata.len();
+            continue;
+        }
+        for entry in path.read_dir()? {
+            pending.push(entry?.path());
+        }
+    }
+    Ok(size)
+}
+
+/// Parses durations such as `30m`, `12h`, `7d` or `2w`. A bare number is in seconds.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split_at = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split_at);
+    let amount = amount
+        .parse::<u64>()
+        .map_err(|_| format!("invalid duration: {}", value))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(format!("invalid duration unit: {}", unit)),
+    };
+    amount
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration: {}", value))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+pub fn format_size(size: u64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut size = size as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < units.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, units[unit])
+    } else {
+        format!("{:.1} {}", size, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_clear_only_old_entries() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let week_ago = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+        for (name, modified) in &[
+            ("index.txt", SystemTime::now()),
+            ("ruby-2.6.4.tar.xz", week_ago),
+            ("ruby-2.7.2.tar.xz", SystemTime::now()),
+        ] {
+            let path = config.cache_dir().join(name);
+            std::fs::write(&path, name).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(*modified)
+                .unwrap();
+        }
+
+        let removed = clear_cache(&config, Some(parse_duration("3d").unwrap())).unwrap();
+
+        assert_eq!(
+            removed
+                .iter()
+                .map(|entry| (entry.path.clone(), entry.size))
+                .collect::<Vec<_>>(),
+            vec![(config.cache_dir().join("ruby-2.6.4.tar.xz"), 17)]
+        );
+        assert_eq!(
+            cache_entries(&config)
+                .unwrap()
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>(),
+            vec![
+                config.cache_dir().join("index.txt"),
+                config.cache_dir().join("ruby-2.7.2.tar.xz"),
+            ]
+        );
+
+        clear_cache(&config, None).unwrap();
+        assert!(cache_entries(&config).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(7 * 86400)));
+        assert!(parse_duration("7y").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("99999999999999999w").is_err());
+    }
+}
@@ -1,16 +1,18 @@
 use crate::cli::build_cli;
-use crate::command::Command;
+use crate::command::{Command, ErrorKind};
 use crate::config::FrumConfig;
 use crate::outln;
-use crate::shell::{infer_shell, AVAILABLE_SHELLS};
-use crate::version::{is_dotfile, Version};
-use clap::Shell;
+use crate::shell::{infer_shell_or_assumed, AVAILABLE_SHELLS};
+use crate::version::Version;
+use clap::{App, Shell};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
 use thiserror::Error;
 
-const USE_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
-const INSTALL_COMMAND_REGEX: &str = r#"opts=" -l -h -V  --list --help --version  "#;
-const UNINSTALL_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
-const LOCAL_COMMAND_REGEX: &str = r#"opts=" -h -V  --help --version  "#;
+/// The file in the cache directory that keeps the last `completions --list` for `VERSION_LIST_TTL`.
+const VERSION_LIST_CACHE: &str = "completion-versions.txt";
+const VERSION_LIST_TTL: Duration = Duration::from_millis(500);
 
 #[derive(Debug)]
 enum FrumCommand {
@@ -34,74 +36,240 @@ pub enum FrumError {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
-    SemverError(#[from] semver::SemVerError),
+    VersionError(#[from] crate::version::Error),
+    #[error("The {shell} completions no longer complete versions. Missing: {missing}")]
+    CompletionsDrifted { shell: Shell, missing: String },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::CantInferShell => "CantInferShell",
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::CompletionsDrifted { .. } => "CompletionsDrifted",
+        }
+    }
+}
+
+/// The values of `--shell`: `auto`, which infers the shell like leaving it out does, and
+/// the shells clap generates completions for.
+pub const SHELL_VALUES: &[&str] = &["auto", "bash", "fish", "zsh", "powershell", "elvish"];
+
+/// The shell given with `--shell`, or `None` to infer it for `auto`.
+pub fn shell_arg(value: &str) -> Option<Shell> {
+    match value {
+        "auto" => None,
+        shell => Some(shell.parse().expect("invalid shell")),
+    }
 }
 
 pub struct Completions {
+    /// The shell to print the completions of, inferred when it isn't given.
     pub shell: Option<Shell>,
     pub list: bool,
+    /// Lists only the versions starting with the word being completed, which the shell passes.
+    pub starts_with: Option<String>,
+    pub check: bool,
+    pub output_dir: Option<PathBuf>,
+    pub dynamic_off: bool,
+    /// The version of the shell, such as `$BASH_VERSION`, to emit syntax it understands.
+    pub shell_version: Option<String>,
 }
 
+impl Completions {
+    /// The shell given with `--shell`, or the one frum runs in, `$SHELL` or `FRUM_SHELL`.
+    fn shell(&self, config: &FrumConfig) -> Result<Shell, FrumError> {
+        self.shell
+            .or_else(|| infer_shell_or_assumed(config).map(Into::into))
+            .ok_or(FrumError::CantInferShell)
+    }
+
+    /// The versions `--list` prints, all of them unless `--starts-with` is given.
+    fn listed_versions(&self, config: &FrumConfig) -> Result<Vec<String>, FrumError> {
+        let mut versions = version_list(config)?;
+        if let Some(prefix) = &self.starts_with {
+            versions.retain(|version| version.starts_with(prefix.as_str()));
+        }
+        Ok(versions)
+    }
+}
+
+/// The shells completions are packaged for, and the file names packaging systems such as Homebrew expect.
+const PACKAGED_COMPLETIONS: [(Shell, &str); 3] = [
+    (Shell::Zsh, "_frum"),
+    (Shell::Bash, "frum.bash"),
+    (Shell::Fish, "frum.fish"),
+];
+
 impl Command for Completions {
     type Error = FrumError;
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
         if self.list {
-            for entry in config
-                .versions_dir()
-                .read_dir()
-                .map_err(FrumError::IoError)?
-            {
-                let entry = entry.map_err(FrumError::IoError)?;
-                if is_dotfile(&entry) {
-                    continue;
-                }
-
-                let path = entry.path();
-                let filename = path
-                    .file_name()
-                    .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                    .map_err(FrumError::IoError)?
-                    .to_str()
-                    .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                    .map_err(FrumError::IoError)?;
-                let version = Version::parse(filename).map_err(FrumError::SemverError)?;
-                outln!(config#Info, "{} {}", " ", version);
+            for version in self.listed_versions(config)? {
+                outln!(config#Info, "{} {}", " ", quote(&version, self.shell));
+            }
+            return Ok(());
+        }
+        if let Some(output_dir) = &self.output_dir {
+            for path in write_completions(output_dir, self.dynamic_off)? {
+                outln!(config#Info, "{}", path.display());
             }
             return Ok(());
         }
 
-        let shell = self
-            .shell
-            .or_else(|| infer_shell().map(Into::into))
-            .ok_or(FrumError::CantInferShell)?;
+        let shell = self.shell(config)?;
 
-        print!("{}", customize_completions(shell));
+        let completions = completions(shell, self.dynamic_off);
+        if self.check {
+            let missing = missing_injections(shell, &completions);
+            if !missing.is_empty() {
+                return Err(FrumError::CompletionsDrifted {
+                    shell,
+                    missing: missing.join(", "),
+                });
+            }
+            outln!(config#Info, "The {} completions are up to date", shell);
+            return Ok(());
+        }
+        let shell_version = self
+            .shell_version
+            .clone()
+            .or_else(|| shell_version_var(shell))
+            .and_then(|version| parse_shell_version(&version));
+        print!("{}", compatible_syntax(shell, shell_version, completions));
         Ok(())
     }
 }
 
-fn customize_completions(shell: Shell) -> String {
+/// Lists the installed versions for completion. Shells run `completions --list` on
+/// every tab, so the list is reused for a moment unless the versions directory changes.
+fn version_list(config: &FrumConfig) -> Result<Vec<String>, FrumError> {
+    let versions_dir = config.versions_dir();
+    let stamp = versions_dir
+        .metadata()?
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string();
+    let cache = config.cache_dir().join(VERSION_LIST_CACHE);
+    if let Some(versions) = cached_version_list(&cache, &stamp) {
+        return Ok(versions);
+    }
+
+    let mut versions = vec![];
+    for entry in versions_dir.read_dir()? {
+        if let Some(version) = Version::from_dir_entry(&entry?)? {
+            versions.push(version);
+        }
+    }
+    versions.sort();
+    let versions = versions
+        .iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+    // The list is still right without the cache, so failing to write it doesn't matter
+    let contents = std::iter::once(&stamp)
+        .chain(&versions)
+        .map(|line| format!("{}\n", line))
+        .collect::<String>();
+    tempfile::NamedTempFile::new_in(config.cache_dir())
+        .and_then(|mut file| file.write_all(contents.as_bytes()).map(|_| file))
+        .ok()
+        .and_then(|file| file.persist(&cache).ok());
+    Ok(versions)
+}
+
+/// The versions in `cache` if it was written less than `VERSION_LIST_TTL` ago for
+/// the versions directory last modified at `stamp`.
+fn cached_version_list(cache: &Path, stamp: &str) -> Option<Vec<String>> {
+    let age = cache.metadata().ok()?.modified().ok()?.elapsed().ok()?;
+    if age > VERSION_LIST_TTL {
+        return None;
+    }
+    let contents = std::fs::read_to_string(cache).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != stamp {
+        return None;
+    }
+    Some(lines.map(String::from).collect())
+}
+
+/// Writes the completions of every packaged shell into `output_dir` and returns the written files.
+fn write_completions(output_dir: &Path, dynamic_off: bool) -> std::io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(output_dir)?;
+    let mut paths = vec![];
+    for (shell, file_name) in PACKAGED_COMPLETIONS.iter() {
+        let path = output_dir.join(file_name);
+        std::fs::write(&path, completions(*shell, dynamic_off))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+/// The completions for `shell`, completing versions by calling frum unless `dynamic_off` is set.
+fn completions(shell: Shell, dynamic_off: bool) -> String {
+    if dynamic_off {
+        clap_completions(shell)
+    } else {
+        customize_completions(shell)
+    }
+}
+
+/// The completions generated by clap, which complete versions with a `<version>` placeholder.
+fn clap_completions(shell: Shell) -> String {
+    clap_completions_of(build_cli(), shell)
+}
+
+fn clap_completions_of(mut app: App<'static, 'static>, shell: Shell) -> String {
     use std::io::BufWriter;
     let mut buffer = BufWriter::new(Vec::new());
-    build_cli().gen_completions_to(env!("CARGO_PKG_NAME"), shell, &mut buffer);
+    app.gen_completions_to(env!("CARGO_PKG_NAME"), shell, &mut buffer);
     let bytes = buffer.into_inner().unwrap();
-    let string = String::from_utf8(bytes).unwrap();
+    String::from_utf8(bytes).unwrap()
+}
+
+/// Matches the bash `opts=` line clap generates for `subcommand` of `app`, capturing
+/// everything before its positional arguments, which are where versions are completed.
+/// Only the positional arguments are spelled out, from the definition of the
+/// subcommand, so the pattern keeps matching whatever options are added.
+fn bash_opts_regex(app: &App, subcommand: &str) -> regex::Regex {
+    let positionals = app
+        .p
+        .subcommands
+        .iter()
+        .find(|app| app.p.meta.name == subcommand)
+        .map(|app| {
+            app.p
+                .positionals
+                .values()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    regex::Regex::new(&format!(
+        r#"^(\s+opts="[^"]*? ){} "$"#,
+        regex::escape(&positionals)
+    ))
+    .unwrap()
+}
+
+fn customize_completions(shell: Shell) -> String {
+    customize_completions_of(build_cli(), shell)
+}
+
+fn customize_completions_of(app: App<'static, 'static>, shell: Shell) -> String {
+    let use_command_regex = bash_opts_regex(&app, "global");
+    let install_command_regex = bash_opts_regex(&app, "install");
+    let uninstall_command_regex = bash_opts_regex(&app, "uninstall");
+    let local_command_regex = bash_opts_regex(&app, "local");
+    let string = clap_completions_of(app, shell);
     let string_split = string.split('\n');
     let mut completions = String::new();
     let mut subcommand = FrumCommand::None;
-    let use_command_regex =
-        regex::Regex::new(format!(r#"(\s+){}{} "#, USE_COMMAND_REGEX, "<version>").as_str())
-            .unwrap();
-    let install_command_regex =
-        regex::Regex::new(format!(r#"(\s+){}{} "#, INSTALL_COMMAND_REGEX, "<version>").as_str())
-            .unwrap();
-    let uninstall_command_regex =
-        regex::Regex::new(format!(r#"(\s+){}{} "#, UNINSTALL_COMMAND_REGEX, "<version>").as_str())
-            .unwrap();
-    let local_command_regex =
-        regex::Regex::new(format!(r#"(\s+){}{} "#, LOCAL_COMMAND_REGEX, "<version>").as_str())
-            .unwrap();
     match shell {
         Shell::Zsh => {
             for (index, line) in string_split.clone().enumerate() {
@@ -121,8 +289,8 @@ fn customize_completions(shell: Shell) -> String {
                         match subcommand {
                             FrumCommand::Local => match line {
                                 "(local)" => r#"(local)
-if [ "$(frum completions --list)" != '' ]; then
-    local_args='::version:_values 'version' $(frum completions --list)'
+if [ "$(frum completions --list --shell zsh)" != '' ]; then
+    local_args='::version:_values 'version' $(frum completions --list --shell zsh --starts-with="$PREFIX")'
 else
     local_args='--version[Prints version information]'
 fi"#
@@ -132,7 +300,7 @@ fi"#
                             },
                             FrumCommand::Global => match line {
                                 r#"':version:_files' \"# =>
-                                    r#"':version:_values 'version' $(frum completions --list)' \"#
+                                    r#"':version:_values 'version' $(frum completions --list --shell zsh --starts-with="$PREFIX")' \"#
                                         .to_string(),
                                 _ => line.to_string(),
                             },
@@ -146,7 +314,7 @@ fi"#
                             },
                             FrumCommand::Uninstall => match line {
                                 r#"':version:_files' \"# =>
-                                    r#"':version:_values 'version' $(frum completions --list)' \"#
+                                    r#"':version:_values 'version' $(frum completions --list --shell zsh --starts-with="$PREFIX")' \"#
                                         .to_string(),
                                 _ => line.to_string(),
                             },
@@ -171,6 +339,9 @@ fi"#
                     FrumCommand::Install
                 } else if line.ends_with("frum__uninstall)") {
                     FrumCommand::Uninstall
+                } else if line.trim_start().starts_with("frum__") && line.ends_with(')') {
+                    // The patterns match any subcommand taking a version, so stop at the next one
+                    FrumCommand::None
                 } else {
                     subcommand
                 };
@@ -181,14 +352,13 @@ fi"#
                             FrumCommand::Local =>
                                 if local_command_regex.is_match(line) {
                                     format!(
-                                        r#"{}{}$(frum completions --list) ""#,
+                                        r#"{}$(frum completions --list --shell bash --starts-with="${{cur}}") ""#,
                                         local_command_regex
                                             .captures(line)
                                             .unwrap()
                                             .get(1)
                                             .unwrap()
                                             .as_str(),
-                                        LOCAL_COMMAND_REGEX
                                     )
                                 } else {
                                     line.to_string()
@@ -196,14 +366,13 @@ fi"#
                             FrumCommand::Global =>
                                 if use_command_regex.is_match(line) {
                                     format!(
-                                        r#"{}{}$(frum completions --list) ""#,
+                                        r#"{}$(frum completions --list --shell bash --starts-with="${{cur}}") ""#,
                                         use_command_regex
                                             .captures(line)
                                             .unwrap()
                                             .get(1)
                                             .unwrap()
                                             .as_str(),
-                                        USE_COMMAND_REGEX
                                     )
                                 } else {
                                     line.to_string()
@@ -211,14 +380,13 @@ fi"#
                             FrumCommand::Install =>
                                 if install_command_regex.is_match(line) {
                                     format!(
-                                        r#"{}{}$(frum install -l) ""#,
+                                        r#"{}$(frum install -l) ""#,
                                         install_command_regex
                                             .captures(line)
                                             .unwrap()
                                             .get(1)
                                             .unwrap()
                                             .as_str(),
-                                        INSTALL_COMMAND_REGEX
                                     )
                                 } else {
                                     line.to_string()
@@ -226,14 +394,13 @@ fi"#
                             FrumCommand::Uninstall =>
                                 if uninstall_command_regex.is_match(line) {
                                     format!(
-                                        r#"{}{}$(frum completions --list) ""#,
+                                        r#"{}$(frum completions --list --shell bash --starts-with="${{cur}}") ""#,
                                         uninstall_command_regex
                                             .captures(line)
                                             .unwrap()
                                             .get(1)
                                             .unwrap()
                                             .as_str(),
-                                        UNINSTALL_COMMAND_REGEX
                                     )
                                 } else {
                                     line.to_string()
@@ -250,6 +417,99 @@ fi"#
     }
 }
 
+/// The variable the shell keeps its version in, if the shell exported it.
+fn shell_version_var(shell: Shell) -> Option<String> {
+    match shell {
+        Shell::Bash => std::env::var("BASH_VERSION").ok(),
+        Shell::Zsh => std::env::var("ZSH_VERSION").ok(),
+        _ => None,
+    }
+}
+
+/// The major and minor version in a shell version such as `5.1.16(1)-release` or `2.05b`.
+fn parse_shell_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.').map(|part| {
+        part.chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+    });
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().and_then(Result::ok).unwrap_or(0);
+    Some((major, minor))
+}
+
+/// Rewrites the syntax older shells don't understand, leaving the completions as they
+/// are for modern shells and when the version isn't known. Bash before 3.0 has no
+/// `bashdefault` option. zsh checks its own version with `is-at-least` in the completions.
+fn compatible_syntax(
+    shell: Shell,
+    shell_version: Option<(u32, u32)>,
+    completions: String,
+) -> String {
+    match (shell, shell_version) {
+        (Shell::Bash, Some(version)) if version < (3, 0) => {
+            completions.replace(" -o bashdefault -o default ", " -o default ")
+        }
+        _ => completions,
+    }
+}
+
+/// Lists the version completions that `customize_completions` should have injected
+/// but didn't, which happens when the CLI changes and the patterns above don't match anymore.
+fn missing_injections(shell: Shell, completions: &str) -> Vec<&'static str> {
+    let injections: &[(&str, usize)] = match shell {
+        Shell::Zsh => &[
+            (
+                r#"local_args='::version:_values 'version' $(frum completions --list --shell zsh --starts-with="$PREFIX")'"#,
+                1,
+            ),
+            (
+                r#"':version:_values 'version' $(frum completions --list --shell zsh --starts-with="$PREFIX")'"#,
+                2,
+            ),
+            ("'::version:_values 'version' $(frum install -l)'", 1),
+        ],
+        Shell::Bash => &[
+            (
+                r#"$(frum completions --list --shell bash --starts-with="${cur}") ""#,
+                3,
+            ),
+            ("$(frum install -l) \"", 1),
+        ],
+        _ => &[],
+    };
+    injections
+        .iter()
+        .filter(|(injection, count)| completions.matches(injection).count() < *count)
+        .map(|(injection, _)| *injection)
+        .collect()
+}
+
+/// Quotes a completion candidate so that the given shell reads it as a single word.
+fn quote(token: &str, shell: Option<Shell>) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_.+".contains(c);
+    match shell {
+        Some(_) if token.chars().all(is_safe) => token.to_string(),
+        Some(Shell::Bash) => format!("'{}'", token.replace('\'', r#"'\''"#)),
+        Some(Shell::Zsh) => token
+            .chars()
+            .map(|c| {
+                if is_safe(c) {
+                    c.to_string()
+                } else {
+                    format!("\\{}", c)
+                }
+            })
+            .collect(),
+        Some(Shell::Fish) => format!("'{}'", token.replace('\\', r"\\").replace('\'', r"\'")),
+        Some(Shell::PowerShell) | Some(Shell::Elvish) => {
+            format!("'{}'", token.replace('\'', "''"))
+        }
+        None => token.to_string(),
+    }
+}
+
 fn shells_as_string() -> String {
     AVAILABLE_SHELLS
         .iter()
@@ -260,7 +520,15 @@ fn shells_as_string() -> String {
 
 #[cfg(test)]
 mod test {
-    use super::customize_completions;
+    use super::{
+        clap_completions, compatible_syntax, completions, customize_completions,
+        customize_completions_of, missing_injections, parse_shell_version, quote, shell_arg,
+        version_list, write_completions, Completions, VERSION_LIST_CACHE, VERSION_LIST_TTL,
+    };
+    use crate::cli::build_cli;
+    use crate::config::FrumConfig;
+    use crate::shell::infer_shell_or_assumed;
+    use clap::Arg;
     use clap::Shell;
     use difference::assert_diff;
     use std::fs::File;
@@ -286,4 +554,199 @@ mod test {
         let actual = customize_completions(Shell::Bash);
         assert_diff!(actual.as_str(), expected.as_str(), "\n", 0);
     }
+
+    #[test]
+    fn test_completions_inject_versions() {
+        for shell in &[Shell::Zsh, Shell::Bash] {
+            assert_eq!(
+                missing_injections(*shell, &customize_completions(*shell)),
+                Vec::<&str>::new()
+            );
+        }
+    }
+
+    #[test]
+    fn test_completions_inject_versions_after_new_options() {
+        let mut app = build_cli();
+        for subcommand in app.p.subcommands.iter_mut() {
+            *subcommand = subcommand.clone().arg(Arg::with_name("stub").long("stub"));
+        }
+
+        let completions = customize_completions_of(app, Shell::Bash);
+        assert!(completions.contains("--stub"));
+        assert_eq!(
+            missing_injections(Shell::Bash, &completions),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn test_detect_uncustomized_completions() {
+        assert_eq!(
+            missing_injections(Shell::Bash, &clap_completions(Shell::Bash)).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_dynamic_off_skips_version_substitution() {
+        for shell in &[Shell::Zsh, Shell::Bash, Shell::Fish] {
+            let completions = completions(*shell, true);
+            assert!(!completions.contains("$(frum"));
+            assert_eq!(completions, clap_completions(*shell));
+        }
+        assert!(completions(Shell::Bash, false).contains("$(frum completions --list"));
+    }
+
+    #[test]
+    fn test_legacy_bash_syntax_for_old_versions() {
+        let modern = customize_completions(Shell::Bash);
+        assert!(modern.contains("complete -F _frum -o bashdefault -o default frum"));
+
+        let legacy = compatible_syntax(
+            Shell::Bash,
+            parse_shell_version("2.05b.0(1)-release"),
+            modern.clone(),
+        );
+        assert!(legacy.contains("complete -F _frum -o default frum"));
+        assert!(!legacy.contains("bashdefault"));
+        for version in &[None, parse_shell_version("5.1.16(1)-release")] {
+            assert_eq!(
+                compatible_syntax(Shell::Bash, *version, modern.clone()),
+                modern
+            );
+        }
+        assert_eq!(parse_shell_version("5.8"), Some((5, 8)));
+        assert_eq!(parse_shell_version("unknown"), None);
+    }
+
+    #[test]
+    fn test_write_completions_with_packaging_names() {
+        let output_dir = tempfile::tempdir().unwrap();
+
+        let paths = write_completions(&output_dir.path().join("completions"), false).unwrap();
+
+        let names = paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["_frum", "frum.bash", "frum.fish"]);
+        assert_eq!(
+            std::fs::read_to_string(&paths[1]).unwrap(),
+            customize_completions(Shell::Bash)
+        );
+    }
+
+    #[test]
+    fn test_shell_auto_infers_the_shell() {
+        let config = FrumConfig {
+            assume_shell: Some("fish".to_string()),
+            ..FrumConfig::default()
+        };
+        let completions_with = |args: &[&str]| {
+            let matches = build_cli().get_matches_from(args);
+            let (_, sub_matches) = matches.subcommand();
+            Completions {
+                shell: sub_matches.unwrap().value_of("shell").and_then(shell_arg),
+                list: false,
+                starts_with: None,
+                check: false,
+                output_dir: None,
+                dynamic_off: false,
+                shell_version: None,
+            }
+        };
+        let inferred =
+            infer_shell_or_assumed(&config).map(|shell| shell.as_clap_shell().to_string());
+
+        let auto = completions_with(&["frum", "completions", "--shell", "auto"]);
+        assert!(auto.shell.is_none());
+        assert_eq!(
+            auto.shell(&config).ok().map(|shell| shell.to_string()),
+            inferred
+        );
+        let explicit = completions_with(&["frum", "completions", "--shell", "zsh"]);
+        assert!(matches!(explicit.shell(&config).unwrap(), Shell::Zsh));
+        assert!(build_cli()
+            .get_matches_from_safe(["frum", "completions", "--shell", "tcsh"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_quote_plain_version() {
+        assert_eq!(quote("2.7.1", Some(Shell::Bash)), "2.7.1");
+        assert_eq!(quote("2.7.1", Some(Shell::Zsh)), "2.7.1");
+        assert_eq!(quote("my alias", None), "my alias");
+    }
+
+    #[test]
+    fn test_quote_alias_with_space() {
+        assert_eq!(quote("my alias", Some(Shell::Bash)), "'my alias'");
+        assert_eq!(quote("my alias", Some(Shell::Zsh)), r"my\ alias");
+        assert_eq!(quote("it's", Some(Shell::Bash)), r#"'it'\''s'"#);
+    }
+
+    #[test]
+    fn test_list_versions_starting_with_the_word() {
+        let config = FrumConfig::default().with_base_dir(tempfile::tempdir().unwrap().path());
+        for version in &["2.7.2", "3.1.0", "3.1.4", "3.2.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let listed = |args: &[&str]| {
+            let matches = build_cli().get_matches_from(args);
+            let (_, sub_matches) = matches.subcommand();
+            let sub_matches = sub_matches.unwrap();
+            Completions {
+                shell: None,
+                list: sub_matches.is_present("list"),
+                starts_with: sub_matches.value_of("starts-with").map(String::from),
+                check: false,
+                output_dir: None,
+                dynamic_off: false,
+                shell_version: None,
+            }
+            .listed_versions(&config)
+            .unwrap()
+        };
+
+        assert_eq!(
+            listed(&["frum", "completions", "--list", "--starts-with", "3.1"]),
+            vec!["3.1.0", "3.1.4"]
+        );
+        assert_eq!(
+            listed(&["frum", "completions", "--list", "--starts-with="]),
+            vec!["2.7.2", "3.1.0", "3.1.4", "3.2.2"]
+        );
+        assert_eq!(
+            listed(&["frum", "completions", "--list"]),
+            vec!["2.7.2", "3.1.0", "3.1.4", "3.2.2"]
+        );
+        assert!(listed(&["frum", "completions", "--list", "--starts-with=--fo"]).is_empty());
+    }
+
+    #[test]
+    fn test_version_list_is_reused_until_versions_change() {
+        let config = FrumConfig::default().with_base_dir(tempfile::tempdir().unwrap().path());
+        std::fs::create_dir_all(config.versions_dir().join("2.7.2")).unwrap();
+        assert_eq!(version_list(&config).unwrap(), vec!["2.7.2"]);
+
+        // A rescan wouldn't find 9.9.9, so listing it means the cache was used
+        let cache = config.cache_dir().join(VERSION_LIST_CACHE);
+        let scanned = std::fs::read_to_string(&cache).unwrap();
+        std::fs::write(&cache, scanned.replace("2.7.2", "9.9.9")).unwrap();
+        assert_eq!(version_list(&config).unwrap(), vec!["9.9.9"]);
+
+        std::fs::File::options()
+            .write(true)
+            .open(&cache)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - VERSION_LIST_TTL * 2)
+            .unwrap();
+        assert_eq!(version_list(&config).unwrap(), vec!["2.7.2"]);
+
+        std::fs::create_dir_all(config.versions_dir().join("3.0.0")).unwrap();
+        let mut versions = version_list(&config).unwrap();
+        versions.sort();
+        assert_eq!(versions, vec!["2.7.2", "3.0.0"]);
+    }
 }
@@ -0,0 +1,78 @@
+use crate::alias::find_aliases;
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::json::JsonEnvelope;
+use crate::outln;
+use crate::version::installed_versions;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+        }
+    }
+}
+
+pub struct Export {
+    /// Where to write the version set, instead of printing it.
+    pub file: Option<PathBuf>,
+}
+
+/// The installed versions, aliases and global version, as written by `frum export`
+/// and read by `frum import`.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct VersionSet {
+    pub versions: Vec<String>,
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub global: Option<String>,
+}
+
+impl crate::command::Command for Export {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let json = JsonEnvelope::new(version_set(config)?).to_string_pretty();
+        match &self.file {
+            Some(file) => std::fs::write(file, format!("{}\n", json))?,
+            None => outln!(config#Info, "{}", json),
+        }
+        Ok(())
+    }
+}
+
+pub fn version_set(config: &FrumConfig) -> Result<VersionSet, FrumError> {
+    let mut version_set = VersionSet {
+        versions: installed_versions(config)?
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+        ..Default::default()
+    };
+    for alias in find_aliases(config)? {
+        // The default alias is the global version
+        if alias.name() == "default" {
+            version_set.global = Some(alias.s_ver().to_string());
+        } else {
+            version_set
+                .aliases
+                .insert(alias.name().to_string(), alias.s_ver().to_string());
+        }
+    }
+    Ok(version_set)
+}
@@ -1,9 +1,20 @@
 use crate::alias::create_alias;
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
 use crate::input_version::InputVersion;
+use crate::outln;
+use crate::symlink::remove_symlink_dir;
 use crate::version::Version;
+use colored::Colorize;
 use log::debug;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use thiserror::Error;
 
+/// How many global selections are remembered for `--rollback`.
+const GLOBAL_HISTORY_LENGTH: usize = 10;
+
 #[derive(Error, Debug)]
 pub enum FrumError {
     #[error(transparent)]
@@ -12,38 +23,147 @@ pub enum FrumError {
     IoError(#[from] std::io::Error),
     #[error("Requested version {version} is not currently installed")]
     VersionNotFound { version: InputVersion },
+    #[error("{} is read-only. Set FRUM_DIR to a directory you can write to, or run frum as the owner of this one, e.g. with sudo for a shared installation.", path.display())]
+    ReadOnlyFrumDir { path: PathBuf },
+    #[error("There is no previous global version to roll back to")]
+    NoPreviousGlobal,
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::ReadOnlyFrumDir { .. } => "ReadOnlyFrumDir",
+            Self::NoPreviousGlobal => "NoPreviousGlobal",
+        }
+    }
 }
 
+#[derive(Default)]
 pub struct Global {
-    pub version: InputVersion,
+    pub version: Option<InputVersion>,
+    pub unset: bool,
+    /// Selects the global version selected before the current one again.
+    pub rollback: bool,
 }
 
 impl crate::command::Command for Global {
     type Error = FrumError;
 
-    fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
-        debug!("Use {} as the default version", &self.version);
-        let version = match self.version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
-            version => return Err(FrumError::VersionNotFound { version }),
-        };
-        if !&config
-            .versions_dir()
-            .join(self.version.to_string())
-            .exists()
-        {
-            return Err(FrumError::VersionNotFound {
-                version: self.version.clone(),
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if !crate::config::is_writable(&config.aliases_dir()) {
+            return Err(FrumError::ReadOnlyFrumDir {
+                path: config.aliases_dir(),
             });
         }
-        create_alias(&config, "default", &version).map_err(FrumError::IoError)?;
-        Ok(())
+        if self.rollback {
+            return rollback(config);
+        }
+        let version = match (&self.version, self.unset) {
+            (Some(version), false) => version.clone(),
+            _ => InputVersion::Full(Version::System),
+        };
+        let previous = current_global(config);
+        set_global(config, &version)?;
+        record_global(config, &previous, &version.to_string())
     }
 }
 
+/// Makes `input_version` the default version, or removes the default version for `system`.
+fn set_global(config: &FrumConfig, input_version: &InputVersion) -> Result<(), FrumError> {
+    if input_version == &InputVersion::Full(Version::System) {
+        // Without a default version, the Ruby found on PATH after frum's is used,
+        // which is also what `global system` selects
+        let default_version_dir = config.default_version_dir();
+        if default_version_dir.symlink_metadata().is_ok() {
+            debug!("Remove {}", default_version_dir.display());
+            remove_symlink_dir(&default_version_dir).map_err(FrumError::IoError)?;
+        }
+        return Ok(());
+    }
+    debug!("Use {} as the default version", input_version);
+    let version = match input_version.clone() {
+        InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
+        version => return Err(FrumError::VersionNotFound { version }),
+    };
+    if !&config
+        .versions_dir()
+        .join(input_version.to_string())
+        .exists()
+    {
+        return Err(FrumError::VersionNotFound {
+            version: input_version.clone(),
+        });
+    }
+    create_alias(config, "default", &version).map_err(FrumError::IoError)?;
+    Ok(())
+}
+
+/// The version the default alias points at, or `system` without one.
+fn current_global(config: &FrumConfig) -> String {
+    std::fs::canonicalize(config.default_version_dir())
+        .ok()
+        .and_then(|dir| dir.file_name()?.to_str().map(String::from))
+        .unwrap_or_else(|| Version::System.to_string())
+}
+
+/// Appends `version` to the history of global selections. The first entry is the global
+/// version `previous` selected before the history was kept, so that it can be rolled back to.
+fn record_global(config: &FrumConfig, previous: &str, version: &str) -> Result<(), FrumError> {
+    let path = config.global_history_file();
+    let now = chrono::Utc::now().to_rfc3339();
+    let mut history = read_history(&path);
+    if history.is_empty() {
+        history.push((now.clone(), previous.to_string()));
+    }
+    if history.last().map(|(_, last)| last.as_str()) != Some(version) {
+        history.push((now, version.to_string()));
+    }
+    let overflow = history.len().saturating_sub(GLOBAL_HISTORY_LENGTH);
+    history.drain(..overflow);
+    write_history(&path, &history)
+}
+
+/// Selects the global version before the current one in the history again, and forgets the current one.
+fn rollback(config: &FrumConfig) -> Result<(), FrumError> {
+    let path = config.global_history_file();
+    let mut history = read_history(&path);
+    if history.len() < 2 {
+        return Err(FrumError::NoPreviousGlobal);
+    }
+    history.pop();
+    let (selected_at, version) = history.last().cloned().unwrap();
+    let version = InputVersion::from_str(&version).map_err(|_| FrumError::NoPreviousGlobal)?;
+    set_global(config, &version)?;
+    write_history(&path, &history)?;
+    outln!(config#Info, "{} Rolled back the global version to {} (selected at {})", "==>".green(), version.to_string().green(), selected_at);
+    Ok(())
+}
+
+/// The global selections in `path`, from the oldest, as their time and version.
+/// Lines that aren't a tab-separated time and version are skipped.
+fn read_history(path: &Path) -> Vec<(String, String)> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(selected_at, version)| (selected_at.to_string(), version.to_string()))
+        .collect()
+}
+
+fn write_history(path: &Path, history: &[(String, String)]) -> Result<(), FrumError> {
+    let contents = history
+        .iter()
+        .map(|(selected_at, version)| format!("{}\t{}\n", selected_at, version))
+        .collect::<String>();
+    std::fs::write(path, contents).map_err(FrumError::IoError)
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Global;
+    use super::{read_history, FrumError, Global, GLOBAL_HISTORY_LENGTH};
     use crate::command::Command;
     use crate::config::FrumConfig;
     use crate::input_version::InputVersion;
@@ -53,21 +173,22 @@ mod tests {
 
     #[test]
     fn test_global_specified_version_success() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            frum_path: Some(std::env::temp_dir().join(format!(
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
                 "frum_{}_{}",
                 std::process::id(),
                 chrono::Utc::now().timestamp_millis(),
-            ))),
-            ..Default::default()
-        };
+            )));
         let dir_path = config.versions_dir().join("2.6.4").join("bin");
         std::fs::create_dir_all(&dir_path).unwrap();
         File::create(dir_path.join("ruby")).unwrap();
 
         Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.6.4").unwrap())),
+            version: Some(InputVersion::Full(Version::Semver(
+                semver::Version::parse("2.6.4").unwrap(),
+            ))),
+            ..Default::default()
         }
         .apply(&config)
         .expect("failed to install");
@@ -79,27 +200,116 @@ mod tests {
             .exists());
     }
 
+    #[test]
+    fn test_global_unset() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        std::fs::create_dir_all(config.versions_dir().join("2.6.4").join("bin")).unwrap();
+        Global {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        assert!(config.default_version_dir().exists());
+
+        for _ in 0..2 {
+            Global {
+                unset: true,
+                ..Default::default()
+            }
+            .apply(&config)
+            .unwrap();
+            assert!(config.default_version_dir().symlink_metadata().is_err());
+        }
+        assert!(config.versions_dir().join("2.6.4").exists());
+
+        Global {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        Global {
+            version: Some(InputVersion::Full(Version::System)),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        assert!(config.default_version_dir().symlink_metadata().is_err());
+    }
+
+    #[test]
+    fn test_global_rollback() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.6.4", "2.7.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version).join("bin")).unwrap();
+        }
+        let global = |version: &str| {
+            Global {
+                version: Some(version.parse().unwrap()),
+                ..Default::default()
+            }
+            .apply(&config)
+            .unwrap()
+        };
+        let rollback = || {
+            Global {
+                rollback: true,
+                ..Default::default()
+            }
+            .apply(&config)
+        };
+        let default_version = || {
+            std::fs::canonicalize(config.default_version_dir())
+                .ok()
+                .map(|dir| dir.file_name().unwrap().to_str().unwrap().to_string())
+        };
+
+        global("2.6.4");
+        global("2.7.2");
+        assert_eq!(default_version().as_deref(), Some("2.7.2"));
+
+        rollback().unwrap();
+        assert_eq!(default_version().as_deref(), Some("2.6.4"));
+        // No global version was selected before the history started
+        rollback().unwrap();
+        assert_eq!(default_version(), None);
+        assert!(matches!(rollback(), Err(FrumError::NoPreviousGlobal)));
+
+        for _ in 0..GLOBAL_HISTORY_LENGTH {
+            global("2.6.4");
+            global("2.7.2");
+        }
+        let history = read_history(&config.global_history_file());
+        assert_eq!(history.len(), GLOBAL_HISTORY_LENGTH);
+        assert!(history
+            .iter()
+            .all(|(selected_at, _)| chrono::DateTime::parse_from_rfc3339(selected_at).is_ok()));
+        assert_eq!(history.last().unwrap().1, "2.7.2");
+    }
+
     #[test]
     fn test_global_specified_version_failure() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            frum_path: Some(std::env::temp_dir().join(format!(
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
                 "frum_{}_{}",
                 std::process::id(),
                 chrono::Utc::now().timestamp_millis(),
-            ))),
-            ..Default::default()
-        };
+            )));
         let dir_path = config.versions_dir().join("2.6.4").join("bin");
         std::fs::create_dir_all(&dir_path).unwrap();
         File::create(dir_path.join("ruby")).unwrap();
 
         let result = Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.7.0").unwrap())),
+            version: Some(InputVersion::Full(Version::Semver(
+                semver::Version::parse("2.7.0").unwrap(),
+            ))),
+            ..Default::default()
         }
         .apply(&config);
         match result {
-            Ok(()) => assert!(false, "global must return an error"),
+            Ok(()) => panic!("global must return an error"),
             Err(e) => assert_eq!(
                 e.to_string(),
                 "Requested version 2.7.0 is not currently installed"
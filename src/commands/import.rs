@@ -0,0 +1,189 @@
+use crate::alias::create_alias;
+use crate::command::{run_all, Command, ErrorKind};
+use crate::commands::export::VersionSet;
+use crate::commands::global::Global;
+use crate::commands::install::{FrumError as InstallError, Install};
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{installed_versions, Version};
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error(transparent)]
+    InstallError(#[from] crate::commands::install::FrumError),
+    #[error(transparent)]
+    GlobalError(#[from] crate::commands::global::FrumError),
+    #[error("Can't read the version set in {}: {source}", file.display())]
+    InvalidVersionSet {
+        file: PathBuf,
+        source: serde_json::Error,
+    },
+    #[error("The version set lists {version}, which isn't a Ruby version")]
+    InvalidVersion { version: String },
+    #[error("Can't install the versions of the version set: {summary}")]
+    InstallsFailed { summary: String },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::InstallError(err) => err.kind(),
+            Self::GlobalError(err) => err.kind(),
+            Self::InvalidVersionSet { .. } => "InvalidVersionSet",
+            Self::InvalidVersion { .. } => "InvalidVersion",
+            Self::InstallsFailed { .. } => "InstallsFailed",
+        }
+    }
+}
+
+pub struct Import {
+    pub file: PathBuf,
+    pub dry_run: bool,
+}
+
+impl crate::command::Command for Import {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let contents = std::fs::read_to_string(&self.file)?;
+        let version_set: VersionSet =
+            serde_json::from_str(&contents).map_err(|source| FrumError::InvalidVersionSet {
+                file: self.file.clone(),
+                source,
+            })?;
+        let parse = |version: &String| {
+            Version::parse(version).map_err(|_| FrumError::InvalidVersion {
+                version: version.clone(),
+            })
+        };
+
+        let installed = installed_versions(config)?;
+        let mut missing = vec![];
+        for version in version_set.versions.iter().map(parse) {
+            let version = version?;
+            if installed.contains(&version) {
+                continue;
+            }
+            if let Version::Dev(_) = version {
+                outln!(config#Error, "{} Ruby {} was built from source and can't be installed again", "warning:".yellow().bold(), version);
+            } else if self.dry_run {
+                outln!(config#Info, "Ruby {} would be installed", version);
+            } else {
+                missing.push(version);
+            }
+        }
+        // Every missing version is tried before giving up on the ones that failed
+        let installs = missing
+            .iter()
+            .map(|version| -> Box<dyn Command<Error = InstallError>> {
+                Box::new(Install {
+                    version: Some(InputVersion::Full(version.clone())),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+        let batch = run_all(&installs, config);
+        if !batch.is_ok() {
+            for (version, result) in missing.iter().zip(&batch.results) {
+                if let Err(err) = result {
+                    outln!(config#Error, "{} Ruby {}: {}", "error:".red().bold(), version, err);
+                }
+            }
+            return Err(FrumError::InstallsFailed {
+                summary: batch.summary(),
+            });
+        }
+
+        let installed = installed_versions(config)?;
+        for (name, version) in &version_set.aliases {
+            let version = parse(version)?;
+            if self.dry_run {
+                outln!(config#Info, "The alias {} would point at {}", name, version);
+            } else if installed.contains(&version) {
+                create_alias(config, name, &version)?;
+            } else {
+                outln!(config#Error, "{} Skipping the alias {}: Ruby {} isn't installed", "warning:".yellow().bold(), name, version);
+            }
+        }
+        if let Some(global) = &version_set.global {
+            let version = parse(global)?;
+            if self.dry_run {
+                outln!(config#Info, "Ruby {} would be the global version", version);
+            } else {
+                Global {
+                    version: Some(InputVersion::Full(version)),
+                    ..Default::default()
+                }
+                .apply(config)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::commands::export::{version_set, Export};
+    use crate::commands::install::tests::serve_stub_ruby;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_export_and_import_round_trip() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        for version in &["2.6.4", "3.2.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version).join("bin")).unwrap();
+        }
+        let old = Version::parse("2.6.4").unwrap();
+        let new = Version::parse("3.2.2").unwrap();
+        create_alias(&config, "legacy", &old).unwrap();
+        create_alias(&config, "default", &new).unwrap();
+        let file = base_dir.path().join("rubies.json");
+        Export {
+            file: Some(file.clone()),
+        }
+        .apply(&config)
+        .unwrap();
+        let exported = version_set(&config).unwrap();
+        assert_eq!(exported.versions, vec!["2.6.4", "3.2.2"]);
+        assert_eq!(exported.global.as_deref(), Some("3.2.2"));
+
+        std::fs::remove_dir_all(config.versions_dir().join("2.6.4")).unwrap();
+        std::fs::remove_dir_all(config.aliases_dir()).unwrap();
+        Import {
+            file: file.clone(),
+            dry_run: true,
+        }
+        .apply(&config)
+        .unwrap();
+        assert_eq!(installed_versions(&config).unwrap(), vec![new.clone()]);
+
+        Import {
+            file,
+            dry_run: false,
+        }
+        .apply(&config)
+        .unwrap();
+        assert_eq!(version_set(&config).unwrap(), exported);
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+}
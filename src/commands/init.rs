@@ -1,6 +1,9 @@
-use crate::shell::infer_shell;
+use crate::command::ErrorKind;
+use crate::commands::local::resolution_cache_path;
+use crate::shell::infer_shell_or_assumed;
 use crate::shell::Shell;
-use crate::symlink::create_symlink_dir;
+use crate::symlink::{create_symlink_dir, remove_symlink_dir};
+use log::debug;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,14 +16,47 @@ pub enum FrumError {
     CantInferShell,
 }
 
-pub struct Init {}
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::CantInferShell => "CantInferShell",
+        }
+    }
+}
+
+pub struct Init {
+    pub path_only: bool,
+}
 
 impl crate::command::Command for Init {
     type Error = FrumError;
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
-        let shell: Box<dyn Shell> = infer_shell().ok_or(FrumError::CantInferShell)?;
-        let frum_path = create_symlink(&config);
+        let shell: Box<dyn Shell> =
+            infer_shell_or_assumed(config).ok_or(FrumError::CantInferShell)?;
+        // Without the session symlink and the cd hook, the default version is the only one on PATH
+        if self.path_only {
+            let default_version_dir = config.default_version_dir();
+            let binary_path = if cfg!(windows) {
+                default_version_dir
+            } else {
+                default_version_dir.join("bin")
+            };
+            println!("{}", shell.path(&binary_path));
+            return Ok(());
+        }
+        if let Err(err) = remove_stale_symlinks(config) {
+            debug!("Can't remove stale multishell symlinks: {}", err);
+        }
+        // A subshell started by `frum shell` keeps its version
+        let frum_path = match &config.shell_version {
+            Some(version) => {
+                create_symlink(config, &config.versions_dir().join(version.to_string()))?
+            }
+            None => create_symlink(config, &config.default_version_dir())?,
+        };
         let binary_path = if cfg!(windows) {
             frum_path.clone()
         } else {
@@ -43,28 +79,140 @@ impl crate::command::Command for Init {
             "{}",
             shell.set_env_var("FRUM_RUBY_BUILD_MIRROR", config.ruby_build_mirror.as_str())
         );
-        println!("{}", shell.use_on_cd(&config));
+        println!("{}", shell.use_on_cd(config));
         Ok(())
     }
 }
 
-fn create_symlink(config: &crate::config::FrumConfig) -> std::path::PathBuf {
-    let system_temp_dir = std::env::temp_dir();
-    let mut temp_dir = generate_symlink_path(&system_temp_dir);
+/// Creates a new session symlink pointing at `target`.
+pub fn create_symlink(
+    config: &crate::config::FrumConfig,
+    target: &std::path::Path,
+) -> std::io::Result<std::path::PathBuf> {
+    let multishells_dir = config.multishells_dir()?;
+    let mut temp_dir = generate_symlink_path(&multishells_dir);
 
-    while temp_dir.exists() {
-        temp_dir = generate_symlink_path(&system_temp_dir);
+    // The symlink dangles until a default version is installed, so don't follow it
+    while temp_dir.symlink_metadata().is_ok() {
+        temp_dir = generate_symlink_path(&multishells_dir);
     }
 
-    create_symlink_dir(config.default_version_dir(), &temp_dir).expect("Can't create symlink!");
-    temp_dir
+    create_symlink_dir(target, &temp_dir)?;
+    Ok(temp_dir)
 }
 
 fn generate_symlink_path(root: &std::path::Path) -> std::path::PathBuf {
     let temp_dir_name = format!(
-        "frum_{}_{}",
-        std::process::id(),
+        "{}_{}",
+        session_pid(),
         chrono::Utc::now().timestamp_millis(),
     );
     root.join(temp_dir_name)
 }
+
+/// The process that evaluates the output of `frum init`, which owns the session.
+#[cfg(unix)]
+fn session_pid() -> u32 {
+    std::os::unix::process::parent_id()
+}
+
+#[cfg(windows)]
+fn session_pid() -> u32 {
+    std::process::id()
+}
+
+#[cfg(unix)]
+fn is_process_alive(pid: u32) -> bool {
+    // Signal 0 only checks whether the process exists. EPERM means that
+    // it exists but belongs to another user.
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(windows)]
+fn is_process_alive(_pid: u32) -> bool {
+    true
+}
+
+/// Removes the symlinks of sessions whose shell has already exited, returning them.
+pub(crate) fn remove_stale_symlinks(
+    config: &crate::config::FrumConfig,
+) -> std::io::Result<Vec<std::path::PathBuf>> {
+    let mut removed = vec![];
+    let session_regex = regex::Regex::new(r"^(\d+)_\d+$").unwrap();
+    for entry in config.multishells_dir()?.read_dir()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_symlink() {
+            continue;
+        }
+        let pid = match entry
+            .file_name()
+            .to_str()
+            .and_then(|name| session_regex.captures(name))
+            .and_then(|captures| captures[1].parse::<u32>().ok())
+        {
+            Some(pid) => pid,
+            None => continue,
+        };
+        if !is_process_alive(pid) {
+            debug!("Remove stale multishell symlink {}", entry.path().display());
+            remove_symlink_dir(entry.path())?;
+            std::fs::remove_file(resolution_cache_path(&entry.path())).ok();
+            removed.push(entry.path());
+        }
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{create_symlink, remove_stale_symlinks};
+    use crate::config::FrumConfig;
+    use crate::symlink::create_symlink_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_create_symlink_per_session() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_multishells_dir(base_dir.path().join("multishells"));
+        let first_session = create_symlink(&config, &config.default_version_dir()).unwrap();
+        let second_session = create_symlink(&config, &config.default_version_dir()).unwrap();
+
+        assert_ne!(first_session, second_session);
+        for session in &[&first_session, &second_session] {
+            assert_eq!(
+                session.parent(),
+                Some(base_dir.path().join("multishells").as_path())
+            );
+            assert!(session
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with(&format!("{}_", super::session_pid())));
+        }
+    }
+
+    #[test]
+    fn test_remove_stale_symlinks() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_multishells_dir(base_dir.path().join("multishells"));
+        let multishells_dir = config.multishells_dir().unwrap();
+        let stale_session = multishells_dir.join(format!("{}_1", i32::MAX));
+        let live_session = multishells_dir.join(format!("{}_1", std::process::id()));
+        let foreign_file = multishells_dir.join(format!("{}_1.txt", i32::MAX));
+        create_symlink_dir(config.default_version_dir(), &stale_session).unwrap();
+        create_symlink_dir(config.default_version_dir(), &live_session).unwrap();
+        create_symlink_dir(config.default_version_dir(), &foreign_file).unwrap();
+
+        remove_stale_symlinks(&config).unwrap();
+
+        assert!(stale_session.symlink_metadata().is_err());
+        assert!(live_session.symlink_metadata().is_ok());
+        assert!(foreign_file.symlink_metadata().is_ok());
+    }
+}
@@ -1,18 +1,30 @@
 use crate::alias::create_alias;
 use crate::archive::{self, extract::Error as ExtractError, extract::Extract};
+use crate::checkpoint::Checkpoint;
+use crate::command::{version_or_inferred, ErrorKind};
+use crate::commands::bundle::ruby_command;
+use crate::commands::which::binary_path;
 use crate::config::FrumConfig;
+use crate::fetch::fetch;
 use crate::input_version::InputVersion;
+use crate::json::JsonEnvelope;
+use crate::mirror::select_mirror;
 use crate::outln;
-use crate::version::Version;
-use crate::version_file::get_user_version_for_directory;
+use crate::project_config::ProjectConfig;
+use crate::sidecar::install_openssl;
+use crate::timings::Timings;
+use crate::version::{Channel, Version};
 use anyhow::Result;
 use colored::Colorize;
 use log::debug;
 use reqwest::Url;
+use serde::Serialize;
 use std::io::prelude::*;
 use std::path::Path;
 use std::path::PathBuf;
-use std::process::Command;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -21,6 +33,8 @@ pub enum FrumError {
     HttpError(#[from] reqwest::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    FetchError(#[from] crate::fetch::Error),
     #[error("Can't find the number of cores")]
     FromUtf8Error(#[from] std::string::FromUtf8Error),
     #[error("Can't extract the file: {source:?}")]
@@ -29,104 +43,1041 @@ pub enum FrumError {
     TarIsEmpty,
     #[error("Can't find version: {version}")]
     VersionNotFound { version: InputVersion },
-    #[error("Can't list the remote versions: {source:?}")]
-    CantListRemoteVersions { source: reqwest::Error },
-    #[error("Version already installed at {path:?}")]
-    VersionAlreadyInstalled { path: PathBuf },
+    #[error("Can't list the remote versions: {source}")]
+    CantListRemoteVersions {
+        source: crate::remote_ruby_index::Error,
+    },
     #[error("Can't find version in dotfiles. Please provide a version manually to the command.")]
     CantInferVersion,
     #[error("The requested version is not installable: {version}")]
     NotInstallableVersion { version: Version },
     #[error("Can't build Ruby: {stderr}")]
     CantBuildRuby { stderr: String },
+    #[error("The download was truncated: expected {expected} bytes but received {received}")]
+    TruncatedDownload { expected: u64, received: u64 },
+    #[error("Can't find the signature of {url}")]
+    SignatureNotFound { url: Url },
+    #[error("The signature of {archive} is invalid: {stderr}")]
+    SignatureInvalid { archive: String, stderr: String },
+    #[error(transparent)]
+    SidecarError(#[from] crate::sidecar::Error),
+    #[error(transparent)]
+    ProjectConfigError(#[from] crate::project_config::Error),
+    #[error(transparent)]
+    ChecksumsError(#[from] crate::checksums::Error),
+    #[error("{} doesn't list a checksum for {archive}", checksum_file.display())]
+    ChecksumNotListed {
+        archive: String,
+        checksum_file: PathBuf,
+    },
+    #[error("The SHA-256 of {archive} is {actual}, but {expected} was expected")]
+    ChecksumMismatch {
+        archive: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("Ruby {version} was built but `ruby -v` fails: {stderr}. It is kept for inspection until `frum uninstall {version}`.")]
+    BuildDoesntRun { version: Version, stderr: String },
+    #[error("Ruby {version} was requested but the build reports `{reported}`. It is kept for inspection until `frum uninstall {version}`.")]
+    BuildVersionMismatch { version: Version, reported: String },
+    #[error("Can't install {versions}")]
+    BatchInstallFailed { versions: String },
+    #[error("The installation was interrupted")]
+    Interrupted,
+    #[error("Can't find the Ruby source at {location}")]
+    SourceNotFound { location: String },
+    #[error("Can't check out the Ruby source: {stderr}")]
+    CantCheckOutSource { stderr: String },
+    #[error("Ruby {version} is already being installed by another frum process")]
+    AlreadyInstalling { version: Version },
+    #[error("{} is read-only. Set FRUM_DIR to a directory you can write to, or run frum as the owner of this one, e.g. with sudo for a shared installation.", path.display())]
+    ReadOnlyFrumDir { path: PathBuf },
+    #[error(
+        "Can't strip {components} leading directories: {dir} doesn't contain a single directory"
+    )]
+    CantStripPrefix { components: usize, dir: PathBuf },
+    #[error("{} isn't a Ruby installation: it doesn't contain bin/ruby", dir.display())]
+    NotRubyInstallation { dir: PathBuf },
+    #[error("Give the full version to register {dir} as, not {version}", dir = dir.display())]
+    CantRegisterPartialVersion { version: InputVersion, dir: PathBuf },
+    #[error("Can't find the patch {patch}")]
+    PatchNotFound { patch: String },
+    #[error("The patch {patch} doesn't apply: {output}")]
+    CantApplyPatch { patch: String, output: String },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::FetchError(err) => err.kind(),
+            Self::FromUtf8Error(_) => "FromUtf8Error",
+            Self::ExtractError { .. } => "ExtractError",
+            Self::TarIsEmpty => "TarIsEmpty",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::CantListRemoteVersions { .. } => "CantListRemoteVersions",
+            Self::CantInferVersion => "CantInferVersion",
+            Self::NotInstallableVersion { .. } => "NotInstallableVersion",
+            Self::CantBuildRuby { .. } => "CantBuildRuby",
+            Self::TruncatedDownload { .. } => "TruncatedDownload",
+            Self::SignatureNotFound { .. } => "SignatureNotFound",
+            Self::SignatureInvalid { .. } => "SignatureInvalid",
+            Self::SidecarError(err) => err.kind(),
+            Self::ProjectConfigError(err) => err.kind(),
+            Self::ChecksumsError(err) => err.kind(),
+            Self::ChecksumNotListed { .. } => "ChecksumNotListed",
+            Self::ChecksumMismatch { .. } => "ChecksumMismatch",
+            Self::BuildDoesntRun { .. } => "BuildDoesntRun",
+            Self::BuildVersionMismatch { .. } => "BuildVersionMismatch",
+            Self::BatchInstallFailed { .. } => "BatchInstallFailed",
+            Self::Interrupted => "Interrupted",
+            Self::SourceNotFound { .. } => "SourceNotFound",
+            Self::CantCheckOutSource { .. } => "CantCheckOutSource",
+            Self::AlreadyInstalling { .. } => "AlreadyInstalling",
+            Self::ReadOnlyFrumDir { .. } => "ReadOnlyFrumDir",
+            Self::CantStripPrefix { .. } => "CantStripPrefix",
+            Self::NotRubyInstallation { .. } => "NotRubyInstallation",
+            Self::CantRegisterPartialVersion { .. } => "CantRegisterPartialVersion",
+            Self::PatchNotFound { .. } => "PatchNotFound",
+            Self::CantApplyPatch { .. } => "CantApplyPatch",
+        }
+    }
+}
+
+/// The file that records where a version registered by `install --no-build` or
+/// imported by `frum migrate` came from.
+const PREBUILT_MANIFEST: &str = ".frum-prebuilt";
+
+/// The file that records the configure options a version was built from source with,
+/// so that `frum reinstall` can build it the same way.
+const BUILD_MANIFEST: &str = ".frum-build";
+
+/// The configure options the version in `version_dir` was built with, or none if it
+/// wasn't built by frum or was built before they were recorded.
+pub fn recorded_configure_opts(version_dir: &Path) -> Vec<String> {
+    std::fs::read_to_string(version_dir.join(BUILD_MANIFEST))
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.strip_prefix("configure-opt\t"))
+        .map(String::from)
+        .collect()
+}
+
+/// The directory the version in `version_dir` was registered or imported from.
+pub fn prebuilt_origin(version_dir: &Path) -> Option<PathBuf> {
+    if is_symlink(version_dir) {
+        return std::fs::read_link(version_dir).ok();
+    }
+    std::fs::read_to_string(version_dir.join(PREBUILT_MANIFEST))
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("from\t"))
+        .map(PathBuf::from)
+}
+
+/// How an installed version was obtained.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstalledBy {
+    /// Built from source by `frum install`.
+    Source,
+    /// Registered from a prebuilt directory with `install --no-build`.
+    Prebuilt,
+    /// Imported from another version manager with `frum migrate`.
+    Imported,
+}
+
+impl InstalledBy {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Source => "source",
+            Self::Prebuilt => "prebuilt",
+            Self::Imported => "imported",
+        }
+    }
+
+    /// How the version in `version_dir` was obtained, according to its manifest. Built
+    /// versions don't have one, and manifests written before the provenance was recorded
+    /// only come from `install --no-build`. Symlinked versions are imported ones.
+    pub fn of(version_dir: &Path) -> Self {
+        if is_symlink(version_dir) {
+            return Self::Imported;
+        }
+        let manifest = match std::fs::read_to_string(version_dir.join(PREBUILT_MANIFEST)) {
+            Ok(manifest) => manifest,
+            Err(_) => return Self::Source,
+        };
+        match manifest
+            .lines()
+            .find_map(|line| line.strip_prefix("installed-by\t"))
+        {
+            Some("imported") => Self::Imported,
+            _ => Self::Prebuilt,
+        }
+    }
+}
+
+/// Where `install --source` builds Ruby from.
+#[derive(Debug, PartialEq)]
+enum Source {
+    Archive(Url),
+    /// A git repository, checked out at `reference` if given as `<repository>#<reference>`.
+    Git {
+        repository: String,
+        reference: Option<String>,
+    },
 }
 
+#[derive(Default, Clone)]
 pub struct Install {
     pub version: Option<InputVersion>,
     pub configure_opts: Vec<String>,
+    pub verify_signature: bool,
+    pub signature_key: Option<PathBuf>,
+    /// A list of SHA-256 checksums like `sha256sum` prints, which the archive must match.
+    pub checksum_file: Option<PathBuf>,
+    pub dry_run: bool,
+    pub print_url: bool,
+    pub only_download: bool,
+    pub source: Option<String>,
+    pub prefix_strip: Option<usize>,
+    /// Patch files or URLs, or `-` for standard input, applied to the source before configuring it.
+    pub patches: Vec<String>,
+    /// A Ruby built outside of frum to register instead of building one, with `--no-build`.
+    pub prebuilt: Option<PathBuf>,
+    pub resume: bool,
+    pub json: bool,
+    pub openssl_version: Option<String>,
+    /// Builds only the sidecar dependencies, such as OpenSSL, for a later Ruby build to reuse.
+    pub dependencies_only: bool,
+    /// Shell commands run with the new version after it is installed, such as `gem update --system`.
+    pub post_install: Vec<String>,
+    pub include_prereleases: bool,
+    /// Resolves partial versions and `latest` within this channel only.
+    pub channel: Option<Channel>,
+    pub force: bool,
+    /// The share of the cores a batch install gives this build, used when the `frum.toml`
+    /// of the project doesn't set the make jobs.
+    pub batch_jobs: Option<usize>,
+    pub quiet_build: bool,
+    /// Compiles through ccache when it is on `PATH`, to speed up building versions again.
+    pub use_ccache: bool,
+    /// Stages the build here instead of the directory `FRUM_TMPDIR` or the versions give.
+    pub tmpdir: Option<PathBuf>,
+    /// Runs `ruby -v` of the new version and fails unless it reports the requested version.
+    pub verify_build: bool,
 }
 
-impl crate::command::Command for Install {
-    type Error = FrumError;
+/// Installs several versions, building up to `parallel` of them at a time.
+pub struct BatchInstall {
+    pub installs: Vec<Install>,
+    pub parallel: usize,
+}
 
-    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
-        let current_version = self
-            .version
-            .clone()
-            .or_else(|| get_user_version_for_directory(std::env::current_dir().unwrap()))
-            .ok_or(FrumError::CantInferVersion)?;
-        let version = match current_version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
+#[derive(Serialize, Debug)]
+struct InstallPlan {
+    version: String,
+    url: String,
+}
+
+/// A way of installing a Ruby version, split into the steps every way goes through, so
+/// that `Install` picks one instead of branching at each step. Steps a way doesn't need
+/// do nothing.
+trait InstallStrategy {
+    /// Fetches what the version is installed from, such as the source archive of a release.
+    fn download(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError>;
+
+    /// Turns the download into what is built, such as an extracted and patched source tree.
+    fn prepare(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError>;
+
+    /// Builds and installs the version into the versions directory.
+    fn build(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError>;
+
+    /// Registers the installed version and cleans up after the steps before.
+    fn finalize(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError>;
+
+    /// Runs the steps in order, stopping at the first one failing.
+    fn run(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError> {
+        self.download(config, timings)?;
+        self.prepare(config, timings)?;
+        self.build(config, timings)?;
+        self.finalize(config, timings)
+    }
+}
+
+/// The strategies `Install` chooses from.
+#[derive(Debug, PartialEq)]
+enum StrategyKind {
+    /// Builds a CRuby release from its source archive.
+    SourceBuild,
+    /// Registers a Ruby built outside of frum, with `--no-build`.
+    NoBuildImport,
+}
+
+/// Builds a CRuby release from its source archive, keeping a checkpoint in the build
+/// directory so that `--resume` skips the steps that already succeeded.
+struct SourceBuild<'a> {
+    install: &'a Install,
+    requested: InputVersion,
+    version: Version,
+    url: Url,
+    /// The downloaded archive, or the one saved by `--only-download`.
+    archive_path: PathBuf,
+    /// Whether `archive_path` was saved by `--only-download`, so it isn't downloaded.
+    cached: bool,
+    build_dir: PathBuf,
+    checkpoint: Checkpoint,
+    _lock: std::fs::File,
+}
+
+impl SourceBuild<'_> {
+    fn extracted_dir(&self) -> PathBuf {
+        self.build_dir.join("source")
+    }
+}
+
+impl InstallStrategy for SourceBuild<'_> {
+    fn download(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError> {
+        if self.cached {
+            outln!(config#Info, "{} Using {}", "==>".green(), self.archive_path.display());
+        } else if self
+            .checkpoint
+            .is_done("download", &downloaded(&self.url, &self.archive_path))
+        {
+            outln!(config#Info, "{} Reusing {}", "==>".green(), self.archive_path.display());
+        } else {
+            let not_found = FrumError::VersionNotFound {
+                version: self.requested.clone(),
+            };
+            self.install
+                .download(config, &self.url, not_found, &self.build_dir, timings)?
+                .persist(&self.archive_path)
+                .map_err(|err| FrumError::IoError(err.error))?;
+            self.checkpoint
+                .complete("download", &downloaded(&self.url, &self.archive_path))?;
+        }
+        self.install.verify(
+            config,
+            &self.version,
+            &self.url,
+            &self.archive_path,
+            timings,
+        )
+    }
+
+    fn prepare(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError> {
+        let extracted_dir = self.extracted_dir();
+        // The source is patched as part of extracting it, so other patches mean extracting again
+        let extracted_from = std::iter::once(self.archive_path.to_string_lossy().to_string())
+            .chain(self.install.patches.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        if extracted_dir.exists() && self.checkpoint.is_done("extract", &extracted_from) {
+            return Ok(());
+        }
+        if extracted_dir.exists() {
+            std::fs::remove_dir_all(&extracted_dir).map_err(FrumError::IoError)?;
+        }
+        outln!(config#Info, "{} Extracting {}", "==>".green(), archive(&self.version).green());
+        timings.measure("extract", || {
+            extract_archive_into(
+                &extracted_dir,
+                std::fs::File::open(&self.archive_path).map_err(FrumError::IoError)?,
+            )
+        })?;
+        let source_dir = source_root(&extracted_dir, self.install.prefix_strip)?;
+        self.install.patch(config, &source_dir, timings)?;
+        self.checkpoint.complete("extract", &extracted_from)?;
+        Ok(())
+    }
+
+    fn build(&mut self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError> {
+        let source_dir = source_root(&self.extracted_dir(), self.install.prefix_strip)?;
+        self.install.build(
+            config,
+            &source_dir,
+            &self.version,
+            &mut self.checkpoint,
+            timings,
+        )
+    }
+
+    fn finalize(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+        std::fs::remove_dir_all(&self.build_dir).map_err(FrumError::IoError)
+    }
+}
+
+/// Registers the Ruby installation given with `--no-build` instead of building one.
+struct NoBuildImport<'a> {
+    install: &'a Install,
+    requested: InputVersion,
+    /// The version it is registered as, once `prepare` checked that `requested` is one.
+    version: Option<Version>,
+}
+
+impl InstallStrategy for NoBuildImport<'_> {
+    fn download(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+        Ok(())
+    }
+
+    fn prepare(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+        self.version = match &self.requested {
             InputVersion::Full(Version::System) => {
                 return Err(FrumError::NotInstallableVersion {
                     version: Version::System,
                 })
             }
-            current_version => {
-                let available_versions = crate::remote_ruby_index::list(&config.ruby_build_mirror)
-                    .map_err(|source| FrumError::CantListRemoteVersions { source })?
-                    .drain(..)
-                    .map(|x| x.version)
-                    .collect::<Vec<_>>();
+            InputVersion::Full(version) => Some(version.clone()),
+            version => {
+                return Err(FrumError::CantRegisterPartialVersion {
+                    version: version.clone(),
+                    dir: self.install.prebuilt.clone().unwrap_or_default(),
+                })
+            }
+        };
+        Ok(())
+    }
+
+    fn build(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+        Ok(())
+    }
+
+    fn finalize(&mut self, config: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+        match (&self.version, &self.install.prebuilt) {
+            (Some(version), Some(prebuilt)) => register_prebuilt(
+                config,
+                version,
+                prebuilt,
+                &self.install.temp_dir(config),
+                self.install.force,
+                InstalledBy::Prebuilt,
+            ),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl crate::command::Command for Install {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        self.install_timed(config, Timings::default())
+    }
+
+    fn supports_version_inference() -> bool {
+        true
+    }
+}
+
+impl Install {
+    /// Installs the version, printing how long its phases took with `--timings`.
+    fn install_timed(&self, config: &FrumConfig, mut timings: Timings) -> Result<(), FrumError> {
+        let result = self.install(config, &mut timings);
+        if config.timings && !timings.phases().is_empty() {
+            if self.json {
+                outln!(config#Info, "{}", timings.to_json());
+            } else {
+                outln!(config#Error, "{}", timings);
+            }
+        }
+        result
+    }
 
-                current_version
-                    .to_version(&available_versions)
-                    .ok_or(FrumError::VersionNotFound {
-                        version: current_version,
-                    })?
-                    .clone()
+    fn install(&self, config: &FrumConfig, timings: &mut Timings) -> Result<(), FrumError> {
+        let _interrupt = crate::interrupt::catch();
+        if let Some(location) = &self.source {
+            return self.install_source(config, location, timings);
+        }
+        // The sidecars don't depend on the Ruby version, so none is resolved
+        if self.dependencies_only {
+            if let Some(openssl_dir) = self.install_dependencies(config, timings)? {
+                outln!(config#Info, "{} OpenSSL is ready in {}", "==>".green(), openssl_dir.display());
+            }
+            return Ok(());
+        }
+        let current_version =
+            version_or_inferred::<Self>(self.version.clone(), &std::env::current_dir().unwrap())
+                .ok_or(FrumError::CantInferVersion)?;
+        let mut strategy: Box<dyn InstallStrategy + '_> = match self.strategy_kind() {
+            StrategyKind::NoBuildImport => Box::new(NoBuildImport {
+                install: self,
+                requested: current_version,
+                version: None,
+            }),
+            StrategyKind::SourceBuild => {
+                match self.source_build(config, &current_version, timings)? {
+                    Some(source_build) => Box::new(source_build),
+                    None => return Ok(()),
+                }
             }
         };
-        let installations_dir = config.versions_dir();
-        let installation_dir = PathBuf::from(&installations_dir).join(version.to_string());
+        strategy.run(config, timings)
+    }
 
-        if installation_dir.exists() {
-            return Err(FrumError::VersionAlreadyInstalled {
-                path: installation_dir,
-            });
+    /// How the version is installed, given the flags.
+    fn strategy_kind(&self) -> StrategyKind {
+        match self.prebuilt {
+            Some(_) => StrategyKind::NoBuildImport,
+            None => StrategyKind::SourceBuild,
         }
+    }
 
-        let url = package_url(config.ruby_build_mirror.clone(), &version);
-        outln!(config#Info, "{} Downloading {}", "==>".green(), format!("{}", url).green());
-        let response = reqwest::blocking::get(url)?;
-        if response.status() == 404 {
-            return Err(FrumError::VersionNotFound {
-                version: current_version,
-            });
+    /// Resolves the release to build from `requested`, or `None` when there is nothing to
+    /// build: with `--print-url`, `--dry-run` and `--only-download`, and when it is installed.
+    fn source_build(
+        &self,
+        config: &FrumConfig,
+        requested: &InputVersion,
+        timings: &mut Timings,
+    ) -> Result<Option<SourceBuild<'_>>, FrumError> {
+        let version = resolve_version(config, requested, self.include_prereleases, self.channel)?;
+        let url = package_url(select_mirror(config, &version), &version);
+
+        if self.print_url {
+            outln!(config#Info, "{}", url);
+            return Ok(None);
+        }
+        if self.dry_run {
+            let plan = InstallPlan {
+                version: version.to_string(),
+                url: url.to_string(),
+            };
+            if self.json {
+                outln!(config#Info, "{}", JsonEnvelope::new(&plan));
+            } else {
+                outln!(config#Info, "Ruby {} would be installed from {}", plan.version, plan.url);
+            }
+            return Ok(None);
+        }
+
+        let cached_archive = config.cache_dir().join(archive(&version));
+        if self.only_download {
+            let not_found = FrumError::VersionNotFound {
+                version: requested.clone(),
+            };
+            let archive_file =
+                self.download(config, &url, not_found, &config.cache_dir(), timings)?;
+            self.verify(config, &version, &url, archive_file.path(), timings)?;
+            archive_file
+                .persist(&cached_archive)
+                .map_err(|err| FrumError::IoError(err.error))?;
+            outln!(config#Info, "{} Saved {}", "==>".green(), cached_archive.display());
+            return Ok(None);
+        }
+
+        let installation_dir = config.versions_dir().join(version.to_string());
+        let lock = lock_version(config, &version)?;
+        if installation_dir.exists() && !self.force {
+            outln!(config#Info, "Ruby {} is already installed at {}. Use --force to rebuild it.", version, installation_dir.display());
+            return Ok(None);
+        }
+        if let Some(warning) = crate::eol::eol_status(&version).warning(&version) {
+            outln!(config#Error, "{} {}", "warning:".yellow().bold(), warning);
         }
 
-        outln!(config#Info, "{} Extracting {}", "==>".green(), archive(&version).green());
-        let temp_installations_dir = installations_dir.join(".downloads");
+        // The build directory is kept when the build fails so that --resume can pick it up
+        let build_dir = self.temp_dir(config).join(format!("ruby-{}", version));
+        if !self.resume && build_dir.exists() {
+            std::fs::remove_dir_all(&build_dir).map_err(FrumError::IoError)?;
+        }
+        std::fs::create_dir_all(&build_dir).map_err(FrumError::IoError)?;
+        let checkpoint = Checkpoint::open(&build_dir, self.resume)?;
+        // An archive saved by --only-download is installed without downloading it again
+        let cached = cached_archive.exists();
+        let archive_path = if cached {
+            cached_archive
+        } else {
+            build_dir.join(archive(&version))
+        };
+        Ok(Some(SourceBuild {
+            install: self,
+            requested: requested.clone(),
+            version,
+            url,
+            archive_path,
+            cached,
+            build_dir,
+            checkpoint,
+            _lock: lock,
+        }))
+    }
+
+    /// Builds Ruby from a tarball URL or a git repository instead of a release,
+    /// installing it as `dev-<hash>`.
+    fn install_source(
+        &self,
+        config: &FrumConfig,
+        location: &str,
+        timings: &mut Timings,
+    ) -> Result<(), FrumError> {
+        ensure_writable(config.versions_dir())?;
+        let temp_installations_dir = self.temp_dir(config);
         std::fs::create_dir_all(&temp_installations_dir).map_err(FrumError::IoError)?;
         let temp_dir = tempfile::TempDir::new_in(&temp_installations_dir)
             .expect("Can't generate a temp directory");
-        extract_archive_into(&temp_dir, response)?;
+        let (source_dir, hash) = match parse_source(location)? {
+            Source::Archive(url) => {
+                let not_found = FrumError::SourceNotFound {
+                    location: location.to_string(),
+                };
+                let archive_file =
+                    self.download(config, &url, not_found, &temp_installations_dir, timings)?;
+                outln!(config#Info, "{} Extracting {}", "==>".green(), url.as_str().green());
+                timings.measure("extract", || {
+                    extract_source_archive_into(
+                        &url,
+                        temp_dir.path(),
+                        archive_file.reopen().map_err(FrumError::IoError)?,
+                    )
+                })?;
+                let source_dir = source_root(temp_dir.path(), self.prefix_strip)?;
+                (source_dir, hash_file(archive_file.path())?)
+            }
+            Source::Git {
+                repository,
+                reference,
+            } => {
+                let checkout = temp_dir.path().join("ruby");
+                outln!(config#Info, "{} Cloning {}", "==>".green(), repository.green());
+                let hash = timings.measure("clone", || {
+                    clone_source(&repository, reference.as_deref(), &checkout)
+                })?;
+                (checkout, hash)
+            }
+        };
 
-        outln!(config#Info, "{} Building {}", "==>".green(), format!("Ruby {}", current_version).green());
-        let installed_directory = std::fs::read_dir(&temp_dir)
-            .map_err(FrumError::IoError)?
-            .next()
-            .ok_or(FrumError::TarIsEmpty)?
-            .map_err(FrumError::IoError)?;
-        let installed_directory = installed_directory.path();
+        self.patch(config, &source_dir, timings)?;
+
+        let version = Version::Dev(hash);
+        let installation_dir = config.versions_dir().join(version.to_string());
+        let _lock = lock_version(config, &version)?;
+        if installation_dir.exists() && !self.force {
+            outln!(config#Info, "Ruby {} is already installed at {}. Use --force to rebuild it.", version, installation_dir.display());
+            return Ok(());
+        }
+        self.build(
+            config,
+            &source_dir,
+            &version,
+            &mut Checkpoint::disabled(),
+            timings,
+        )
+    }
+
+    /// Where the build is staged, `--tmpdir` taking precedence over `FRUM_TMPDIR`.
+    fn temp_dir(&self, config: &FrumConfig) -> PathBuf {
+        match &self.tmpdir {
+            Some(tmpdir) => {
+                std::fs::create_dir_all(tmpdir).ok();
+                tmpdir.clone()
+            }
+            None => config.temp_dir(),
+        }
+    }
+
+    /// Builds the Ruby source in `source_dir` and installs it as `version`.
+    fn build(
+        &self,
+        config: &FrumConfig,
+        source_dir: &Path,
+        version: &Version,
+        checkpoint: &mut Checkpoint,
+        timings: &mut Timings,
+    ) -> Result<(), FrumError> {
+        let installation_dir = config.versions_dir().join(version.to_string());
+        let project_config = match ProjectConfig::find(std::env::current_dir().unwrap())? {
+            Some(project_config) => project_config,
+            None => ProjectConfig::load(config.config_file())?.unwrap_or_default(),
+        };
+        let configure_opts = merge_configure_opts(&project_config, &self.configure_opts);
+        let jobs = self.make_jobs(&project_config);
+        let openssl_dir = self
+            .install_dependencies(config, timings)?
+            .or(project_config.openssl_dir);
+
+        let compiler = if self.use_ccache || project_config.ccache {
+            let compiler = ccache_compiler(std::env::var_os("PATH"), std::env::var_os("CC"));
+            if compiler.is_none() {
+                outln!(config#Error, "{} Building without ccache, which isn't on PATH", "warning:".yellow().bold());
+            }
+            compiler
+        } else {
+            None
+        };
+
+        let build_log = self.quiet_build.then(|| {
+            config
+                .cache_dir()
+                .join(format!("ruby-{}-build.log", version))
+        });
+        let ccache_used = match &compiler {
+            Some(compiler) => format!("frum: building with CC=\"{}\"", compiler),
+            None => "frum: building without ccache".to_string(),
+        };
+        match &build_log {
+            Some(build_log) => {
+                std::fs::write(build_log, format!("{}\n", ccache_used))
+                    .map_err(FrumError::IoError)?;
+                outln!(config#Info, "{} Building {} (output in {})", "==>".green(), format!("Ruby {}", version).green(), build_log.display());
+            }
+            None => {
+                outln!(config#Info, "{} Building {}", "==>".green(), format!("Ruby {}", version).green());
+                // Without ccache, the warning above already says so when it was asked for
+                if compiler.is_some() {
+                    outln!(config#Info, "{}", ccache_used);
+                }
+            }
+        }
+        // `make install` goes into a staging directory, so that a failed rebuild keeps the
+        // version that works and a failed install doesn't leave half a version behind
+        let staging_dir =
+            tempfile::TempDir::new_in(config.versions_dir()).map_err(FrumError::IoError)?;
         build_package(
-            &installed_directory,
+            source_dir,
             &installation_dir,
-            &self.configure_opts,
+            staging_dir.path(),
+            &configure_opts,
+            openssl_dir.as_deref(),
+            jobs,
+            compiler.as_deref(),
+            build_log.as_deref(),
+            checkpoint,
+            timings,
         )?;
+        let staged_dir = staging_dir.path().join(
+            installation_dir
+                .components()
+                .filter(|component| matches!(component, std::path::Component::Normal(_)))
+                .collect::<PathBuf>(),
+        );
+        if !staged_dir.is_dir() {
+            return Err(FrumError::CantBuildRuby {
+                stderr: format!("make install didn't install into {}", staged_dir.display()),
+            });
+        }
+        let build_manifest = configure_opts
+            .iter()
+            .map(|opt| format!("configure-opt\t{}\n", opt))
+            .collect::<String>();
+        std::fs::write(staged_dir.join(BUILD_MANIFEST), build_manifest)?;
+        swap_in(&staged_dir, &installation_dir)?;
+        if self.verify_build {
+            outln!(config#Info, "{} Verifying {}", "==>".green(), format!("Ruby {}", version).green());
+            timings.measure("verify", || verify_build(config, version))?;
+        }
+        create_default_alias(config, version)?;
+        install_default_gems(config, version, build_log.as_deref())?;
+        self.run_post_install(config, version, build_log.as_deref())
+    }
+
+    /// Runs the `--post-install` commands with `version` first on `PATH` and its own gems,
+    /// like `frum bundle` does. A failing command is reported, but the version stays installed.
+    fn run_post_install(
+        &self,
+        config: &FrumConfig,
+        version: &Version,
+        build_log: Option<&Path>,
+    ) -> Result<(), FrumError> {
+        for command in &self.post_install {
+            outln!(config#Info, "{} Running {}", "==>".green(), command.green());
+            let output = run_interruptible(
+                ruby_command(config, version, PathBuf::from("sh"))
+                    .arg("-c")
+                    .arg(command),
+                crate::interrupt::flag(),
+            )?;
+            append_to_build_log(build_log, &output)?;
+            if !output.status.success() {
+                outln!(config#Error, "{} `{}` failed after installing Ruby {}: {}", "warning:".yellow().bold(), command, version, String::from_utf8_lossy(&output.stderr).trim());
+            }
+        }
+        Ok(())
+    }
+
+    /// The make jobs of the build, where the share of a batch install comes last so that
+    /// it doesn't override the jobs of the project.
+    fn make_jobs(&self, project_config: &ProjectConfig) -> Option<usize> {
+        project_config.jobs.or(self.batch_jobs)
+    }
+
+    /// Builds the sidecar dependencies requested with `--openssl-version`, or reuses
+    /// earlier builds of them, returning the OpenSSL prefix.
+    fn install_dependencies(
+        &self,
+        config: &FrumConfig,
+        timings: &mut Timings,
+    ) -> Result<Option<PathBuf>, FrumError> {
+        let openssl_dir = self
+            .openssl_version
+            .as_ref()
+            .map(|openssl_version| {
+                timings.measure("openssl", || install_openssl(config, openssl_version))
+            })
+            .transpose()?;
+        Ok(openssl_dir)
+    }
+
+    /// Applies the patches given with `--patch` to the source in `source_dir`, in order.
+    fn patch(
+        &self,
+        config: &FrumConfig,
+        source_dir: &Path,
+        timings: &mut Timings,
+    ) -> Result<(), FrumError> {
+        if self.patches.is_empty() {
+            return Ok(());
+        }
+        timings.measure("patch", || {
+            for patch in &self.patches {
+                outln!(config#Info, "{} Applying {}", "==>".green(), patch.green());
+                apply_patch(source_dir, patch, &read_patch(patch)?)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Downloads the archive at `url` into a temporary file in `dir`.
+    fn download(
+        &self,
+        config: &FrumConfig,
+        url: &Url,
+        not_found: FrumError,
+        dir: &Path,
+        timings: &mut Timings,
+    ) -> Result<tempfile::NamedTempFile, FrumError> {
+        let mut archive_file = tempfile::NamedTempFile::new_in(dir).map_err(FrumError::IoError)?;
+        outln!(config#Info, "{} Downloading {}", "==>".green(), format!("{}", url).green());
+        timings.measure("download", || {
+            let fetched = fetch(url)?.ok_or(not_found)?;
+            download_into(fetched.body, fetched.content_length, &mut archive_file)
+        })?;
+        Ok(archive_file)
+    }
 
-        if !config.default_version_dir().exists() {
-            debug!("Use {} as the default version", current_version);
-            create_alias(&config, "default", &version).map_err(FrumError::IoError)?;
+    fn verify(
+        &self,
+        config: &FrumConfig,
+        version: &Version,
+        url: &Url,
+        archive_path: &Path,
+        timings: &mut Timings,
+    ) -> Result<(), FrumError> {
+        if self.verify_signature {
+            outln!(config#Info, "{} Verifying {}", "==>".green(), archive(version).green());
+            timings.measure("verify", || {
+                verify_signature(url, archive_path, self.signature_key.as_deref())
+            })?;
+        }
+        if let Some(checksum_file) = &self.checksum_file {
+            outln!(config#Info, "{} Checking the checksum of {}", "==>".green(), archive(version).green());
+            timings.measure("checksum", || {
+                verify_checksum(checksum_file, &archive(version), archive_path)
+            })?;
         }
         Ok(())
     }
 }
 
-fn extract_archive_into<P: AsRef<Path>>(
-    path: P,
-    response: reqwest::blocking::Response,
-) -> Result<(), FrumError> {
+impl crate::command::Command for BatchInstall {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let parallel = self.parallel.clamp(1, num_cpus::get());
+        // Share the cores between the builds running at the same time
+        let jobs = (num_cpus::get() / parallel).max(1);
+        let installs = self
+            .installs
+            .iter()
+            .map(|install| Install {
+                batch_jobs: Some(jobs),
+                ..install.clone()
+            })
+            .collect::<Vec<_>>();
+
+        let _interrupt = crate::interrupt::catch();
+        let progress = BatchProgress::new(&installs);
+        let indexed = installs.iter().enumerate().collect::<Vec<_>>();
+        let results = run_parallel(&indexed, parallel, |(index, install)| {
+            if crate::interrupt::interrupted() {
+                return Err(FrumError::Interrupted);
+            }
+            let timings = Timings::with_progress(|phase| progress.update(config, *index, phase));
+            let result = install.install_timed(config, timings);
+            progress.update(
+                config,
+                *index,
+                if result.is_ok() { "done" } else { "failed" },
+            );
+            result
+        });
+        let mut failed = vec![];
+        for (install, result) in installs.iter().zip(results) {
+            if let Err(err) = result {
+                let version = install
+                    .version
+                    .as_ref()
+                    .map(|version| version.to_string())
+                    .unwrap_or_default();
+                outln!(config#Error, "{} Ruby {}: {}", "error:".red().bold(), version, err);
+                failed.push(version);
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(FrumError::BatchInstallFailed {
+                versions: failed.join(", "),
+            })
+        }
+    }
+}
+
+/// The phase each version of a batch install is in, printed as one line whenever one
+/// of them moves on, such as `[1/3] 2.7.2 done, 3.0.0 make, 3.1.0 queued`.
+struct BatchProgress {
+    versions: Vec<String>,
+    phases: Mutex<Vec<&'static str>>,
+}
+
+impl BatchProgress {
+    fn new(installs: &[Install]) -> Self {
+        Self {
+            versions: installs
+                .iter()
+                .map(|install| {
+                    install
+                        .version
+                        .as_ref()
+                        .map(|version| version.to_string())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            phases: Mutex::new(vec!["queued"; installs.len()]),
+        }
+    }
+
+    fn update(&self, config: &FrumConfig, index: usize, phase: &'static str) {
+        let mut phases = self.phases.lock().unwrap_or_else(|err| err.into_inner());
+        phases[index] = phase;
+        outln!(config#Info, "{} {}", "==>".green(), self.summary(&phases));
+    }
+
+    fn summary(&self, phases: &[&'static str]) -> String {
+        let finished = phases
+            .iter()
+            .filter(|phase| matches!(**phase, "done" | "failed"))
+            .count();
+        let versions = self
+            .versions
+            .iter()
+            .zip(phases)
+            .map(|(version, phase)| format!("{} {}", version, phase))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("[{}/{}] {}", finished, phases.len(), versions)
+    }
+}
+
+/// Runs `job` for every item on at most `parallel` threads and returns the results in the order of the items.
+fn run_parallel<T, E, F>(items: &[T], parallel: usize, job: F) -> Vec<Result<(), E>>
+where
+    T: Sync,
+    E: Send,
+    F: Fn(&T) -> Result<(), E> + Sync,
+{
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new((0..items.len()).map(|_| None).collect::<Vec<_>>());
+    std::thread::scope(|scope| {
+        for _ in 0..parallel.clamp(1, items.len().max(1)) {
+            scope.spawn(|| {
+                let mut index = next.fetch_add(1, Ordering::SeqCst);
+                while let Some(item) = items.get(index) {
+                    let result = job(item);
+                    results.lock().unwrap()[index] = Some(result);
+                    index = next.fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+    });
+    results
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|result| result.expect("every item runs once"))
+        .collect()
+}
+
+fn resolve_version(
+    config: &FrumConfig,
+    version: &InputVersion,
+    include_prereleases: bool,
+    channel: Option<Channel>,
+) -> Result<Version, FrumError> {
+    match version {
+        InputVersion::Full(Version::Semver(v)) => Ok(Version::Semver(v.clone())),
+        InputVersion::Full(Version::System) => Err(FrumError::NotInstallableVersion {
+            version: Version::System,
+        }),
+        InputVersion::Full(version @ Version::Dev(_)) => Err(FrumError::NotInstallableVersion {
+            version: version.clone(),
+        }),
+        version => {
+            let available_versions = match channel {
+                Some(channel) => crate::remote_ruby_index::list_channel(config, channel),
+                None => crate::remote_ruby_index::list_versions(config, include_prereleases),
+            }
+            .map_err(|source| FrumError::CantListRemoteVersions { source })?;
+            select_version(version, &available_versions)
+        }
+    }
+}
+
+fn select_version(
+    version: &InputVersion,
+    available_versions: &[Version],
+) -> Result<Version, FrumError> {
+    version
+        .to_version(available_versions)
+        .cloned()
+        .ok_or_else(|| FrumError::VersionNotFound {
+            version: version.clone(),
+        })
+}
+
+/// Copies the response body into `writer`, making sure that as many bytes as the
+/// `Content-Length` header advertises arrived.
+fn download_into<R: Read, W: Write>(
+    mut response: R,
+    content_length: Option<u64>,
+    writer: &mut W,
+) -> Result<u64, FrumError> {
+    let mut received = 0;
+    let mut buffer = [0; 8 * 1024];
+    loop {
+        if crate::interrupt::interrupted() {
+            return Err(FrumError::Interrupted);
+        }
+        match response.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => {
+                writer.write_all(&buffer[..read])?;
+                received += read as u64;
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            // The connection was closed early, which is reported below
+            Err(_) if content_length.is_some_and(|expected| received < expected) => break,
+            Err(err) => return Err(FrumError::IoError(err)),
+        }
+    }
+    match content_length {
+        Some(expected) if expected != received => {
+            Err(FrumError::TruncatedDownload { expected, received })
+        }
+        _ => Ok(received),
+    }
+}
+
+fn extract_archive_into<P: AsRef<Path>, R: Read>(path: P, response: R) -> Result<(), FrumError> {
     #[cfg(unix)]
     let extractor = archive::tar_xz::TarXz::new(response);
     #[cfg(windows)]
@@ -137,165 +1088,965 @@ fn extract_archive_into<P: AsRef<Path>>(
     Ok(())
 }
 
-fn package_url(mirror_url: Url, version: &Version) -> Url {
-    debug!("pakage url");
-    Url::parse(&format!(
-        "{}/{}/{}",
-        mirror_url.as_str().trim_end_matches('/'),
-        match version {
-            Version::Semver(version) => format!("{}.{}", version.major, version.minor),
-            _ => unreachable!(),
-        },
-        archive(version),
-    ))
-    .unwrap()
+/// Reads the patch at `location`, which is a URL, a file, or `-` for standard input.
+fn read_patch(location: &str) -> Result<Vec<u8>, FrumError> {
+    if location == "-" {
+        // Standard input can only be read once, so every version of a batch gets the same patch
+        static STDIN: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+        let mut stdin = STDIN.lock().unwrap();
+        if stdin.is_none() {
+            let mut contents = vec![];
+            std::io::stdin().read_to_end(&mut contents)?;
+            *stdin = Some(contents);
+        }
+        return Ok(stdin.clone().unwrap_or_default());
+    }
+    let not_found = || FrumError::PatchNotFound {
+        patch: location.to_string(),
+    };
+    match Url::parse(location) {
+        Ok(url) if ["http", "https", "file"].contains(&url.scheme()) => {
+            let mut contents = vec![];
+            let fetched = fetch(&url)?.ok_or_else(not_found)?;
+            download_into(fetched.body, fetched.content_length, &mut contents)?;
+            Ok(contents)
+        }
+        _ => std::fs::read(location).map_err(|_| not_found()),
+    }
 }
 
-#[cfg(unix)]
-fn archive(version: &Version) -> String {
-    format!("ruby-{}.tar.xz", version)
+/// Applies `contents` to the source in `source_dir` with `patch -p1`, like ruby-build does.
+fn apply_patch(source_dir: &Path, patch: &str, contents: &[u8]) -> Result<(), FrumError> {
+    let mut patch_file = tempfile::NamedTempFile::new().map_err(FrumError::IoError)?;
+    patch_file.write_all(contents)?;
+    debug!("patch -p1 < {}", patch);
+    let output = run_interruptible(
+        Command::new("patch")
+            .arg("-p1")
+            .arg("--batch")
+            .arg("-i")
+            .arg(patch_file.path())
+            .current_dir(source_dir),
+        crate::interrupt::flag(),
+    )?;
+    if !output.status.success() {
+        return Err(FrumError::CantApplyPatch {
+            patch: patch.to_string(),
+            output: format!(
+                "{}{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            )
+            .trim()
+            .to_string(),
+        });
+    }
+    Ok(())
 }
 
-#[cfg(windows)]
-fn archive(version: &Version) -> String {
-    format!("ruby-{}.zip", version)
+/// Locks the installation of `version` against other frum processes until the returned file
+/// is dropped, which also happens when the process panics or exits.
+fn lock_version(config: &FrumConfig, version: &Version) -> Result<std::fs::File, FrumError> {
+    let locks_dir = ensure_writable(config.versions_dir())?.join(".downloads");
+    std::fs::create_dir_all(&locks_dir).map_err(FrumError::IoError)?;
+    let lock_file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(locks_dir.join(format!("{}.lock", version)))
+        .map_err(FrumError::IoError)?;
+    match lock_file.try_lock() {
+        Ok(()) => Ok(lock_file),
+        Err(std::fs::TryLockError::WouldBlock) => Err(FrumError::AlreadyInstalling {
+            version: version.clone(),
+        }),
+        Err(std::fs::TryLockError::Error(err)) => Err(FrumError::IoError(err)),
+    }
 }
 
-#[allow(clippy::unnecessary_wraps)]
-fn openssl_dir() -> Result<String, FrumError> {
-    #[cfg(target_os = "macos")]
-    return Ok(String::from_utf8_lossy(
-        &Command::new("brew")
-            .arg("--prefix")
-            .arg("openssl")
-            .output()
-            .map_err(FrumError::IoError)?
-            .stdout,
-    )
-    .trim()
-    .to_string());
-    #[cfg(not(target_os = "macos"))]
-    return Ok("/usr/local".to_string());
+/// `dir`, or the error telling how to fix it if frum can't write to it. Commands changing
+/// `frum-dir` check that first so that they don't fail halfway with a raw IO error.
+pub fn ensure_writable(dir: PathBuf) -> Result<PathBuf, FrumError> {
+    if crate::config::is_writable(&dir) {
+        Ok(dir)
+    } else {
+        Err(FrumError::ReadOnlyFrumDir { path: dir })
+    }
 }
 
-fn build_package(
-    current_dir: &Path,
-    installed_dir: &Path,
-    configure_opts: &[String],
+/// Copies the Ruby installation in `prebuilt` into the versions directory as `version`
+/// by way of `temp_dir`, replacing an installed one only if `force` is set.
+pub fn register_prebuilt(
+    config: &FrumConfig,
+    version: &Version,
+    prebuilt: &Path,
+    temp_dir: &Path,
+    force: bool,
+    installed_by: InstalledBy,
 ) -> Result<(), FrumError> {
-    debug!("./configure {}", configure_opts.join(" "));
-    let mut command = Command::new("sh");
-    command
-        .arg("configure")
-        .arg(format!("--prefix={}", installed_dir.to_str().unwrap()))
-        .args(configure_opts);
+    if !is_ruby_installation(prebuilt) {
+        return Err(FrumError::NotRubyInstallation {
+            dir: prebuilt.to_path_buf(),
+        });
+    }
 
-    // Provide a default value for --with-openssl-dir
-    if !configure_opts
-        .iter()
-        .any(|opt| opt.starts_with("--with-openssl-dir"))
-    {
-        command.arg(format!("--with-openssl-dir={}", openssl_dir()?));
+    let installation_dir = config.versions_dir().join(version.to_string());
+    let _lock = lock_version(config, version)?;
+    if installation_dir.exists() && !force {
+        outln!(config#Info, "Ruby {} is already installed at {}. Use --force to replace it.", version, installation_dir.display());
+        return Ok(());
     }
+    outln!(config#Info, "{} Registering {} from {}", "==>".green(), format!("Ruby {}", version).green(), prebuilt.display());
+    // Copy to the temp directory first so that a failed copy doesn't leave a version behind
+    let temp_dir = tempfile::TempDir::new_in(temp_dir).map_err(FrumError::IoError)?;
+    let copied_dir = temp_dir.path().join(version.to_string());
+    copy_tree(prebuilt, &copied_dir)?;
+    std::fs::write(
+        copied_dir.join(PREBUILT_MANIFEST),
+        format!(
+            "version\t{}\nfrom\t{}\ninstalled-by\t{}\n",
+            version,
+            prebuilt.display(),
+            installed_by.name()
+        ),
+    )?;
+    swap_in(&copied_dir, &installation_dir)?;
+    create_default_alias(config, version)
+}
 
-    let configure = command
-        .current_dir(&current_dir)
-        .output()
-        .map_err(FrumError::IoError)?;
-    if !configure.status.success() {
-        return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "configure failed: {}",
-                String::from_utf8_lossy(&configure.stderr).to_string()
-            ),
+/// Registers `version` as a symlink to `prebuilt`, a Ruby of another version manager.
+/// Its scripts and `RbConfig` point at where it was built, so a copy of it wouldn't run
+/// once that directory is gone, and the symlink keeps them right.
+pub fn link_prebuilt(
+    config: &FrumConfig,
+    version: &Version,
+    prebuilt: &Path,
+    force: bool,
+) -> Result<(), FrumError> {
+    if !is_ruby_installation(prebuilt) {
+        return Err(FrumError::NotRubyInstallation {
+            dir: prebuilt.to_path_buf(),
         });
+    }
+
+    let installation_dir = config.versions_dir().join(version.to_string());
+    let _lock = lock_version(config, version)?;
+    let installed = is_symlink(&installation_dir) || installation_dir.exists();
+    if installed && !force {
+        outln!(config#Info, "Ruby {} is already installed at {}. Use --force to replace it.", version, installation_dir.display());
+        return Ok(());
+    }
+    outln!(config#Info, "{} Linking {} to {}", "==>".green(), format!("Ruby {}", version).green(), prebuilt.display());
+    let prebuilt = prebuilt.canonicalize()?;
+    if is_symlink(&installation_dir) {
+        crate::symlink::remove_symlink_dir(&installation_dir)?;
+    } else if installed {
+        std::fs::remove_dir_all(&installation_dir)?;
+    }
+    crate::symlink::create_symlink_dir(&prebuilt, &installation_dir)?;
+    create_default_alias(config, version)
+}
+
+fn is_symlink(path: &Path) -> bool {
+    path.symlink_metadata()
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false)
+}
+
+/// Whether `dir` looks like the prefix Ruby was installed into.
+pub fn is_ruby_installation(dir: &Path) -> bool {
+    let ruby = if cfg!(windows) { "ruby.exe" } else { "ruby" };
+    dir.join("bin").join(ruby).is_file()
+}
+
+/// Makes `version` the default version unless there already is one.
+fn create_default_alias(config: &FrumConfig, version: &Version) -> Result<(), FrumError> {
+    if !config.default_version_dir().exists() {
+        debug!("Use {} as the default version", version);
+        match create_alias(config, "default", version) {
+            // Another installation running in parallel has just created it
+            Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => (),
+            result => result.map_err(FrumError::IoError)?,
+        }
+    }
+    Ok(())
+}
+
+/// Copies the directory `from` to `to`, keeping the symlinks in it on unix.
+fn copy_tree(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in from.read_dir()? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else if cfg!(unix) && file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(std::fs::read_link(entry.path())?, &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Moves `new_dir` to `installation_dir`, first moving the version it replaces aside,
+/// and back if the new one can't be moved there.
+fn swap_in(new_dir: &Path, installation_dir: &Path) -> std::io::Result<()> {
+    // Next to the version, so that moving it aside is a rename
+    let backup_dir = tempfile::TempDir::new_in(
+        installation_dir
+            .parent()
+            .expect("a version directory has a parent"),
+    )?;
+    let replaced_dir = backup_dir.path().join("replaced");
+    let replaces = installation_dir.symlink_metadata().is_ok();
+    if replaces {
+        std::fs::rename(installation_dir, &replaced_dir)?;
+    }
+    if let Err(err) = move_dir(new_dir, installation_dir) {
+        if replaces {
+            std::fs::rename(&replaced_dir, installation_dir).ok();
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// Moves the directory `from` to `to`, copying it when they are on different file systems,
+/// as they are when the temp directory is on a tmpfs.
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(err) if err.kind() == std::io::ErrorKind::CrossesDevices => {
+            debug!(
+                "Copy {} to {} across file systems",
+                from.display(),
+                to.display()
+            );
+            if let Err(err) = copy_tree(from, to) {
+                std::fs::remove_dir_all(to).ok();
+                return Err(err);
+            }
+            std::fs::remove_dir_all(from)
+        }
+        result => result,
+    }
+}
+
+/// What a download is recorded as in a checkpoint, so that a partial or
+/// replaced archive isn't mistaken for the one that was downloaded.
+fn downloaded(url: &Url, archive_path: &Path) -> String {
+    let size = archive_path.metadata().map_or(0, |metadata| metadata.len());
+    format!("{} {}", url, size)
+}
+
+/// Finds the source tree in the directory an archive was extracted into. It is the
+/// directory `prefix_strip` levels down, going into the only directory of each level.
+/// By default, that is the top-level directory if it is the only entry, and the
+/// extracted directory itself otherwise.
+fn source_root(extracted_dir: &Path, prefix_strip: Option<usize>) -> Result<PathBuf, FrumError> {
+    let entries = |dir: &Path| -> Result<Vec<PathBuf>, FrumError> {
+        std::fs::read_dir(dir)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| entry.path()))
+                    .collect()
+            })
+            .map_err(FrumError::IoError)
     };
-    debug!("make -j {}", num_cpus::get().to_string());
-    let make = Command::new("make")
-        .arg("-j")
-        .arg(num_cpus::get().to_string())
-        .current_dir(&current_dir)
-        .output()
-        .map_err(FrumError::IoError)?;
-    if !make.status.success() {
-        return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "make failed: {}",
-                String::from_utf8_lossy(&make.stderr).to_string()
-            ),
+    let entries_of_root = entries(extracted_dir)?;
+    if entries_of_root.is_empty() {
+        return Err(FrumError::TarIsEmpty);
+    }
+    let components = match prefix_strip {
+        Some(components) => components,
+        None if entries_of_root.len() == 1 && entries_of_root[0].is_dir() => 1,
+        None => 0,
+    };
+    let mut root = extracted_dir.to_path_buf();
+    for _ in 0..components {
+        root = match entries(&root)?.as_slice() {
+            [dir] if dir.is_dir() => dir.clone(),
+            _ => {
+                return Err(FrumError::CantStripPrefix {
+                    components,
+                    dir: root,
+                })
+            }
+        };
+    }
+    Ok(root)
+}
+
+/// Reads `--source` as a tarball URL or path when it has an archive extension, and as a git repository otherwise.
+fn parse_source(location: &str) -> Result<Source, FrumError> {
+    if [".tar.xz", ".tar.gz", ".tgz", ".zip"]
+        .iter()
+        .any(|extension| location.ends_with(extension))
+    {
+        let url = match Url::parse(location) {
+            Ok(url) => url,
+            Err(_) => {
+                Url::from_file_path(std::env::current_dir()?.join(location)).map_err(|_| {
+                    FrumError::SourceNotFound {
+                        location: location.to_string(),
+                    }
+                })?
+            }
+        };
+        return Ok(Source::Archive(url));
+    }
+    let (repository, reference) = match location.rsplit_once('#') {
+        Some((repository, reference)) => (repository, Some(reference.to_string())),
+        None => (location, None),
+    };
+    Ok(Source::Git {
+        repository: repository.to_string(),
+        reference,
+    })
+}
+
+fn extract_source_archive_into<R: Read>(
+    url: &Url,
+    path: &Path,
+    archive: R,
+) -> Result<(), FrumError> {
+    let result = if url.path().ends_with(".zip") {
+        archive::zip::Zip::new(archive).extract_into(path)
+    } else if url.path().ends_with(".tar.xz") {
+        archive::tar_xz::TarXz::new(archive).extract_into(path)
+    } else {
+        archive::tar_gz::TarGz::new(archive).extract_into(path)
+    };
+    result.map_err(|source| FrumError::ExtractError { source })
+}
+
+/// Clones `repository` into `checkout` and returns the abbreviated hash of the checked out commit.
+fn clone_source(
+    repository: &str,
+    reference: Option<&str>,
+    checkout: &Path,
+) -> Result<String, FrumError> {
+    run_git(
+        Command::new("git")
+            .arg("clone")
+            .arg("--quiet")
+            .arg(repository)
+            .arg(checkout),
+    )?;
+    if let Some(reference) = reference {
+        run_git(
+            Command::new("git")
+                .arg("-C")
+                .arg(checkout)
+                .args(["checkout", "--quiet", reference]),
+        )?;
+    }
+    let output = run_git(Command::new("git").arg("-C").arg(checkout).args([
+        "rev-parse",
+        "--short=12",
+        "HEAD",
+    ]))?;
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn run_git(command: &mut Command) -> Result<Output, FrumError> {
+    let output = command.output().map_err(FrumError::IoError)?;
+    if !output.status.success() {
+        return Err(FrumError::CantCheckOutSource {
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(output)
+}
+
+/// The first 12 hex digits of the 64-bit FNV-1a hash of a file, to name a build of a tarball.
+fn hash_file(path: &Path) -> Result<String, FrumError> {
+    let hash = std::fs::read(path)
+        .map_err(FrumError::IoError)?
+        .iter()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    Ok(format!("{:016x}", hash)[..12].to_string())
+}
+
+pub fn package_url(mirror_url: Url, version: &Version) -> Url {
+    debug!("pakage url");
+    Url::parse(&format!(
+        "{}/{}/{}",
+        mirror_url.as_str().trim_end_matches('/'),
+        match version {
+            Version::Semver(version) => format!("{}.{}", version.major, version.minor),
+            _ => unreachable!(),
+        },
+        archive(version),
+    ))
+    .unwrap()
+}
+
+fn verify_signature(
+    url: &Url,
+    archive_path: &Path,
+    public_key: Option<&Path>,
+) -> Result<(), FrumError> {
+    let mut signature_file = tempfile::NamedTempFile::new().map_err(FrumError::IoError)?;
+    let mut signature_found = false;
+    for extension in &["asc", "sig"] {
+        let signature_url = Url::parse(&format!("{}.{}", url, extension)).unwrap();
+        debug!("Downloading the signature from {}", signature_url);
+        if let Some(mut fetched) = fetch(&signature_url)? {
+            std::io::copy(&mut fetched.body, &mut signature_file)?;
+            signature_found = true;
+            break;
+        }
+    }
+    if !signature_found {
+        return Err(FrumError::SignatureNotFound { url: url.clone() });
+    }
+    verify_detached_signature(archive_path, signature_file.path(), public_key)
+}
+
+/// Checks that the SHA-256 of the archive at `archive_path` is the one `checksum_file`
+/// lists for `archive`, its file name on the mirror.
+fn verify_checksum(
+    checksum_file: &Path,
+    archive: &str,
+    archive_path: &Path,
+) -> Result<(), FrumError> {
+    let expected = crate::checksums::Checksums::read(checksum_file)?
+        .get(archive)
+        .ok_or_else(|| FrumError::ChecksumNotListed {
+            archive: archive.to_string(),
+            checksum_file: checksum_file.to_path_buf(),
+        })?
+        .to_string();
+    let actual = crate::checksums::sha256(archive_path)?;
+    debug!("The SHA-256 of {} is {}", archive_path.display(), actual);
+    if actual != expected {
+        return Err(FrumError::ChecksumMismatch {
+            archive: archive.to_string(),
+            expected,
+            actual,
         });
+    }
+    Ok(())
+}
+
+fn verify_detached_signature(
+    archive_path: &Path,
+    signature_path: &Path,
+    public_key: Option<&Path>,
+) -> Result<(), FrumError> {
+    // Import the provided key into a throwaway keyring instead of the user's one
+    let gnupg_home = match public_key {
+        Some(public_key) => {
+            let gnupg_home = tempfile::tempdir().map_err(FrumError::IoError)?;
+            debug!("gpg --import {}", public_key.display());
+            let import = Command::new("gpg")
+                .arg("--batch")
+                .arg("--homedir")
+                .arg(gnupg_home.path())
+                .arg("--import")
+                .arg(public_key)
+                .output()
+                .map_err(FrumError::IoError)?;
+            if !import.status.success() {
+                return Err(FrumError::SignatureInvalid {
+                    archive: archive_path.display().to_string(),
+                    stderr: String::from_utf8_lossy(&import.stderr).to_string(),
+                });
+            }
+            Some(gnupg_home)
+        }
+        None => None,
     };
-    debug!("make install");
-    let make_install = Command::new("make")
-        .arg("install")
-        .current_dir(&current_dir)
+
+    debug!("gpg --verify {}", signature_path.display());
+    let mut command = Command::new("gpg");
+    command.arg("--batch");
+    if let Some(gnupg_home) = &gnupg_home {
+        command.arg("--homedir").arg(gnupg_home.path());
+    }
+    let verify = command
+        .arg("--verify")
+        .arg(signature_path)
+        .arg(archive_path)
         .output()
         .map_err(FrumError::IoError)?;
-    if !make_install.status.success() {
+    if !verify.status.success() {
+        return Err(FrumError::SignatureInvalid {
+            archive: archive_path.display().to_string(),
+            stderr: String::from_utf8_lossy(&verify.stderr).to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+pub fn archive(version: &Version) -> String {
+    format!("ruby-{}.tar.xz", version)
+}
+
+#[cfg(windows)]
+pub fn archive(version: &Version) -> String {
+    format!("ruby-{}.zip", version)
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn default_openssl_dir() -> Result<String, FrumError> {
+    #[cfg(target_os = "macos")]
+    return Ok(String::from_utf8_lossy(
+        &Command::new("brew")
+            .arg("--prefix")
+            .arg("openssl")
+            .output()
+            .map_err(FrumError::IoError)?
+            .stdout,
+    )
+    .trim()
+    .to_string());
+    #[cfg(not(target_os = "macos"))]
+    return Ok("/usr/local".to_string());
+}
+
+fn configure_args(
+    installed_dir: &Path,
+    configure_opts: &[String],
+    openssl_dir: Option<&Path>,
+) -> Result<Vec<String>, FrumError> {
+    let mut args = vec![format!("--prefix={}", installed_dir.to_str().unwrap())];
+    args.extend(configure_opts.iter().cloned());
+
+    // Provide a default value for --with-openssl-dir
+    if !configure_opts
+        .iter()
+        .any(|opt| opt.starts_with("--with-openssl-dir"))
+    {
+        let openssl_dir = match openssl_dir {
+            Some(openssl_dir) => openssl_dir.to_str().unwrap().to_string(),
+            None => default_openssl_dir()?,
+        };
+        args.push(format!("--with-openssl-dir={}", openssl_dir));
+    }
+    Ok(args)
+}
+
+/// Puts the options given on the command line last so that they win over `frum.toml`.
+fn merge_configure_opts(project_config: &ProjectConfig, configure_opts: &[String]) -> Vec<String> {
+    project_config
+        .configure_opts
+        .iter()
+        .chain(configure_opts.iter())
+        .cloned()
+        .collect()
+}
+
+/// The compiler that builds through ccache, if `ccache` is on `path`: ccache wrapping
+/// the compiler in `cc`, which comes from `CC`, or else `cc`.
+fn ccache_compiler(
+    path: Option<std::ffi::OsString>,
+    cc: Option<std::ffi::OsString>,
+) -> Option<String> {
+    if !std::env::split_paths(&path?).any(|dir| dir.join("ccache").is_file()) {
+        return None;
+    }
+    let cc = cc
+        .map(|cc| cc.to_string_lossy().trim().to_string())
+        .filter(|cc| !cc.is_empty())
+        .unwrap_or_else(|| "cc".to_string());
+    if cc.starts_with("ccache ") {
+        return Some(cc);
+    }
+    Some(format!("ccache {}", cc))
+}
+
+/// Configures, makes and installs the source in `current_dir`, skipping the
+/// phases `checkpoint` has recorded as done with the same configure arguments.
+/// `compiler` is given to configure as `CC`, which keeps it for make. The prefix is
+/// `installed_dir`, but `make install` installs it under `destdir`.
+#[allow(clippy::too_many_arguments)]
+fn build_package(
+    current_dir: &Path,
+    installed_dir: &Path,
+    destdir: &Path,
+    configure_opts: &[String],
+    openssl_dir: Option<&Path>,
+    jobs: Option<usize>,
+    compiler: Option<&str>,
+    build_log: Option<&Path>,
+    checkpoint: &mut Checkpoint,
+    timings: &mut Timings,
+) -> Result<(), FrumError> {
+    let configure_args = configure_args(installed_dir, configure_opts, openssl_dir)?;
+    let configured_with = match compiler {
+        Some(compiler) => format!("CC={} {}", compiler, configure_args.join(" ")),
+        None => configure_args.join(" "),
+    };
+    if checkpoint.is_done("configure", &configured_with) {
+        debug!("Skip ./configure, it has already run");
+    } else {
+        // A git checkout has to generate configure first
+        if !current_dir.join("configure").exists() {
+            debug!("./autogen.sh");
+            timings.measure("autogen", || {
+                run_build_step(
+                    Command::new("sh")
+                        .arg("autogen.sh")
+                        .current_dir(current_dir),
+                    "autogen.sh failed",
+                    build_log,
+                )
+            })?;
+        }
+        debug!("./configure {}", configure_opts.join(" "));
+        timings.measure("configure", || {
+            run_build_step(
+                Command::new("sh")
+                    .arg("configure")
+                    .args(configure_args)
+                    .envs(compiler.map(|compiler| ("CC", compiler)))
+                    .current_dir(current_dir),
+                "configure failed",
+                build_log,
+            )
+        })?;
+        checkpoint
+            .complete("configure", &configured_with)
+            .map_err(FrumError::IoError)?;
+    }
+    if checkpoint.is_done("make", "") {
+        debug!("Skip make, it has already run");
+    } else {
+        let jobs = jobs.unwrap_or_else(num_cpus::get);
+        debug!("make -j {}", jobs);
+        timings.measure("make", || {
+            run_build_step(
+                Command::new("make")
+                    .arg("-j")
+                    .arg(jobs.to_string())
+                    .current_dir(current_dir),
+                "make failed",
+                build_log,
+            )
+        })?;
+        checkpoint
+            .complete("make", "")
+            .map_err(FrumError::IoError)?;
+    }
+    debug!("make install DESTDIR={}", destdir.display());
+    timings.measure("make_install", || {
+        run_build_step(
+            Command::new("make")
+                .arg("install")
+                .arg(format!("DESTDIR={}", destdir.display()))
+                .current_dir(current_dir),
+            "make install",
+            build_log,
+        )
+    })
+}
+
+/// Runs a build step with its output captured, appending the output to `build_log` if given.
+fn run_build_step(
+    command: &mut Command,
+    failure: &str,
+    build_log: Option<&Path>,
+) -> Result<(), FrumError> {
+    let output = run_interruptible(command, crate::interrupt::flag())?;
+    append_to_build_log(build_log, &output)?;
+    if !output.status.success() {
         return Err(FrumError::CantBuildRuby {
-            stderr: format!(
-                "make install: {}",
-                String::from_utf8_lossy(&make_install.stderr).to_string()
-            ),
+            stderr: format!("{}: {}", failure, String::from_utf8_lossy(&output.stderr)),
         });
     };
     Ok(())
 }
 
+/// Installs the gems listed in the `default-gems` file into the newly built `version`.
+/// A gem that fails to install is reported, but the version stays installed.
+fn install_default_gems(
+    config: &FrumConfig,
+    version: &Version,
+    build_log: Option<&Path>,
+) -> Result<(), FrumError> {
+    let default_gems = match std::fs::read_to_string(config.default_gems_file()) {
+        Ok(default_gems) => parse_default_gems(&default_gems),
+        Err(_) => return Ok(()),
+    };
+    for args in default_gems {
+        outln!(config#Info, "{} Installing the gem {}", "==>".green(), args[0].green());
+        let result = run_interruptible(
+            ruby_command(config, version, binary_path(config, version, "gem"))
+                .arg("install")
+                .args(&args),
+            crate::interrupt::flag(),
+        );
+        // A `gem` that can't even start fails like any other gem, without the installed version
+        let reason = match result {
+            Ok(output) => {
+                append_to_build_log(build_log, &output)?;
+                if output.status.success() {
+                    continue;
+                }
+                String::from_utf8_lossy(&output.stderr).trim().to_string()
+            }
+            Err(FrumError::Interrupted) => return Err(FrumError::Interrupted),
+            Err(err) => err.to_string(),
+        };
+        outln!(config#Error, "{} Can't install the gem {} into Ruby {}: {}", "warning:".yellow().bold(), args[0], version, reason);
+    }
+    Ok(())
+}
+
+/// Runs `ruby -v` of the installed `version` the way `frum bundle` runs it, to catch
+/// builds that compile but don't run, such as ones missing a shared library. A failing
+/// version is left installed for inspection.
+fn verify_build(config: &FrumConfig, version: &Version) -> Result<(), FrumError> {
+    let output = run_interruptible(
+        ruby_command(config, version, binary_path(config, version, "ruby")).arg("-v"),
+        crate::interrupt::flag(),
+    )?;
+    if !output.status.success() {
+        return Err(FrumError::BuildDoesntRun {
+            version: version.clone(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let reported = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    debug!("Ruby {} reports {}", version, reported);
+    // Builds from source aren't named after a release, so only running matters for them
+    if let Version::Semver(semver) = version {
+        // `ruby -v` prints prereleases without the dash and may add the patch level,
+        // e.g. `ruby 3.3.0preview1 (...)` or `ruby 2.7.2p137 (...)`
+        let expected = semver.to_string().replacen('-', "", 1);
+        let matches = reported
+            .split_whitespace()
+            .nth(1)
+            .and_then(|reported| reported.strip_prefix(&expected))
+            .is_some_and(|patch_level| {
+                patch_level.is_empty()
+                    || patch_level
+                        .strip_prefix('p')
+                        .is_some_and(|level| level.chars().all(|c| c.is_ascii_digit()))
+            });
+        if !matches {
+            return Err(FrumError::BuildVersionMismatch {
+                version: version.clone(),
+                reported,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// The `gem install` arguments of each line of a `default-gems` file, which are read like
+/// rbenv-default-gems does: a gem name, optionally followed by a version requirement
+/// such as `~>2.0` or by `--pre`. Blank lines and lines starting with `#` are skipped.
+fn parse_default_gems(default_gems: &str) -> Vec<Vec<String>> {
+    default_gems
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|words| !words.is_empty() && !words[0].starts_with('#'))
+        .map(|words| match words.get(1) {
+            Some(&"--pre") => vec![words[0].to_string(), "--pre".to_string()],
+            Some(requirement) => vec![
+                words[0].to_string(),
+                "--version".to_string(),
+                requirement.to_string(),
+            ],
+            None => vec![words[0].to_string()],
+        })
+        .collect()
+}
+
+fn append_to_build_log(build_log: Option<&Path>, output: &Output) -> Result<(), FrumError> {
+    if let Some(build_log) = build_log {
+        let mut log_file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(build_log)
+            .map_err(FrumError::IoError)?;
+        log_file
+            .write_all(&output.stdout)
+            .and_then(|_| log_file.write_all(&output.stderr))
+            .map_err(FrumError::IoError)?;
+    }
+    Ok(())
+}
+
+/// Runs `command` to completion like `Command::output`, but stops it and
+/// everything it has started as soon as `interrupted` is set.
+fn run_interruptible(command: &mut Command, interrupted: &AtomicBool) -> Result<Output, FrumError> {
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(command, 0);
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(FrumError::IoError)?;
+    let read_all = |mut pipe: Box<dyn Read + Send>| {
+        std::thread::spawn(move || {
+            let mut buffer = vec![];
+            pipe.read_to_end(&mut buffer).map(|_| buffer)
+        })
+    };
+    let stdout = read_all(Box::new(child.stdout.take().unwrap()));
+    let stderr = read_all(Box::new(child.stderr.take().unwrap()));
+    let status = loop {
+        if interrupted.load(Ordering::SeqCst) {
+            #[cfg(unix)]
+            unsafe {
+                libc::kill(-(child.id() as libc::pid_t), libc::SIGTERM);
+            }
+            #[cfg(windows)]
+            child.kill().ok();
+            child.wait().ok();
+            return Err(FrumError::Interrupted);
+        }
+        match child.try_wait().map_err(FrumError::IoError)? {
+            Some(status) => break status,
+            None => std::thread::sleep(std::time::Duration::from_millis(50)),
+        }
+    };
+    Ok(Output {
+        status,
+        stdout: stdout.join().unwrap().map_err(FrumError::IoError)?,
+        stderr: stderr.join().unwrap().map_err(FrumError::IoError)?,
+    })
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::command::Command;
     use crate::config::FrumConfig;
     use crate::version::Version;
     use tempfile::tempdir;
 
+    /// Serves a stub Ruby archive, whose build only creates `bin/ruby`, once and returns the mirror URL.
+    #[cfg(unix)]
+    pub(crate) fn serve_stub_ruby(version: &str) -> Url {
+        let archive = stub_ruby_archive(version);
+        let content_length = archive.len();
+        serve_once(archive, content_length)
+    }
+
+    /// A Ruby source archive whose build only creates `bin/ruby`.
+    #[cfg(unix)]
+    pub(crate) fn stub_ruby_archive(version: &str) -> Vec<u8> {
+        stub_ruby_archive_with(
+            version,
+            indoc::indoc!(
+                r#"
+                prefix="${1#--prefix=}"
+                printf 'all:\n\ttrue\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n' "$prefix" "$prefix" > Makefile
+                "#
+            ),
+        )
+    }
+
+    /// Archives a Ruby source tree whose configure script is `configure`.
+    #[cfg(unix)]
+    pub(crate) fn stub_ruby_archive_with(version: &str, configure: &str) -> Vec<u8> {
+        let source_dir = tempdir().unwrap();
+        let ruby_dir = source_dir.path().join(format!("ruby-{}", version));
+        std::fs::create_dir_all(&ruby_dir).unwrap();
+        std::fs::write(ruby_dir.join("configure"), configure).unwrap();
+        std::process::Command::new("tar")
+            .arg("-cJf")
+            .arg("-")
+            .arg(format!("ruby-{}", version))
+            .current_dir(source_dir.path())
+            .output()
+            .unwrap()
+            .stdout
+    }
+
+    /// Responds to a single request with `body`, advertising `content_length` bytes.
+    pub(crate) fn serve_once(body: Vec<u8>, content_length: usize) -> Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mirror = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = std::io::BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while request.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                content_length
+            )
+            .unwrap();
+            stream.write_all(&body).unwrap();
+        });
+        mirror
+    }
+
+    /// A `file://` mirror holding a stub archive of each of `versions`.
+    #[cfg(unix)]
+    fn stub_ruby_mirror(versions: &[&str]) -> (tempfile::TempDir, Url) {
+        let mirror_dir = tempdir().unwrap();
+        for version in versions {
+            let series = mirror_dir
+                .path()
+                .join(&version[..version.rfind('.').unwrap()]);
+            std::fs::create_dir_all(&series).unwrap();
+            std::fs::write(
+                series.join(format!("ruby-{}.tar.xz", version)),
+                stub_ruby_archive(version),
+            )
+            .unwrap();
+        }
+        let mirror = Url::from_directory_path(mirror_dir.path()).unwrap();
+        (mirror_dir, mirror)
+    }
+
+    #[cfg(unix)]
     #[test]
     fn test_install_second_version() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            ..Default::default()
-        };
+        let (_mirror_dir, mirror) = stub_ruby_mirror(&["2.7.0", "2.6.4"]);
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(mirror);
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.7.0").unwrap(),
-            ))),
-            configure_opts: vec![],
+            version: Some(InputVersion::Full(Version::parse("2.7.0").unwrap())),
+            ..Default::default()
         }
         .apply(&config)
         .expect("Can't install 2.7.0");
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
-            configure_opts: vec![],
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
         }
         .apply(&config)
         .expect("Can't install 2.6.4");
 
         assert_eq!(
-            std::fs::read_link(&config.default_version_dir())
+            std::fs::read_link(config.default_version_dir())
                 .unwrap()
                 .components()
-                .last(),
+                .next_back(),
             Some(std::path::Component::Normal(std::ffi::OsStr::new("2.7.0")))
         );
     }
 
+    #[cfg(unix)]
     #[test]
     fn test_install_default_version() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            ..Default::default()
-        };
+        let (_mirror_dir, mirror) = stub_ruby_mirror(&["2.6.4"]);
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(mirror);
 
         Install {
-            version: Some(InputVersion::Full(Version::Semver(
-                semver::Version::parse("2.6.4").unwrap(),
-            ))),
-            configure_opts: vec![],
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
         }
         .apply(&config)
         .expect("Can't install");
@@ -309,4 +2060,1147 @@ mod tests {
             .exists());
         assert!(config.default_version_dir().exists());
     }
+
+    #[test]
+    fn test_resolve_latest_in_channel() {
+        let config = FrumConfig {
+            offline: true,
+            ..FrumConfig::default().with_base_dir(tempdir().unwrap().path())
+        };
+        std::fs::write(
+            config.cache_dir().join("index.txt"),
+            "name\turl\tsha1\tsha256\tsha512\nruby-3.3.6\t-\ta\tb\tc\nruby-3.4.0-preview2\t-\ta\tb\tc\nruby-3.4.0-dev\t-\ta\tb\tc\n",
+        )
+        .unwrap();
+        let latest = |channel| {
+            resolve_version(&config, &InputVersion::Latest, false, channel)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(latest(None), "3.3.6");
+        assert_eq!(latest(Some(Channel::Preview)), "3.4.0-preview2");
+        assert_eq!(latest(Some(Channel::Dev)), "3.4.0-dev");
+    }
+
+    #[test]
+    fn test_install_plan_resolves_partial_version() {
+        let config = FrumConfig::default();
+        let available_versions = ["2.6.6", "2.7.0", "2.7.2", "3.0.0"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect::<Vec<_>>();
+        let version = select_version(&InputVersion::MajorMinor(2, 7), &available_versions).unwrap();
+        let plan = InstallPlan {
+            version: version.to_string(),
+            url: package_url(config.ruby_build_mirror.clone(), &version).to_string(),
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&JsonEnvelope::new(&plan).to_string()).unwrap();
+        assert_eq!(json["schema"], crate::json::SCHEMA);
+        assert_eq!(json["version"], "2.7.2");
+        assert_eq!(
+            json["url"],
+            "https://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.2.tar.xz"
+        );
+    }
+
+    #[test]
+    fn test_configure_args_use_openssl_sidecar() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let openssl_dir = crate::sidecar::openssl_prefix(&config, "1.1.1k");
+        let args = configure_args(
+            Path::new("/tmp/ruby"),
+            &["--disable-werror".to_string()],
+            Some(&openssl_dir),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "--prefix=/tmp/ruby".to_string(),
+                "--disable-werror".to_string(),
+                format!("--with-openssl-dir={}", openssl_dir.to_str().unwrap()),
+            ]
+        );
+
+        let args = configure_args(
+            Path::new("/tmp/ruby"),
+            &["--with-openssl-dir=/opt/openssl".to_string()],
+            Some(&openssl_dir),
+        )
+        .unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "--prefix=/tmp/ruby".to_string(),
+                "--with-openssl-dir=/opt/openssl".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_truncated_download() {
+        let response = reqwest::blocking::get(serve_once(b"ruby".to_vec(), 10)).unwrap();
+        let content_length = response.content_length();
+        let result = download_into(response, content_length, &mut vec![]);
+
+        assert!(matches!(
+            result,
+            Err(FrumError::TruncatedDownload {
+                expected: 10,
+                received: 4
+            })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_download_seeds_cache() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        let install = Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        };
+
+        Install {
+            only_download: true,
+            ..install.clone()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(config.cache_dir().join("ruby-2.6.4.tar.xz").exists());
+        assert!(!config.versions_dir().join("2.6.4").exists());
+
+        // The stub mirror only serves one request, so this builds from the cache
+        install.apply(&config).unwrap();
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_concurrent_install_of_same_version_fails() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        let install = Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        };
+        let version = Version::parse("2.6.4").unwrap();
+
+        std::thread::scope(|scope| {
+            // Another install holds the lock while this one starts
+            let lock = lock_version(&config, &version).unwrap();
+            let second = scope.spawn(|| install.apply(&config)).join().unwrap();
+            assert!(matches!(second, Err(FrumError::AlreadyInstalling { .. })));
+            assert!(!config.versions_dir().join("2.6.4").exists());
+            drop(lock);
+        });
+
+        install.apply(&config).unwrap();
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+
+    #[test]
+    fn test_skip_installed_version() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let installation_dir = config.versions_dir().join("2.6.4");
+        std::fs::create_dir_all(&installation_dir).unwrap();
+        std::fs::write(installation_dir.join("stub"), "").unwrap();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(installation_dir.join("stub").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_force_rebuild_installed_version() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        let installation_dir = config.versions_dir().join("2.6.4");
+        std::fs::create_dir_all(&installation_dir).unwrap();
+        std::fs::write(installation_dir.join("stub"), "").unwrap();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            force: true,
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(!installation_dir.join("stub").exists());
+        assert!(installation_dir.join("bin").join("ruby").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_register_prebuilt_ruby() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let prebuilt = tempdir().unwrap();
+        std::fs::create_dir_all(prebuilt.path().join("bin")).unwrap();
+        std::fs::write(prebuilt.path().join("bin").join("ruby"), "ruby").unwrap();
+        std::os::unix::fs::symlink("ruby", prebuilt.path().join("bin").join("ruby3.2")).unwrap();
+        let install = Install {
+            version: Some(InputVersion::Full(Version::parse("3.2.2").unwrap())),
+            prebuilt: Some(prebuilt.path().to_path_buf()),
+            ..Default::default()
+        };
+
+        install.apply(&config).unwrap();
+
+        let installed = crate::version::installed_versions(&config).unwrap();
+        assert_eq!(installed, vec![Version::parse("3.2.2").unwrap()]);
+        let resolved = "3.2"
+            .parse::<InputVersion>()
+            .unwrap()
+            .to_version(&installed)
+            .cloned();
+        assert_eq!(resolved, Some(Version::parse("3.2.2").unwrap()));
+        let installation_dir = config.versions_dir().join("3.2.2");
+        assert_eq!(
+            std::fs::read_link(installation_dir.join("bin").join("ruby3.2")).unwrap(),
+            Path::new("ruby")
+        );
+        assert!(installation_dir.join(PREBUILT_MANIFEST).exists());
+        assert!(config.default_version_dir().exists());
+
+        std::fs::remove_file(prebuilt.path().join("bin").join("ruby")).unwrap();
+        assert!(matches!(
+            Install {
+                force: true,
+                ..install
+            }
+            .apply(&config),
+            Err(FrumError::NotRubyInstallation { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_records_phase_timings() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        let mut timings = Timings::default();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .install(&config, &mut timings)
+        .unwrap();
+
+        assert_eq!(
+            timings
+                .phases()
+                .iter()
+                .map(|(phase, _)| *phase)
+                .collect::<Vec<_>>(),
+            vec!["download", "extract", "configure", "make", "make_install"]
+        );
+        assert!(timings
+            .to_json()
+            .starts_with("{\"schema\":1,\"timings\":{\"download\":"));
+    }
+
+    #[test]
+    fn test_dependencies_only_skips_ruby_build() {
+        // No mirror is served, since Ruby is neither downloaded nor built
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let openssl_dir = crate::sidecar::openssl_prefix(&config, "1.1.1k");
+        std::fs::create_dir_all(&openssl_dir).unwrap();
+        std::fs::write(openssl_dir.join(crate::sidecar::COMPLETE_MARKER), "").unwrap();
+        let mut timings = Timings::default();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            openssl_version: Some("1.1.1k".to_string()),
+            dependencies_only: true,
+            ..Default::default()
+        }
+        .install(&config, &mut timings)
+        .unwrap();
+
+        assert_eq!(
+            timings
+                .phases()
+                .iter()
+                .map(|(phase, _)| *phase)
+                .collect::<Vec<_>>(),
+            vec!["openssl"]
+        );
+        assert!(openssl_dir.exists());
+        assert!(!config.versions_dir().join("2.6.4").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_post_install_runs_with_new_version() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_stub_ruby("2.6.4"));
+        let output_dir = tempdir().unwrap();
+        let path_file = output_dir.path().join("path.txt");
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            post_install: vec![
+                format!("echo \"$PATH\" > {}", path_file.display()),
+                "exit 1".to_string(),
+            ],
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        let path = std::fs::read_to_string(&path_file).unwrap();
+        assert_eq!(
+            std::env::split_paths(path.trim()).next(),
+            Some(config.versions_dir().join("2.6.4").join("bin"))
+        );
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+
+    #[test]
+    fn test_verify_checksum_from_file() {
+        let dir = tempdir().unwrap();
+        let archive_path = dir.path().join("ruby-3.2.2.tar.xz");
+        std::fs::write(&archive_path, "ruby").unwrap();
+        let checksum_file = dir.path().join("SHA256SUMS");
+        std::fs::write(
+            &checksum_file,
+            indoc::indoc!(
+                "
+                b9138194ffe9e7c8bb6d79d1ed56259553d18d9cb60b66e3ba5aa2e5b078055a  3.2/ruby-3.2.2.tar.xz
+                0000000000000000000000000000000000000000000000000000000000000000 *ruby-3.2.1.tar.xz
+                "
+            ),
+        )
+        .unwrap();
+
+        assert!(verify_checksum(&checksum_file, "ruby-3.2.2.tar.xz", &archive_path).is_ok());
+        assert!(matches!(
+            verify_checksum(&checksum_file, "ruby-3.2.1.tar.xz", &archive_path),
+            Err(FrumError::ChecksumMismatch { actual, .. })
+                if actual == "b9138194ffe9e7c8bb6d79d1ed56259553d18d9cb60b66e3ba5aa2e5b078055a"
+        ));
+        assert!(matches!(
+            verify_checksum(&checksum_file, "ruby-3.3.0.tar.xz", &archive_path),
+            Err(FrumError::ChecksumNotListed { .. })
+        ));
+
+        std::fs::write(&checksum_file, "not a checksum\n").unwrap();
+        assert!(matches!(
+            verify_checksum(&checksum_file, "ruby-3.2.2.tar.xz", &archive_path),
+            Err(FrumError::ChecksumsError(
+                crate::checksums::Error::InvalidLine { line: 1, .. }
+            ))
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_default_gems_installed_after_build() {
+        // The stub `gem` records its arguments next to the Ruby and fails for `broken`
+        let archive = stub_ruby_archive_with(
+            "2.6.4",
+            indoc::indoc!(
+                r#"
+                prefix="${1#--prefix=}"
+                printf '#!/bin/sh\necho "$@" >> "$(dirname "$0")/../gems.txt"\ntest "$2" != broken\n' > gem
+                chmod +x gem
+                printf 'all:\n\ttrue\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n\tcp gem $(DESTDIR)%s/bin/gem\n' "$prefix" "$prefix" "$prefix" > Makefile
+                "#
+            ),
+        );
+        let content_length = archive.len();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_once(archive, content_length));
+        let config = FrumConfig {
+            config_dir: Some(tempdir().unwrap().into_path()),
+            ..config
+        };
+        std::fs::write(
+            config.default_gems_file(),
+            "# Tools for every Ruby\nbundler ~>2.0\n\nbroken\nrubocop --pre\n",
+        )
+        .unwrap();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(config.versions_dir().join("2.6.4").join("gems.txt")).unwrap(),
+            "install bundler --version ~>2.0\ninstall broken\ninstall rubocop --pre\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_default_gems_without_gem_keep_the_install() {
+        let archive = stub_ruby_archive("2.6.4");
+        let content_length = archive.len();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_once(archive, content_length));
+        let config = FrumConfig {
+            config_dir: Some(tempdir().unwrap().into_path()),
+            ..config
+        };
+        std::fs::write(config.default_gems_file(), "bundler\n").unwrap();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resume_failed_make() {
+        let state_dir = tempdir().unwrap();
+        let configured = state_dir.path().join("configured");
+        let make_works = state_dir.path().join("make-works");
+        let archive = stub_ruby_archive_with(
+            "2.6.4",
+            &format!(
+                indoc::indoc!(
+                    r#"
+                    prefix="${{1#--prefix=}}"
+                    echo configured >> {}
+                    printf 'all:\n\ttest -f {}\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n' "$prefix" "$prefix" > Makefile
+                    "#
+                ),
+                configured.display(),
+                make_works.display()
+            ),
+        );
+        let content_length = archive.len();
+        // The archive can only be downloaded once
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_once(archive, content_length));
+        let install = Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            install.install(&config, &mut Timings::default()),
+            Err(FrumError::CantBuildRuby { .. })
+        ));
+        std::fs::write(&make_works, "").unwrap();
+        let mut timings = Timings::default();
+        Install {
+            resume: true,
+            ..install
+        }
+        .install(&config, &mut timings)
+        .unwrap();
+
+        assert_eq!(
+            timings
+                .phases()
+                .iter()
+                .map(|(phase, _)| *phase)
+                .collect::<Vec<_>>(),
+            vec!["make", "make_install"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&configured).unwrap(),
+            "configured\n"
+        );
+        assert!(config.versions_dir().join("2.6.4/bin/ruby").exists());
+        assert!(!config.versions_dir().join(".downloads/ruby-2.6.4").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_from_file_mirror() {
+        let mirror_dir = tempdir().unwrap();
+        std::fs::create_dir_all(mirror_dir.path().join("2.6")).unwrap();
+        std::fs::write(
+            mirror_dir.path().join("2.6").join("ruby-2.6.4.tar.xz"),
+            stub_ruby_archive("2.6.4"),
+        )
+        .unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(Url::from_directory_path(mirror_dir.path()).unwrap());
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(config
+            .versions_dir()
+            .join("2.6.4")
+            .join("bin")
+            .join("ruby")
+            .exists());
+        assert!(matches!(
+            Install {
+                version: Some(InputVersion::Full(Version::parse("2.7.2").unwrap())),
+                ..Default::default()
+            }
+            .apply(&config),
+            Err(FrumError::VersionNotFound { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_interrupt_stops_build_step() {
+        let interrupted = AtomicBool::new(false);
+        let started = std::time::Instant::now();
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(std::time::Duration::from_millis(100));
+                interrupted.store(true, Ordering::SeqCst);
+            });
+            let result = run_interruptible(
+                std::process::Command::new("sh")
+                    .arg("-c")
+                    .arg("sleep 30 & wait"),
+                &interrupted,
+            );
+            assert!(matches!(result, Err(FrumError::Interrupted)));
+        });
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_git_source_runs_autogen() {
+        let repository = tempdir().unwrap();
+        std::fs::write(repository.path().join("configure.ac"), "AC_INIT([ruby])\n").unwrap();
+        std::fs::write(
+            repository.path().join("autogen.sh"),
+            indoc::indoc!(
+                r#"
+                echo 'autogen' > autogen.ran
+                echo 'prefix="${1#--prefix=}"' > configure
+                echo 'printf "all:\n\ttrue\ninstall:\n\tmkdir -p \$(DESTDIR)%s/bin\n\tcp autogen.ran \$(DESTDIR)%s/bin/ruby\n" "$prefix" "$prefix" > Makefile' >> configure
+                "#
+            ),
+        )
+        .unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .args(["-c", "user.name=frum", "-c", "user.email=frum@example.com"])
+                .args(args)
+                .current_dir(repository.path())
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8(output.stdout).unwrap()
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "Stub Ruby"]);
+        let hash = git(&["rev-parse", "--short=12", "HEAD"]);
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let mut timings = Timings::default();
+
+        Install {
+            source: Some(repository.path().display().to_string()),
+            ..Default::default()
+        }
+        .install(&config, &mut timings)
+        .unwrap();
+
+        let ruby = config
+            .versions_dir()
+            .join(format!("dev-{}", hash.trim()))
+            .join("bin")
+            .join("ruby");
+        assert_eq!(std::fs::read_to_string(ruby).unwrap(), "autogen\n");
+        assert!(timings
+            .phases()
+            .iter()
+            .any(|(phase, _)| *phase == "autogen"));
+        assert!(!repository.path().join("configure").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_patch_source_before_configure() {
+        let archive = stub_ruby_archive_with(
+            "2.4.10",
+            indoc::indoc!(
+                r#"
+                prefix="${1#--prefix=}"
+                test "$(cat patched)" = yes || exit 1
+                printf 'all:\n\ttrue\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n' "$prefix" "$prefix" > Makefile
+                "#
+            ),
+        );
+        let content_length = archive.len();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(serve_once(archive, content_length));
+        let patches_dir = tempdir().unwrap();
+        let patch = patches_dir.path().join("patched.patch");
+        std::fs::write(
+            &patch,
+            "--- /dev/null\n+++ b/patched\n@@ -0,0 +1 @@\n+yes\n",
+        )
+        .unwrap();
+        let broken_patch = patches_dir.path().join("broken.patch");
+        std::fs::write(
+            &broken_patch,
+            "--- a/configure\n+++ b/configure\n@@ -1 +1 @@\n-missing\n+line\n",
+        )
+        .unwrap();
+        let install = Install {
+            version: Some(InputVersion::Full(Version::parse("2.4.10").unwrap())),
+            patches: vec![broken_patch.to_string_lossy().to_string()],
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            install.install(&config, &mut Timings::default()),
+            Err(FrumError::CantApplyPatch { .. })
+        ));
+        // The downloaded archive is extracted again without the broken patch
+        Install {
+            patches: vec![patch.to_string_lossy().to_string()],
+            resume: true,
+            ..install
+        }
+        .install(&config, &mut Timings::default())
+        .unwrap();
+
+        assert!(config
+            .versions_dir()
+            .join("2.4.10")
+            .join("bin")
+            .join("ruby")
+            .exists());
+        assert!(matches!(
+            read_patch(&patches_dir.path().join("missing.patch").to_string_lossy()),
+            Err(FrumError::PatchNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_source_root_of_single_top_directory() {
+        let extracted_dir = tempdir().unwrap();
+        let top_dir = extracted_dir.path().join("ruby-3.2.0");
+        std::fs::create_dir_all(top_dir.join("src")).unwrap();
+        std::fs::write(top_dir.join("configure"), "").unwrap();
+
+        assert_eq!(source_root(extracted_dir.path(), None).unwrap(), top_dir);
+        assert_eq!(
+            source_root(extracted_dir.path(), Some(0)).unwrap(),
+            extracted_dir.path()
+        );
+        assert!(matches!(
+            source_root(extracted_dir.path(), Some(2)),
+            Err(FrumError::CantStripPrefix { components: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_source_root_of_multiple_entries() {
+        let extracted_dir = tempdir().unwrap();
+        std::fs::create_dir_all(extracted_dir.path().join("src")).unwrap();
+        std::fs::write(extracted_dir.path().join("configure"), "").unwrap();
+
+        assert_eq!(
+            source_root(extracted_dir.path(), None).unwrap(),
+            extracted_dir.path()
+        );
+
+        let nested_dir = tempdir().unwrap();
+        let source_dir = nested_dir.path().join("build").join("ruby");
+        std::fs::create_dir_all(&source_dir).unwrap();
+        assert_eq!(source_root(nested_dir.path(), Some(2)).unwrap(), source_dir);
+        assert!(matches!(
+            source_root(tempdir().unwrap().path(), None),
+            Err(FrumError::TarIsEmpty)
+        ));
+    }
+
+    #[test]
+    fn test_parse_source() {
+        assert_eq!(
+            parse_source("https://example.com/ruby-3.2.0.tar.gz").unwrap(),
+            Source::Archive(Url::parse("https://example.com/ruby-3.2.0.tar.gz").unwrap())
+        );
+        assert_eq!(
+            parse_source("https://github.com/ruby/ruby.git#v3_2_0").unwrap(),
+            Source::Git {
+                repository: "https://github.com/ruby/ruby.git".to_string(),
+                reference: Some("v3_2_0".to_string()),
+            }
+        );
+        assert_eq!(
+            parse_source("../ruby").unwrap(),
+            Source::Git {
+                repository: "../ruby".to_string(),
+                reference: None,
+            }
+        );
+        assert!(matches!(
+            parse_source("ruby-3.2.0.tar.xz").unwrap(),
+            Source::Archive(url) if url.scheme() == "file"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_project_configure_opts_reach_configure() {
+        let project_dir = tempdir().unwrap();
+        std::fs::write(
+            project_dir.path().join("frum.toml"),
+            indoc::indoc!(
+                r#"
+                [build]
+                configure_opts = ["--disable-install-doc", "--with-jemalloc"]
+                jobs = 2
+                "#
+            ),
+        )
+        .unwrap();
+        let source_dir = tempdir().unwrap();
+        std::fs::write(
+            source_dir.path().join("configure"),
+            "echo \"$@\" > configure.log\nprintf 'all:\\n\\ttrue\\ninstall:\\n\\ttrue\\n' > Makefile\n",
+        )
+        .unwrap();
+
+        let project_config = ProjectConfig::find(project_dir.path().to_path_buf())
+            .unwrap()
+            .unwrap();
+        let configure_opts =
+            merge_configure_opts(&project_config, &["--with-jemalloc=no".to_string()]);
+        build_package(
+            source_dir.path(),
+            Path::new("/tmp/ruby"),
+            source_dir.path(),
+            &configure_opts,
+            Some(Path::new("/opt/openssl")),
+            project_config.jobs,
+            None,
+            None,
+            &mut Checkpoint::disabled(),
+            &mut Timings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(source_dir.path().join("configure.log")).unwrap(),
+            "--prefix=/tmp/ruby --disable-install-doc --with-jemalloc --with-jemalloc=no --with-openssl-dir=/opt/openssl\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_with_ccache() {
+        let bin_dir = tempdir().unwrap();
+        assert_eq!(ccache_compiler(Some(bin_dir.path().into()), None), None);
+        std::fs::write(bin_dir.path().join("ccache"), "").unwrap();
+        let path = Some(bin_dir.path().as_os_str().to_os_string());
+        assert_eq!(
+            ccache_compiler(path.clone(), None),
+            Some("ccache cc".to_string())
+        );
+        assert_eq!(
+            ccache_compiler(path.clone(), Some("ccache clang".into())),
+            Some("ccache clang".to_string())
+        );
+        let compiler = ccache_compiler(path, Some("gcc-12".into()));
+        assert_eq!(compiler.as_deref(), Some("ccache gcc-12"));
+
+        let source_dir = tempdir().unwrap();
+        std::fs::write(
+            source_dir.path().join("configure"),
+            "echo \"$CC\" > cc.log\nprintf 'all:\\n\\ttrue\\ninstall:\\n\\ttrue\\n' > Makefile\n",
+        )
+        .unwrap();
+        build_package(
+            source_dir.path(),
+            Path::new("/tmp/ruby"),
+            source_dir.path(),
+            &[],
+            None,
+            Some(1),
+            compiler.as_deref(),
+            None,
+            &mut Checkpoint::disabled(),
+            &mut Timings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(source_dir.path().join("cc.log")).unwrap(),
+            "ccache gcc-12\n"
+        );
+    }
+
+    #[test]
+    fn test_select_install_strategy() {
+        assert_eq!(
+            Install::default().strategy_kind(),
+            StrategyKind::SourceBuild
+        );
+        assert_eq!(
+            Install {
+                resume: true,
+                use_ccache: true,
+                ..Default::default()
+            }
+            .strategy_kind(),
+            StrategyKind::SourceBuild
+        );
+        assert_eq!(
+            Install {
+                prebuilt: Some(PathBuf::from("/opt/ruby")),
+                ..Default::default()
+            }
+            .strategy_kind(),
+            StrategyKind::NoBuildImport
+        );
+    }
+
+    /// Records the steps it goes through, failing at `failing_step`.
+    struct StubStrategy {
+        steps: Vec<&'static str>,
+        failing_step: Option<&'static str>,
+    }
+
+    impl StubStrategy {
+        fn step(&mut self, step: &'static str) -> Result<(), FrumError> {
+            self.steps.push(step);
+            match self.failing_step {
+                Some(failing_step) if failing_step == step => Err(FrumError::TarIsEmpty),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl InstallStrategy for StubStrategy {
+        fn download(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+            self.step("download")
+        }
+
+        fn prepare(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+            self.step("prepare")
+        }
+
+        fn build(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+            self.step("build")
+        }
+
+        fn finalize(&mut self, _: &FrumConfig, _: &mut Timings) -> Result<(), FrumError> {
+            self.step("finalize")
+        }
+    }
+
+    #[test]
+    fn test_strategy_runs_steps_in_order() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let run = |failing_step| {
+            let mut strategy = StubStrategy {
+                steps: vec![],
+                failing_step,
+            };
+            let result = strategy.run(&config, &mut Timings::default());
+            (result.is_ok(), strategy.steps)
+        };
+
+        assert_eq!(
+            run(None),
+            (true, vec!["download", "prepare", "build", "finalize"])
+        );
+        assert_eq!(run(Some("prepare")), (false, vec!["download", "prepare"]));
+
+        let mut import = NoBuildImport {
+            install: &Install {
+                prebuilt: Some(PathBuf::from("/opt/ruby")),
+                ..Default::default()
+            },
+            requested: "2.7".parse().unwrap(),
+            version: None,
+        };
+        assert!(matches!(
+            import.run(&config, &mut Timings::default()),
+            Err(FrumError::CantRegisterPartialVersion { .. })
+        ));
+        assert!(config.versions_dir().read_dir().unwrap().next().is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_build_runs_ruby() {
+        let mirror_dir = tempdir().unwrap();
+        std::fs::create_dir_all(mirror_dir.path().join("2.7")).unwrap();
+        // 2.7.3 is built with a `ruby` that still reports 2.7.2
+        for version in &["2.7.2", "2.7.3"] {
+            std::fs::write(
+                mirror_dir
+                    .path()
+                    .join("2.7")
+                    .join(format!("ruby-{}.tar.xz", version)),
+                stub_ruby_archive_with(
+                    version,
+                    indoc::indoc!(
+                        r#"
+                        prefix="${1#--prefix=}"
+                        printf '#!/bin/sh\necho "ruby 2.7.2p137 (2020-10-01 revision 5445e04352) [x86_64-linux]"\n' > ruby
+                        printf 'all:\n\ttrue\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\tinstall -m 755 ruby $(DESTDIR)%s/bin/ruby\n' "$prefix" "$prefix" > Makefile
+                        "#
+                    ),
+                ),
+            )
+            .unwrap();
+        }
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(Url::from_directory_path(mirror_dir.path()).unwrap());
+        let install = |version: &str| {
+            Install {
+                version: Some(InputVersion::Full(Version::parse(version).unwrap())),
+                verify_build: true,
+                ..Default::default()
+            }
+            .apply(&config)
+        };
+
+        install("2.7.2").unwrap();
+        let result = install("2.7.3");
+
+        assert!(matches!(
+            result,
+            Err(FrumError::BuildVersionMismatch { ref reported, .. }) if reported.starts_with("ruby 2.7.2p137")
+        ));
+        assert!(config
+            .versions_dir()
+            .join("2.7.3")
+            .join("bin")
+            .join("ruby")
+            .exists());
+
+        std::fs::write(
+            config.versions_dir().join("2.7.2").join("bin").join("ruby"),
+            "#!/bin/sh\necho 'libruby.so.2.7: cannot open shared object file' >&2\nexit 127\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            verify_build(&config, &Version::parse("2.7.2").unwrap()),
+            Err(FrumError::BuildDoesntRun { ref stderr, .. }) if stderr.contains("libruby.so.2.7")
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_in_tmpdir() {
+        let mirror_dir = tempdir().unwrap();
+        std::fs::create_dir_all(mirror_dir.path().join("2.7")).unwrap();
+        std::fs::write(
+            mirror_dir.path().join("2.7").join("ruby-2.7.2.tar.xz"),
+            stub_ruby_archive_with(
+                "2.7.2",
+                indoc::indoc!(
+                    r#"
+                    prefix="${1#--prefix=}"
+                    printf 'all:\n\ttrue\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n\techo %s > $(DESTDIR)%s/built-in\n' "$prefix" "$prefix" "$(pwd)" "$prefix" > Makefile
+                    "#
+                ),
+            ),
+        )
+        .unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(Url::from_directory_path(mirror_dir.path()).unwrap());
+        let tmpdir = tempdir().unwrap();
+
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.7.2").unwrap())),
+            tmpdir: Some(tmpdir.path().join("builds")),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        let installation_dir = config.versions_dir().join("2.7.2");
+        assert!(installation_dir.join("bin").join("ruby").exists());
+        assert!(std::fs::read_to_string(installation_dir.join("built-in"))
+            .unwrap()
+            .starts_with(&tmpdir.path().join("builds").to_string_lossy().to_string()));
+        assert!(!tmpdir.path().join("builds").join("ruby-2.7.2").exists());
+        assert!(!config.versions_dir().join(".downloads/ruby-2.7.2").exists());
+
+        let prebuilt = tempdir().unwrap();
+        std::fs::create_dir_all(prebuilt.path().join("bin")).unwrap();
+        std::fs::write(prebuilt.path().join("bin").join("ruby"), "").unwrap();
+        Install {
+            version: Some(InputVersion::Full(Version::parse("3.0.0").unwrap())),
+            prebuilt: Some(prebuilt.path().to_path_buf()),
+            tmpdir: Some(tmpdir.path().join("builds")),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(config
+            .versions_dir()
+            .join("3.0.0")
+            .join("bin")
+            .join("ruby")
+            .exists());
+        assert_eq!(
+            std::fs::read_dir(tmpdir.path().join("builds"))
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_verify_valid_signature() {
+        let fixtures = Path::new("tests/fixtures/signature");
+        verify_detached_signature(
+            &fixtures.join("archive.txt"),
+            &fixtures.join("archive.txt.asc"),
+            Some(&fixtures.join("public.asc")),
+        )
+        .expect("Can't verify the signature");
+    }
+
+    #[test]
+    fn test_verify_invalid_signature() {
+        let fixtures = Path::new("tests/fixtures/signature");
+        let temp_dir = tempdir().unwrap();
+        let tampered_archive = temp_dir.path().join("archive.txt");
+        std::fs::write(&tampered_archive, "tampered archive\n").unwrap();
+        let result = verify_detached_signature(
+            &tampered_archive,
+            &fixtures.join("archive.txt.asc"),
+            Some(&fixtures.join("public.asc")),
+        );
+        assert!(matches!(result, Err(FrumError::SignatureInvalid { .. })));
+    }
+
+    #[test]
+    fn test_run_parallel_respects_bound() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let versions = ["2.6.4", "2.7.2", "3.0.0", "3.1.0", "3.2.0"];
+
+        let results = run_parallel(&versions, 2, |_| {
+            let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(now_running, Ordering::SeqCst);
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            Ok::<(), ()>(())
+        });
+
+        assert_eq!(results, vec![Ok(()); versions.len()]);
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_batch_share_doesnt_override_project_jobs() {
+        let install = Install {
+            batch_jobs: Some(4),
+            ..Default::default()
+        };
+        let project_config = ProjectConfig {
+            jobs: Some(2),
+            ..Default::default()
+        };
+
+        assert_eq!(install.make_jobs(&project_config), Some(2));
+        assert_eq!(install.make_jobs(&ProjectConfig::default()), Some(4));
+        assert_eq!(
+            Install::default().make_jobs(&ProjectConfig::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_batch_progress_shows_every_version() {
+        let installs = ["2.7.2", "3.0.0", "3.1.0"]
+            .iter()
+            .map(|version| Install {
+                version: Some(version.parse().unwrap()),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let progress = BatchProgress::new(&installs);
+
+        assert_eq!(
+            progress.summary(&["done", "make", "queued"]),
+            "[1/3] 2.7.2 done, 3.0.0 make, 3.1.0 queued"
+        );
+    }
+
+    #[test]
+    fn test_extra_versions_are_given_with_also() {
+        let matches = crate::cli::build_cli()
+            .get_matches_from_safe(["frum", "install", "2.7.2", "--also", "3.0.0", "--", "2to3"])
+            .unwrap();
+        let install = matches.subcommand_matches("install").unwrap();
+
+        assert_eq!(
+            install.values_of("also").unwrap().collect::<Vec<_>>(),
+            vec!["3.0.0"]
+        );
+        assert_eq!(
+            install
+                .values_of("configure_opts")
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec!["2to3"]
+        );
+        assert!(crate::cli::build_cli()
+            .get_matches_from_safe(["frum", "install", "2.7.2", "--also", "three"])
+            .is_err());
+    }
 }
@@ -1,23 +1,231 @@
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::json::JsonEnvelope;
+use crate::remote_ruby_index::IndexedRubyVersion;
+use crate::version::Channel;
+use serde::Serialize;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum FrumError {
     #[error(transparent)]
     HttpError(#[from] reqwest::Error),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    RemoteIndexError(#[from] crate::remote_ruby_index::Error),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::RemoteIndexError(err) => err.kind(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct InstallList {
+    pub include_prereleases: bool,
+    /// Lists only the versions in this channel, instead of the stable ones and maybe the previews.
+    pub channel: Option<Channel>,
+    pub major: Option<InputVersion>,
+    pub minor: Option<InputVersion>,
+    pub filter: Option<String>,
+    /// Lists only the newest versions. `Some(0)` lists all of them.
+    pub limit: Option<usize>,
+    pub json: bool,
 }
 
-pub struct InstallList {}
+/// How many versions are listed on a terminal without `--limit`.
+const TERMINAL_LIMIT: usize = 20;
+
+/// An installable version as printed by `install --list --json`. The index
+/// doesn't provide release dates or EOL information, so they are left out until it does.
+#[derive(Serialize, Debug)]
+struct ListedVersion {
+    version: String,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    released: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    eol: Option<bool>,
+}
 
 impl crate::command::Command for InstallList {
     type Error = FrumError;
 
-    fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), FrumError> {
-        let versions = crate::remote_ruby_index::list(&config.ruby_build_mirror)?;
+    fn apply(&self, config: &FrumConfig) -> Result<(), FrumError> {
+        // Everything is listed for scripts, but a terminal only gets the newest versions
+        let terminal_limit =
+            (!self.json && atty::is(atty::Stream::Stdout)).then_some(TERMINAL_LIMIT);
+        let limit = self.limit.or(terminal_limit).filter(|limit| *limit > 0);
+        let mut versions = self.listed_versions(config)?;
+        let hidden = newest(&mut versions, limit);
+        if self.json {
+            crate::outln!(config#Info, "{}", to_json(&versions));
+            return Ok(());
+        }
         for version in versions {
             crate::outln!(config#Info, "{}", version.version);
         }
+        if hidden > 0 && self.limit.is_none() {
+            crate::outln!(config#Error, "{} older versions are hidden. Use --limit 0 to list them.", hidden);
+        }
         Ok(())
     }
 }
+
+impl InstallList {
+    /// The versions to list, from the oldest.
+    fn listed_versions(&self, config: &FrumConfig) -> Result<Vec<IndexedRubyVersion>, FrumError> {
+        let mut versions = crate::remote_ruby_index::list(config)?
+            .drain(..)
+            .filter(|indexed| {
+                let version = &indexed.version;
+                let in_channel = match self.channel {
+                    Some(channel) => version.channel() == channel,
+                    None => self.include_prereleases || !version.is_prerelease(),
+                };
+                in_channel
+                    && self
+                        .major
+                        .as_ref()
+                        .is_none_or(|major| major.matches(version))
+                    && self
+                        .minor
+                        .as_ref()
+                        .is_none_or(|minor| minor.matches(version))
+                    && self
+                        .filter
+                        .as_ref()
+                        .is_none_or(|filter| version.to_string().contains(filter.as_str()))
+            })
+            .collect::<Vec<_>>();
+        // The index is in release order, which mixes up patch releases of several series
+        versions.sort_by(|a, b| a.version.cmp(&b.version));
+        Ok(versions)
+    }
+}
+
+/// Keeps the newest `limit` of `versions`, which are sorted from the oldest, and
+/// returns how many were dropped.
+fn newest(versions: &mut Vec<IndexedRubyVersion>, limit: Option<usize>) -> usize {
+    let hidden = limit.map_or(0, |limit| versions.len().saturating_sub(limit));
+    versions.drain(..hidden);
+    hidden
+}
+
+fn to_json(versions: &[IndexedRubyVersion]) -> String {
+    let versions = versions
+        .iter()
+        .map(|indexed| ListedVersion {
+            version: indexed.version.to_string(),
+            url: indexed.url.clone(),
+            released: None,
+            eol: None,
+        })
+        .collect::<Vec<_>>();
+    JsonEnvelope::new(serde_json::json!({ "versions": versions })).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newest, to_json, InstallList};
+    use crate::config::FrumConfig;
+    use crate::input_version::InputVersion;
+    use tempfile::tempdir;
+
+    const INDEX: &str = "name\turl\tsha1\tsha256\tsha512
+ruby-3.0.2\thttps://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.2.tar.gz\ta\tb\tc
+ruby-3.1.0\thttps://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.0.tar.gz\ta\tb\tc
+ruby-3.1.2\thttps://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.2.tar.gz\ta\tb\tc
+ruby-3.10.0\thttps://cache.ruby-lang.org/pub/ruby/3.10/ruby-3.10.0.tar.gz\ta\tb\tc
+";
+
+    #[test]
+    fn test_list_minor_series() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        std::fs::write(config.cache_dir().join("index.txt"), INDEX).unwrap();
+        let listed = |install_list: InstallList| {
+            install_list
+                .listed_versions(&config)
+                .unwrap()
+                .iter()
+                .map(|indexed| indexed.version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            listed(InstallList {
+                minor: Some(InputVersion::MajorMinor(3, 1)),
+                ..Default::default()
+            }),
+            vec!["3.1.0", "3.1.2"]
+        );
+        assert_eq!(
+            listed(InstallList {
+                major: Some(InputVersion::Major(3)),
+                filter: Some("10".to_string()),
+                ..Default::default()
+            }),
+            vec!["3.10.0"]
+        );
+    }
+
+    #[test]
+    fn test_limit_to_newest_versions() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        let index = (0..=9).fold(INDEX.to_string(), |index, patch| {
+            format!("{}ruby-2.7.{}\t-\ta\tb\tc\n", index, patch)
+        });
+        std::fs::write(config.cache_dir().join("index.txt"), index).unwrap();
+        let mut versions = InstallList::default().listed_versions(&config).unwrap();
+
+        assert_eq!(newest(&mut versions, Some(5)), 9);
+        assert_eq!(
+            versions
+                .iter()
+                .map(|indexed| indexed.version.to_string())
+                .collect::<Vec<_>>(),
+            vec!["2.7.9", "3.0.2", "3.1.0", "3.1.2", "3.10.0"]
+        );
+    }
+
+    #[test]
+    fn test_list_as_json() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        std::fs::write(config.cache_dir().join("index.txt"), INDEX).unwrap();
+        let versions = InstallList::default().listed_versions(&config).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&versions)).unwrap();
+
+        assert_eq!(json["schema"], crate::json::SCHEMA);
+        let json = json["versions"].as_array().unwrap();
+        assert_eq!(json.len(), 4);
+        for entry in json {
+            assert!(entry["version"].as_str().is_some_and(|v| !v.is_empty()));
+            assert!(entry.get("released").is_none());
+        }
+        assert_eq!(
+            json[0]["url"],
+            "https://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.2.tar.gz"
+        );
+    }
+}
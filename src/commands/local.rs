@@ -1,7 +1,12 @@
+use crate::command::{version_or_inferred, ErrorKind};
 use crate::input_version::InputVersion;
+use crate::outln;
 use crate::symlink::{create_symlink_dir, remove_symlink_dir};
-use crate::version_file::get_user_version_for_directory;
+use crate::version::{installed_versions, Version};
+use crate::version_file::{find_up, get_user_version_for_file, VERSION_FILE};
 use log::debug;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -14,12 +19,58 @@ pub enum FrumError {
     FrumPathNotFound,
     #[error("Requested version {version} is not currently installed")]
     VersionNotFound { version: InputVersion },
+    #[error("No installed version satisfies {requirement}. Install one with `frum install '{requirement}'`.")]
+    RequirementNotSatisfied { requirement: InputVersion },
     #[error("Can't find version in dotfiles. Please provide a version manually to the command.")]
     CantInferVersion,
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error(transparent)]
+    InstallError(#[from] crate::commands::install::FrumError),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::FrumPathNotFound => "FrumPathNotFound",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::RequirementNotSatisfied { .. } => "RequirementNotSatisfied",
+            Self::CantInferVersion => "CantInferVersion",
+            Self::VersionError(err) => err.kind(),
+            Self::InstallError(err) => err.kind(),
+        }
+    }
 }
 
+#[derive(Default)]
 pub struct Local {
     pub version: Option<InputVersion>,
+    pub install_if_missing: bool,
+    pub yes: bool,
+    pub quiet: bool,
+    pub print: bool,
+}
+
+/// Where the version selected by `local` comes from.
+#[derive(Debug, PartialEq)]
+pub enum VersionSource {
+    Argument,
+    Shell,
+    VersionFile(PathBuf),
+    Global,
+}
+
+impl std::fmt::Display for VersionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Argument => write!(f, "set by the command line"),
+            Self::Shell => write!(f, "set by frum shell"),
+            Self::VersionFile(path) => write!(f, "set by {}", path.display()),
+            Self::Global => write!(f, "set by the global version"),
+        }
+    }
 }
 
 impl crate::command::Command for Local {
@@ -27,50 +78,206 @@ impl crate::command::Command for Local {
 
     fn apply(&self, config: &crate::config::FrumConfig) -> Result<(), Self::Error> {
         debug!("log level {:?}", config.log_level);
-        let current_version = match self.version.clone().ok_or_else(|| {
-            match get_user_version_for_directory(std::env::current_dir().unwrap()) {
-                Some(version) => Ok(version),
-                None => {
-                    replace_symlink(
-                        &config.default_version_dir(),
-                        &config
-                            .frum_path
-                            .clone()
-                            .ok_or(FrumError::FrumPathNotFound)?,
-                    )?;
-
-                    Err(FrumError::CantInferVersion)
-                }
+        if self.print {
+            let (version, source) = resolve_version(
+                config,
+                self.version.as_ref(),
+                &std::env::current_dir().unwrap(),
+            )?;
+            outln!(config#Info, "{} ({})", version, source);
+            return Ok(());
+        }
+        let resolution = if self.quiet && self.version.is_none() {
+            let resolution = resolution_key(&std::env::current_dir().unwrap());
+            if is_resolved(config, &resolution) {
+                debug!("Neither the directory nor {} has changed", VERSION_FILE);
+                return Ok(());
+            }
+            Some(resolution)
+        } else {
+            None
+        };
+        forget_resolution(config);
+
+        let current_version = match version_or_inferred::<Self>(
+            self.version
+                .clone()
+                .or_else(|| config.shell_version.clone()),
+            &std::env::current_dir().unwrap(),
+        ) {
+            Some(version) => version,
+            None => {
+                replace_symlink(
+                    &config.default_version_dir(),
+                    &config
+                        .frum_path
+                        .clone()
+                        .ok_or(FrumError::FrumPathNotFound)?,
+                )?;
+                return Err(FrumError::CantInferVersion);
             }
-        }) {
-            Ok(version) => version,
-            Err(result) => result?,
         };
         debug!("Use {} as the current version", current_version);
-        if !&config
-            .versions_dir()
-            .join(current_version.to_string())
-            .exists()
-        {
-            return Err(FrumError::VersionNotFound {
-                version: current_version,
-            });
+        let mut version_dir = match &current_version {
+            InputVersion::Full(Version::System) => config.system_dir(),
+            InputVersion::Full(version) => config.versions_dir().join(version.to_string()),
+            // Partial versions and requirements such as `~> 3.1` select the best installed match
+            version => match version.to_version(&installed_versions(config)?) {
+                Some(installed_version) => {
+                    config.versions_dir().join(installed_version.to_string())
+                }
+                None => config.versions_dir().join(version.to_string()),
+            },
+        };
+        if !version_dir.exists() {
+            if !(self.install_if_missing || config.install_if_missing)
+                || !(self.yes || confirm_install(&current_version)?)
+            {
+                if let InputVersion::Requirement(..) = current_version {
+                    return Err(FrumError::RequirementNotSatisfied {
+                        requirement: current_version,
+                    });
+                }
+                return Err(FrumError::VersionNotFound {
+                    version: current_version,
+                });
+            }
+            crate::commands::install::Install {
+                version: Some(current_version.clone()),
+                ..Default::default()
+            }
+            .apply(config)?;
+            let installed_version = current_version
+                .to_version(&installed_versions(config)?)
+                .cloned()
+                .ok_or_else(|| FrumError::VersionNotFound {
+                    version: current_version.clone(),
+                })?;
+            version_dir = config.versions_dir().join(installed_version.to_string());
         }
         replace_symlink(
-            &config.versions_dir().join(current_version.to_string()),
+            &version_dir,
             &config
                 .frum_path
                 .clone()
                 .ok_or(FrumError::FrumPathNotFound)?,
         )
         .map_err(FrumError::IoError)?;
+        if let Some(resolution) = resolution {
+            remember_resolution(config, &resolution)?;
+        }
         Ok(())
     }
+
+    fn supports_version_inference() -> bool {
+        true
+    }
+}
+
+/// Finds the version `local` would select in `current_dir`, trying the argument,
+/// the version of a `frum shell`, the nearest `.ruby-version` and then the global version.
+fn resolve_version(
+    config: &crate::config::FrumConfig,
+    version: Option<&InputVersion>,
+    current_dir: &Path,
+) -> Result<(String, VersionSource), FrumError> {
+    let (version, source) = match (version, &config.shell_version) {
+        (Some(version), _) => (version.clone(), VersionSource::Argument),
+        (None, Some(version)) => (version.clone(), VersionSource::Shell),
+        (None, None) => match find_up(current_dir.to_path_buf(), VERSION_FILE) {
+            Some(version_file) => (
+                get_user_version_for_file(version_file.clone())
+                    .ok_or(FrumError::CantInferVersion)?,
+                VersionSource::VersionFile(version_file),
+            ),
+            None => {
+                let global_version = std::fs::canonicalize(config.default_version_dir())
+                    .map_err(|_| FrumError::CantInferVersion)?;
+                let global_version = global_version
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .ok_or(FrumError::CantInferVersion)?;
+                return Ok((global_version.to_string(), VersionSource::Global));
+            }
+        },
+    };
+    let version = match version.to_version(&installed_versions(config)?) {
+        Some(installed_version) => installed_version.to_string(),
+        None if version == InputVersion::Full(Version::System) => version.to_string(),
+        None => format!("{} (not installed)", version),
+    };
+    Ok((version, source))
+}
+
+/// The file next to the session symlink that remembers what `local --quiet` last resolved.
+pub fn resolution_cache_path(frum_path: &Path) -> PathBuf {
+    frum_path.with_extension("local")
+}
+
+/// Describes the directory and the `.ruby-version` it resolves to by their modification times,
+/// so that editing either of them invalidates the cached resolution.
+fn resolution_key(current_dir: &Path) -> String {
+    let modified = |path: &Path| {
+        path.metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|modified| modified.as_nanos())
+    };
+    let version_file = find_up(current_dir.to_path_buf(), VERSION_FILE);
+    format!(
+        "{}\n{:?}\n{:?}\n{:?}\n",
+        current_dir.display(),
+        modified(current_dir),
+        version_file,
+        version_file.as_deref().and_then(modified),
+    )
+}
+
+fn is_resolved(config: &crate::config::FrumConfig, resolution: &str) -> bool {
+    let frum_path = match &config.frum_path {
+        Some(frum_path) => frum_path,
+        None => return false,
+    };
+    frum_path.exists()
+        && std::fs::read_to_string(resolution_cache_path(frum_path))
+            .map(|cached| cached == resolution)
+            .unwrap_or(false)
+}
+
+fn remember_resolution(
+    config: &crate::config::FrumConfig,
+    resolution: &str,
+) -> Result<(), FrumError> {
+    let frum_path = config
+        .frum_path
+        .as_ref()
+        .ok_or(FrumError::FrumPathNotFound)?;
+    std::fs::write(resolution_cache_path(frum_path), resolution)?;
+    Ok(())
+}
+
+fn forget_resolution(config: &crate::config::FrumConfig) {
+    if let Some(frum_path) = &config.frum_path {
+        std::fs::remove_file(resolution_cache_path(frum_path)).ok();
+    }
+}
+
+/// Asks whether the missing version should be installed. Non-interactive sessions install it without asking.
+fn confirm_install(version: &InputVersion) -> std::io::Result<bool> {
+    if !atty::is(atty::Stream::Stdin) {
+        return Ok(true);
+    }
+    eprint!("Ruby {} is not installed. Install it? [y/N] ", version);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
 }
 
 fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Result<()> {
-    let symlink_deletion_result = remove_symlink_dir(&to);
-    match create_symlink_dir(&from, &to) {
+    let symlink_deletion_result = remove_symlink_dir(to);
+    match create_symlink_dir(from, to) {
         ok @ Ok(_) => ok,
         err @ Err(_) => symlink_deletion_result.and(err),
     }
@@ -78,7 +285,10 @@ fn replace_symlink(from: &std::path::Path, to: &std::path::Path) -> std::io::Res
 
 #[cfg(test)]
 mod tests {
-    use super::{FrumError, Local};
+    use super::{
+        is_resolved, remember_resolution, resolution_key, resolve_version, FrumError, Local,
+        VersionSource,
+    };
     use crate::command::Command;
     use crate::config::FrumConfig;
     use crate::input_version::InputVersion;
@@ -88,21 +298,22 @@ mod tests {
 
     #[test]
     fn test_local_specified_version() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            frum_path: Some(std::env::temp_dir().join(format!(
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
                 "frum_{}_{}",
                 std::process::id(),
                 chrono::Utc::now().timestamp_millis(),
-            ))),
-            ..Default::default()
-        };
+            )));
         let dir_path = config.versions_dir().join("2.6.4").join("bin");
         std::fs::create_dir_all(&dir_path).unwrap();
         File::create(dir_path.join("ruby")).unwrap();
 
         crate::commands::global::Global {
-            version: InputVersion::Full(Version::Semver(semver::Version::parse("2.6.4").unwrap())),
+            version: Some(InputVersion::Full(Version::Semver(
+                semver::Version::parse("2.6.4").unwrap(),
+            ))),
+            ..Default::default()
         }
         .apply(&config)
         .unwrap();
@@ -111,6 +322,7 @@ mod tests {
             version: Some(InputVersion::Full(Version::Semver(
                 semver::Version::parse("2.6.4").unwrap(),
             ))),
+            ..Default::default()
         }
         .apply(&config)
         .expect("failed to install");
@@ -118,39 +330,333 @@ mod tests {
         assert!(config.frum_path.unwrap().join("bin").join("ruby").exists());
     }
 
+    #[test]
+    fn test_local_system_version() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
+                "frum_{}_{}_system",
+                std::process::id(),
+                chrono::Utc::now().timestamp_millis(),
+            )));
+        let dir_path = config.versions_dir().join("2.6.4").join("bin");
+        std::fs::create_dir_all(&dir_path).unwrap();
+        File::create(dir_path.join("ruby")).unwrap();
+        let local = |version: &str| {
+            Local {
+                version: Some(version.parse().unwrap()),
+                ..Default::default()
+            }
+            .apply(&config)
+            .unwrap()
+        };
+
+        local("2.6.4");
+        local("system");
+
+        assert_eq!(
+            crate::version::current_version(&config).unwrap(),
+            Some(Version::System)
+        );
+        assert!(!config
+            .frum_path
+            .as_ref()
+            .unwrap()
+            .join("bin")
+            .join("ruby")
+            .exists());
+    }
+
+    #[test]
+    fn test_local_is_independent_per_session() {
+        let base_dir = tempdir().unwrap().path().to_path_buf();
+        let session = |name: &str| {
+            FrumConfig::default()
+                .with_base_dir(base_dir.clone())
+                .with_frum_path(std::env::temp_dir().join(format!(
+                    "frum_{}_{}_{}",
+                    std::process::id(),
+                    chrono::Utc::now().timestamp_millis(),
+                    name,
+                )))
+        };
+        let first_session = session("first");
+        let second_session = session("second");
+        for version in &["2.6.4", "2.7.0"] {
+            let dir_path = first_session.versions_dir().join(version).join("bin");
+            std::fs::create_dir_all(&dir_path).unwrap();
+            File::create(dir_path.join("ruby")).unwrap();
+        }
+
+        Local {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&first_session)
+        .unwrap();
+        Local {
+            version: Some(InputVersion::Full(Version::parse("2.7.0").unwrap())),
+            ..Default::default()
+        }
+        .apply(&second_session)
+        .unwrap();
+
+        let active_version = |config: &FrumConfig| {
+            std::fs::read_link(config.frum_path.as_ref().unwrap())
+                .unwrap()
+                .file_name()
+                .map(|name| name.to_os_string())
+        };
+        assert_eq!(active_version(&first_session), Some("2.6.4".into()));
+        assert_eq!(active_version(&second_session), Some("2.7.0".into()));
+    }
+
     #[test]
     fn test_not_found_version() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            frum_path: Some(std::env::temp_dir().join(format!(
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
                 "frum_{}_{}",
                 std::process::id(),
                 chrono::Utc::now().timestamp_millis(),
-            ))),
-            ..FrumConfig::default()
-        };
+            )));
         let result = Local {
             version: Some(InputVersion::Full(Version::Semver(
                 semver::Version::parse("2.6.4").unwrap(),
             ))),
+            ..Default::default()
         }
         .apply(&config);
         assert!(matches!(result, Err(FrumError::VersionNotFound { .. })));
     }
 
     #[test]
-    fn test_not_found_version_file() {
-        let config = FrumConfig {
-            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
-            frum_path: Some(std::env::temp_dir().join(format!(
+    fn test_quiet_resolution_short_circuits_until_changed() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
                 "frum_{}_{}",
                 std::process::id(),
                 chrono::Utc::now().timestamp_millis(),
-            ))),
+            )));
+        let dir_path = config.versions_dir().join("2.6.4");
+        std::fs::create_dir_all(&dir_path).unwrap();
+        Local {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        let project_dir = tempdir().unwrap();
+        let version_file = project_dir.path().join(".ruby-version");
+        std::fs::write(&version_file, "2.6.4\n").unwrap();
+
+        let resolution = resolution_key(project_dir.path());
+        assert!(!is_resolved(&config, &resolution));
+        remember_resolution(&config, &resolution).unwrap();
+        assert!(is_resolved(&config, &resolution_key(project_dir.path())));
+
+        let modified = std::fs::metadata(&version_file)
+            .unwrap()
+            .modified()
+            .unwrap();
+        File::options()
+            .write(true)
+            .open(&version_file)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(!is_resolved(&config, &resolution_key(project_dir.path())));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_if_missing() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(crate::commands::install::tests::serve_stub_ruby("2.6.4"))
+            .with_frum_path(std::env::temp_dir().join(format!(
+                "frum_{}_{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_millis(),
+            )));
+        Local {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            install_if_missing: true,
+            yes: true,
+            quiet: false,
+            print: false,
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(config.versions_dir().join("2.6.4").exists());
+        assert!(config.frum_path.unwrap().join("bin").join("ruby").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_only_frum_dir() {
+        use std::os::unix::fs::PermissionsExt;
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(crate::commands::install::tests::serve_stub_ruby("2.6.4"))
+            .with_frum_path(std::env::temp_dir().join(format!(
+                "frum_{}_{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_millis(),
+            )));
+        let versions_dir = config.versions_dir();
+        std::fs::set_permissions(&versions_dir, std::fs::Permissions::from_mode(0o555)).unwrap();
+        let version = InputVersion::Full(Version::parse("2.6.4").unwrap());
+
+        let install_result = crate::commands::install::Install {
+            version: Some(version.clone()),
+            ..Default::default()
+        }
+        .apply(&config);
+        let local_result = Local {
+            version: Some(version),
+            install_if_missing: true,
+            yes: true,
+            quiet: false,
+            print: false,
+        }
+        .apply(&config);
+        std::fs::set_permissions(&versions_dir, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(matches!(
+            install_result,
+            Err(crate::commands::install::FrumError::ReadOnlyFrumDir { path }) if path == versions_dir
+        ));
+        assert!(matches!(
+            local_result,
+            Err(FrumError::InstallError(
+                crate::commands::install::FrumError::ReadOnlyFrumDir { .. }
+            ))
+        ));
+        assert!(local_result
+            .unwrap_err()
+            .to_string()
+            .contains("Set FRUM_DIR to a directory you can write to"));
+    }
+
+    #[test]
+    fn test_resolve_version_sources() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.6.4", "2.7.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let project_dir = tempdir().unwrap();
+        let resolve = |version: Option<&str>| {
+            resolve_version(
+                &config,
+                version
+                    .map(|version| version.parse::<InputVersion>().unwrap())
+                    .as_ref(),
+                project_dir.path(),
+            )
+        };
+
+        assert!(matches!(resolve(None), Err(FrumError::CantInferVersion)));
+
+        crate::alias::create_alias(&config, "default", &Version::parse("2.6.4").unwrap()).unwrap();
+        assert_eq!(
+            resolve(None).unwrap(),
+            ("2.6.4".to_string(), VersionSource::Global)
+        );
+
+        let version_file = project_dir.path().join(".ruby-version");
+        std::fs::write(&version_file, "2.7\n").unwrap();
+        assert_eq!(
+            resolve(None).unwrap(),
+            (
+                "2.7.2".to_string(),
+                VersionSource::VersionFile(std::fs::canonicalize(&version_file).unwrap())
+            )
+        );
+
+        assert_eq!(
+            resolve(Some("3.0.0")).unwrap(),
+            ("3.0.0 (not installed)".to_string(), VersionSource::Argument)
+        );
+
+        let shell_config = FrumConfig {
+            base_dir: config.base_dir.clone(),
+            shell_version: Some("2.6.4".parse().unwrap()),
             ..FrumConfig::default()
         };
+        assert_eq!(
+            resolve_version(&shell_config, None, project_dir.path()).unwrap(),
+            ("2.6.4".to_string(), VersionSource::Shell)
+        );
+    }
+
+    #[test]
+    fn test_version_file_with_requirement() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
+                "frum_{}_{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_millis(),
+            )));
+        for version in &["3.0.6", "3.1.4", "3.2.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        let project_dir = tempdir().unwrap();
+        let version_file = project_dir.path().join(".ruby-version");
+        std::fs::write(&version_file, "~> 3.1\n").unwrap();
+
+        assert_eq!(
+            resolve_version(&config, None, project_dir.path()).unwrap(),
+            (
+                "3.2.2".to_string(),
+                VersionSource::VersionFile(std::fs::canonicalize(&version_file).unwrap())
+            )
+        );
+
+        Local {
+            version: Some("~> 3.1.0".parse().unwrap()),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        assert_eq!(
+            std::fs::canonicalize(config.frum_path.as_ref().unwrap()).unwrap(),
+            std::fs::canonicalize(config.versions_dir().join("3.1.4")).unwrap()
+        );
+
+        let result = Local {
+            version: Some("~> 3.3".parse().unwrap()),
+            ..Default::default()
+        }
+        .apply(&config);
+        assert!(matches!(
+            result,
+            Err(FrumError::RequirementNotSatisfied { .. })
+        ));
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("Install one with `frum install '~> 3.3'`"));
+    }
+
+    #[test]
+    fn test_not_found_version_file() {
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_frum_path(std::env::temp_dir().join(format!(
+                "frum_{}_{}",
+                std::process::id(),
+                chrono::Utc::now().timestamp_millis(),
+            )));
         std::env::set_current_dir(std::env::temp_dir()).unwrap();
-        let result = Local { version: None }.apply(&config);
+        let result = Local {
+            version: None,
+            ..Default::default()
+        }
+        .apply(&config);
         assert!(matches!(result, Err(FrumError::CantInferVersion)));
     }
 }
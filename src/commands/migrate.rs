@@ -0,0 +1,272 @@
+use crate::command::ErrorKind;
+use crate::commands::install::{is_ruby_installation, link_prebuilt};
+use crate::config::FrumConfig;
+use crate::outln;
+use crate::version::Version;
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    InstallError(#[from] crate::commands::install::FrumError),
+    #[error("Can't find the Rubies of {manager} in {}", dir.display())]
+    VersionsDirNotFound { manager: &'static str, dir: PathBuf },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::InstallError(err) => err.kind(),
+            Self::VersionsDirNotFound { .. } => "VersionsDirNotFound",
+        }
+    }
+}
+
+/// Another version manager to import the Rubies of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VersionManager {
+    Rbenv,
+    Rvm,
+    Asdf,
+}
+
+impl VersionManager {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rbenv => "rbenv",
+            Self::Rvm => "rvm",
+            Self::Asdf => "asdf",
+        }
+    }
+
+    /// Where the version manager keeps its data, honoring the variable it uses to move it.
+    fn default_root(self) -> Option<PathBuf> {
+        let (variable, dir) = match self {
+            Self::Rbenv => ("RBENV_ROOT", ".rbenv"),
+            Self::Rvm => ("rvm_path", ".rvm"),
+            Self::Asdf => ("ASDF_DATA_DIR", ".asdf"),
+        };
+        std::env::var_os(variable)
+            .filter(|root| !root.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home_dir| home_dir.join(dir)))
+    }
+
+    /// The directory under `root` holding one directory per installed Ruby.
+    fn versions_dir(self, root: &Path) -> PathBuf {
+        match self {
+            Self::Rbenv => root.join("versions"),
+            Self::Rvm => root.join("rubies"),
+            Self::Asdf => root.join("installs").join("ruby"),
+        }
+    }
+}
+
+pub struct Migrate {
+    pub from: VersionManager,
+    /// The data directory of the version manager, such as `~/.rbenv`, if it isn't the default.
+    pub root: Option<PathBuf>,
+    pub dry_run: bool,
+}
+
+impl crate::command::Command for Migrate {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let root = self
+            .root
+            .clone()
+            .or_else(|| self.from.default_root())
+            .unwrap_or_default();
+        let versions_dir = self.from.versions_dir(&root);
+        if !versions_dir.is_dir() {
+            return Err(FrumError::VersionsDirNotFound {
+                manager: self.from.name(),
+                dir: versions_dir,
+            });
+        }
+
+        for (name, dir) in discover(&versions_dir)? {
+            let version = match Version::parse(&name) {
+                Ok(version @ (Version::Semver(_) | Version::Dev(_))) => version,
+                _ => {
+                    outln!(config#Error, "{} Skipping {}: it isn't a CRuby version", "warning:".yellow().bold(), dir.display());
+                    continue;
+                }
+            };
+            if !is_ruby_installation(&dir) {
+                outln!(config#Error, "{} Skipping {}: it doesn't contain bin/ruby", "warning:".yellow().bold(), dir.display());
+                continue;
+            }
+            if self.dry_run {
+                outln!(config#Info, "Ruby {} would be imported from {}", version, dir.display());
+                continue;
+            }
+            link_prebuilt(config, &version, &dir, false)?;
+        }
+        Ok(())
+    }
+}
+
+/// The directories in `versions_dir` of another version manager, with their names, sorted.
+fn discover(versions_dir: &Path) -> std::io::Result<Vec<(String, PathBuf)>> {
+    let mut found = vec![];
+    for entry in versions_dir.read_dir()? {
+        let path = entry?.path();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() && !name.starts_with('.') {
+            found.push((name.to_string(), path));
+        }
+    }
+    found.sort();
+    Ok(found)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use crate::commands::install::InstalledBy;
+    use crate::version::installed_versions;
+    use tempfile::tempdir;
+
+    fn stub_ruby(dir: &Path) {
+        std::fs::create_dir_all(dir.join("bin")).unwrap();
+        std::fs::write(dir.join("bin").join("ruby"), "ruby").unwrap();
+    }
+
+    #[test]
+    fn test_migrate_from_rbenv() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let rbenv = tempdir().unwrap();
+        stub_ruby(&rbenv.path().join("versions").join("2.7.8"));
+        stub_ruby(&rbenv.path().join("versions").join("3.2.2"));
+        stub_ruby(&rbenv.path().join("versions").join("jruby-9.4.3.0"));
+        std::fs::create_dir_all(rbenv.path().join("versions").join("3.3.0")).unwrap();
+        let migrate = Migrate {
+            from: VersionManager::Rbenv,
+            root: Some(rbenv.path().to_path_buf()),
+            dry_run: true,
+        };
+
+        migrate.apply(&config).unwrap();
+        assert!(installed_versions(&config).unwrap().is_empty());
+
+        Migrate {
+            dry_run: false,
+            ..migrate
+        }
+        .apply(&config)
+        .unwrap();
+        assert_eq!(
+            installed_versions(&config).unwrap(),
+            vec![
+                Version::parse("2.7.8").unwrap(),
+                Version::parse("3.2.2").unwrap()
+            ]
+        );
+        assert!(config
+            .versions_dir()
+            .join("3.2.2")
+            .join("bin")
+            .join("ruby")
+            .exists());
+        assert_eq!(
+            InstalledBy::of(&config.versions_dir().join("3.2.2")),
+            InstalledBy::Imported
+        );
+    }
+
+    #[test]
+    fn test_migrated_scripts_keep_running() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_frum_path(base_dir.path().join("frum_path"));
+        let rbenv = tempdir().unwrap();
+        let source_dir = rbenv.path().join("versions").join("3.2.2");
+        stub_ruby(&source_dir);
+        // Like rubygems does, the scripts point at the ruby of the prefix Ruby was built in
+        let shebang = format!("#!{}\n", source_dir.join("bin").join("ruby").display());
+        std::fs::write(source_dir.join("bin").join("gem"), &shebang).unwrap();
+
+        Migrate {
+            from: VersionManager::Rbenv,
+            root: Some(rbenv.path().to_path_buf()),
+            dry_run: false,
+        }
+        .apply(&config)
+        .unwrap();
+
+        let version_dir = config.versions_dir().join("3.2.2");
+        let interpreter = std::fs::read_to_string(version_dir.join("bin").join("gem")).unwrap();
+        assert_eq!(interpreter, shebang);
+        assert_eq!(
+            std::path::Path::new(interpreter.trim_start_matches("#!").trim_end())
+                .canonicalize()
+                .unwrap(),
+            version_dir.join("bin").join("ruby").canonicalize().unwrap()
+        );
+        assert_eq!(InstalledBy::of(&version_dir), InstalledBy::Imported);
+        assert_eq!(
+            crate::commands::install::prebuilt_origin(&version_dir),
+            Some(source_dir.canonicalize().unwrap())
+        );
+
+        crate::symlink::create_symlink_dir(&version_dir, config.frum_path.as_ref().unwrap())
+            .unwrap();
+        crate::commands::uninstall::Uninstall {
+            version: crate::input_version::InputVersion::Full(Version::parse("3.2.2").unwrap()),
+            prune_cache: false,
+        }
+        .apply(&config)
+        .unwrap();
+        assert!(!version_dir.exists());
+        assert!(source_dir.join("bin").join("ruby").exists());
+    }
+
+    #[test]
+    fn test_migrate_from_asdf_and_rvm() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let asdf = tempdir().unwrap();
+        stub_ruby(&asdf.path().join("installs").join("ruby").join("3.1.4"));
+        let rvm = tempdir().unwrap();
+        stub_ruby(&rvm.path().join("rubies").join("ruby-3.0.6"));
+
+        for (from, root) in &[
+            (VersionManager::Asdf, asdf.path()),
+            (VersionManager::Rvm, rvm.path()),
+        ] {
+            Migrate {
+                from: *from,
+                root: Some(root.to_path_buf()),
+                dry_run: false,
+            }
+            .apply(&config)
+            .unwrap();
+        }
+
+        assert_eq!(
+            installed_versions(&config).unwrap(),
+            vec![
+                Version::parse("3.0.6").unwrap(),
+                Version::parse("3.1.4").unwrap()
+            ]
+        );
+        assert!(matches!(
+            Migrate {
+                from: VersionManager::Rbenv,
+                root: Some(asdf.path().to_path_buf()),
+                dry_run: false,
+            }
+            .apply(&config),
+            Err(FrumError::VersionsDirNotFound { .. })
+        ));
+    }
+}
@@ -1,8 +1,20 @@
+pub mod alias;
+pub mod bundle;
+pub mod cache;
 pub mod completions;
+pub mod export;
 pub mod global;
+pub mod import;
 pub mod init;
 pub mod install;
 pub mod install_list;
 pub mod local;
+pub mod migrate;
+pub mod prune;
+pub mod reinstall;
+pub mod repair;
+pub mod shell;
 pub mod uninstall;
+pub mod upgrade;
 pub mod versions;
+pub mod which;
@@ -0,0 +1,132 @@
+use crate::command::ErrorKind;
+use crate::commands::init::remove_stale_symlinks;
+use crate::config::FrumConfig;
+use crate::outln;
+use crate::symlink::remove_symlink_dir;
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+        }
+    }
+}
+
+pub struct Prune {
+    /// Removes the symlinks whose destination is gone. It is the only cleanup so far.
+    pub dangling: bool,
+}
+
+impl crate::command::Command for Prune {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if !self.dangling {
+            return Ok(());
+        }
+        let removed = remove_dangling_symlinks(&config.aliases_dir())?;
+        // A session may point at a default version that isn't installed yet, so sessions
+        // go when their shell has exited instead of when their version is gone
+        let removed_sessions = remove_stale_symlinks(config)?;
+        if removed.is_empty() && removed_sessions.is_empty() {
+            outln!(config#Info, "No dangling symlinks found");
+        }
+        for path in removed {
+            outln!(config#Info, "{} Removed the dangling symlink {}", "==>".green(), path.display());
+        }
+        for path in removed_sessions {
+            outln!(config#Info, "{} Removed the symlink of the exited session {}", "==>".green(), path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Removes the aliases in `aliases_dir`, including the default one, whose version no
+/// longer exists. Directories and files are left alone, so no version is ever removed.
+fn remove_dangling_symlinks(aliases_dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = aliases_dir
+        .read_dir()?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+    let mut removed = vec![];
+    for path in paths.into_iter().filter(|path| is_dangling(path)) {
+        remove_symlink_dir(&path)?;
+        removed.push(path);
+    }
+    Ok(removed)
+}
+
+fn is_dangling(path: &Path) -> bool {
+    let is_symlink = path
+        .symlink_metadata()
+        .map(|metadata| metadata.file_type().is_symlink())
+        .unwrap_or(false);
+    is_symlink && !path.exists()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{remove_dangling_symlinks, Prune};
+    use crate::command::Command;
+    use crate::config::FrumConfig;
+    use crate::log::LogLevel;
+    use crate::symlink::create_symlink_dir;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_remove_only_dangling_symlinks() {
+        let versions_dir = tempdir().unwrap();
+        let aliases_dir = tempdir().unwrap();
+        std::fs::create_dir_all(versions_dir.path().join("2.7.2")).unwrap();
+        let link = |dir: &tempfile::TempDir, name: &str, version: &str| {
+            create_symlink_dir(versions_dir.path().join(version), dir.path().join(name)).unwrap()
+        };
+        link(&aliases_dir, "default", "2.6.0");
+        link(&aliases_dir, "lts", "2.7.2");
+        link(&aliases_dir, "old", "2.6.0");
+        std::fs::create_dir(aliases_dir.path().join("not_a_link")).unwrap();
+
+        let removed = remove_dangling_symlinks(aliases_dir.path()).unwrap();
+
+        assert_eq!(
+            removed,
+            vec![
+                aliases_dir.path().join("default"),
+                aliases_dir.path().join("old"),
+            ]
+        );
+        assert!(aliases_dir.path().join("lts").exists());
+        assert!(aliases_dir.path().join("not_a_link").exists());
+        assert!(versions_dir.path().join("2.7.2").exists());
+    }
+
+    #[test]
+    fn test_prune_keeps_live_sessions_without_a_default_version() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_multishells_dir(base_dir.path().join("multishells"))
+            .with_log_level(LogLevel::Quiet);
+        let multishells_dir = config.multishells_dir().unwrap();
+        let live_session = multishells_dir.join(format!("{}_1", std::process::id()));
+        let exited_session = multishells_dir.join(format!("{}_1", i32::MAX));
+        create_symlink_dir(config.default_version_dir(), &live_session).unwrap();
+        create_symlink_dir(config.default_version_dir(), &exited_session).unwrap();
+
+        Prune { dangling: true }.apply(&config).unwrap();
+
+        assert!(live_session.symlink_metadata().is_ok());
+        assert!(exited_session.symlink_metadata().is_err());
+    }
+}
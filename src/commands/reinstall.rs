@@ -0,0 +1,267 @@
+use crate::command::{run_all, BatchResults, Command, ErrorKind};
+use crate::commands::install::{
+    link_prebuilt, prebuilt_origin, recorded_configure_opts, register_prebuilt,
+    FrumError as InstallError, Install, InstalledBy,
+};
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{installed_versions, Version};
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error("Can't reinstall every version: {summary}")]
+    ReinstallsFailed { summary: String },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::ReinstallsFailed { .. } => "ReinstallsFailed",
+        }
+    }
+}
+
+pub struct Reinstall {
+    /// Rebuilds every installed version. It is the only way to pick versions so far.
+    pub all: bool,
+    /// Leaves the versions registered with `install --no-build` or imported by `frum migrate` alone.
+    pub only_source: bool,
+}
+
+/// Copies a version registered from a prebuilt directory again, or links an imported one
+/// again, keeping how it was obtained.
+struct Reregister {
+    version: Version,
+    prebuilt: PathBuf,
+    installed_by: InstalledBy,
+}
+
+impl Command for Reregister {
+    type Error = InstallError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if self.installed_by == InstalledBy::Imported {
+            return link_prebuilt(config, &self.version, &self.prebuilt, true);
+        }
+        register_prebuilt(
+            config,
+            &self.version,
+            &self.prebuilt,
+            &config.temp_dir(),
+            true,
+            self.installed_by,
+        )
+    }
+}
+
+impl Command for Reinstall {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if !self.all {
+            return Ok(());
+        }
+        let (versions, results) = self.reinstall_all(config)?;
+        for (version, result) in versions.iter().zip(&results.results) {
+            if let Err(err) = result {
+                outln!(config#Error, "{} Ruby {}: {}", "error:".red().bold(), version, err);
+            }
+        }
+        if !results.is_ok() {
+            return Err(FrumError::ReinstallsFailed {
+                summary: results.summary(),
+            });
+        }
+        outln!(config#Info, "{} Reinstalled: {}", "==>".green(), results.summary());
+        Ok(())
+    }
+}
+
+impl Reinstall {
+    /// Rebuilds the installed versions the way they were installed, going on after the
+    /// ones that fail, and returns them with their results.
+    fn reinstall_all(
+        &self,
+        config: &FrumConfig,
+    ) -> Result<(Vec<Version>, BatchResults<InstallError>), FrumError> {
+        let mut versions = vec![];
+        let mut reinstalls: Vec<Box<dyn Command<Error = InstallError>>> = vec![];
+        for version in installed_versions(config)? {
+            let version_dir = config.versions_dir().join(version.to_string());
+            let reinstall: Box<dyn Command<Error = InstallError>> = match (
+                InstalledBy::of(&version_dir),
+                &version,
+            ) {
+                (InstalledBy::Source, Version::Semver(_)) => Box::new(Install {
+                    version: Some(InputVersion::Full(version.clone())),
+                    configure_opts: recorded_configure_opts(&version_dir),
+                    force: true,
+                    ..Default::default()
+                }),
+                (InstalledBy::Source, _) => {
+                    outln!(config#Error, "{} Skipping Ruby {}: builds from `install --source` can't be rebuilt", "warning:".yellow().bold(), version);
+                    continue;
+                }
+                (_, _) if self.only_source => continue,
+                (installed_by, _) => match prebuilt_origin(&version_dir) {
+                    Some(prebuilt) if prebuilt.is_dir() => Box::new(Reregister {
+                        version: version.clone(),
+                        prebuilt,
+                        installed_by,
+                    }),
+                    _ => {
+                        outln!(config#Error, "{} Skipping Ruby {}: the directory it was registered from is gone", "warning:".yellow().bold(), version);
+                        continue;
+                    }
+                },
+            };
+            outln!(config#Info, "{} Reinstalling {}", "==>".green(), format!("Ruby {}", version).green());
+            versions.push(version);
+            reinstalls.push(reinstall);
+        }
+        Ok((versions, run_all(&reinstalls, config)))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::commands::install::tests::{stub_ruby_archive, stub_ruby_archive_with};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_reinstall_all_versions() {
+        let mirror_dir = tempdir().unwrap();
+        for (series, version) in &[("2.6", "2.6.4"), ("2.7", "2.7.2")] {
+            std::fs::create_dir_all(mirror_dir.path().join(series)).unwrap();
+            std::fs::write(
+                mirror_dir
+                    .path()
+                    .join(series)
+                    .join(format!("ruby-{}.tar.xz", version)),
+                stub_ruby_archive(version),
+            )
+            .unwrap();
+        }
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(reqwest::Url::from_directory_path(mirror_dir.path()).unwrap());
+        for version in &["2.6.4", "2.7.2"] {
+            Install {
+                version: Some(InputVersion::Full(Version::parse(version).unwrap())),
+                configure_opts: vec!["--disable-install-doc".to_string()],
+                ..Default::default()
+            }
+            .apply(&config)
+            .unwrap();
+        }
+        let prebuilt = tempdir().unwrap();
+        std::fs::create_dir_all(prebuilt.path().join("bin")).unwrap();
+        std::fs::write(prebuilt.path().join("bin").join("ruby"), "").unwrap();
+        Install {
+            version: Some(InputVersion::Full(Version::parse("3.0.0").unwrap())),
+            prebuilt: Some(prebuilt.path().to_path_buf()),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        // Files left in the versions show which ones were installed again
+        for version in &["2.6.4", "2.7.2", "3.0.0"] {
+            std::fs::write(config.versions_dir().join(version).join("stale"), "").unwrap();
+        }
+
+        let (versions, results) = Reinstall {
+            all: true,
+            only_source: true,
+        }
+        .reinstall_all(&config)
+        .unwrap();
+
+        assert_eq!(
+            versions,
+            vec![
+                Version::parse("2.6.4").unwrap(),
+                Version::parse("2.7.2").unwrap()
+            ]
+        );
+        assert_eq!(results.summary(), "2 succeeded, 0 failed");
+        for version in &["2.6.4", "2.7.2"] {
+            let version_dir = config.versions_dir().join(version);
+            assert!(!version_dir.join("stale").exists());
+            assert!(version_dir.join("bin").join("ruby").exists());
+            assert_eq!(
+                recorded_configure_opts(&version_dir),
+                vec!["--disable-install-doc"]
+            );
+        }
+        assert!(config.versions_dir().join("3.0.0").join("stale").exists());
+    }
+
+    #[test]
+    fn test_failed_rebuild_keeps_the_version() {
+        let state_dir = tempdir().unwrap();
+        let make_works = state_dir.path().join("make-works");
+        std::fs::write(&make_works, "").unwrap();
+        let mirror_dir = tempdir().unwrap();
+        std::fs::create_dir_all(mirror_dir.path().join("2.7")).unwrap();
+        std::fs::write(
+            mirror_dir.path().join("2.7").join("ruby-2.7.2.tar.xz"),
+            stub_ruby_archive_with(
+                "2.7.2",
+                &format!(
+                    indoc::indoc!(
+                        r#"
+                        prefix="${{1#--prefix=}}"
+                        printf 'all:\n\ttest -f {}\ninstall:\n\tmkdir -p $(DESTDIR)%s/bin\n\ttouch $(DESTDIR)%s/bin/ruby\n' "$prefix" "$prefix" > Makefile
+                        "#
+                    ),
+                    make_works.display()
+                ),
+            ),
+        )
+        .unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(reqwest::Url::from_directory_path(mirror_dir.path()).unwrap());
+        Install {
+            version: Some(InputVersion::Full(Version::parse("2.7.2").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+        let version_dir = config.versions_dir().join("2.7.2");
+        std::fs::write(version_dir.join("installed-gem"), "").unwrap();
+        std::fs::remove_file(&make_works).unwrap();
+
+        let (_, results) = Reinstall {
+            all: true,
+            only_source: false,
+        }
+        .reinstall_all(&config)
+        .unwrap();
+
+        assert_eq!(results.summary(), "0 succeeded, 1 failed");
+        assert!(version_dir.join("bin").join("ruby").exists());
+        assert!(version_dir.join("installed-gem").exists());
+        assert_eq!(
+            installed_versions(&config).unwrap(),
+            vec![Version::parse("2.7.2").unwrap()]
+        );
+        assert!(!config.versions_dir().read_dir().unwrap().any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(".tmp")));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::alias::create_alias;
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::outln;
+use crate::symlink::remove_symlink_dir;
+use crate::version::installed_versions;
+use colored::Colorize;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+        }
+    }
+}
+
+pub struct Repair {}
+
+/// What was done about an alias whose destination no longer exists.
+#[derive(Debug, PartialEq)]
+pub enum Repaired {
+    Removed { name: String, destination: PathBuf },
+    Repointed { name: String, version: String },
+}
+
+impl std::fmt::Display for Repaired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Removed { name, destination } => write!(
+                f,
+                "Removed the alias {} pointing at missing {}",
+                name,
+                destination.display()
+            ),
+            Self::Repointed { name, version } => {
+                write!(f, "Pointed the alias {} at {}", name, version)
+            }
+        }
+    }
+}
+
+impl crate::command::Command for Repair {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let repaired = repair_symlinks(config)?;
+        if repaired.is_empty() {
+            outln!(config#Info, "No dangling symlinks found");
+        }
+        for repaired in repaired {
+            outln!(config#Info, "{} {}", "==>".green(), repaired);
+        }
+        Ok(())
+    }
+}
+
+/// Removes aliases whose destination is gone. The default alias is pointed at
+/// the newest installed version instead, so that the global version keeps working.
+pub fn repair_symlinks(config: &FrumConfig) -> Result<Vec<Repaired>, FrumError> {
+    let mut paths = config
+        .aliases_dir()
+        .read_dir()?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    paths.sort();
+
+    let mut repaired = vec![];
+    for path in paths {
+        if path.exists() || path.symlink_metadata().is_err() {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let destination = std::fs::read_link(&path)?;
+        remove_symlink_dir(&path)?;
+
+        let newest_version = installed_versions(config)?.pop();
+        match newest_version {
+            Some(version) if path == config.default_version_dir() => {
+                create_alias(config, &name, &version)?;
+                repaired.push(Repaired::Repointed {
+                    name,
+                    version: version.to_string(),
+                });
+            }
+            _ => repaired.push(Repaired::Removed { name, destination }),
+        }
+    }
+    Ok(repaired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{repair_symlinks, Repaired};
+    use crate::alias::create_alias;
+    use crate::config::FrumConfig;
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_repair_dangling_aliases() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.6.0", "2.7.2", "3.0.0"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        create_alias(&config, "default", &Version::parse("2.6.0").unwrap()).unwrap();
+        create_alias(&config, "old", &Version::parse("2.6.0").unwrap()).unwrap();
+        create_alias(&config, "lts", &Version::parse("2.7.2").unwrap()).unwrap();
+        std::fs::remove_dir(config.versions_dir().join("2.6.0")).unwrap();
+
+        let repaired = repair_symlinks(&config).unwrap();
+
+        assert_eq!(
+            repaired,
+            vec![
+                Repaired::Repointed {
+                    name: "default".to_string(),
+                    version: "3.0.0".to_string(),
+                },
+                Repaired::Removed {
+                    name: "old".to_string(),
+                    destination: config.versions_dir().join("2.6.0"),
+                },
+            ]
+        );
+        assert!(config.aliases_dir().join("old").symlink_metadata().is_err());
+        assert!(config.aliases_dir().join("lts").exists());
+        assert_eq!(
+            std::fs::read_link(config.default_version_dir()).unwrap(),
+            config.versions_dir().join("3.0.0")
+        );
+    }
+}
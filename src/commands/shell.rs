@@ -0,0 +1,201 @@
+use crate::command::ErrorKind;
+use crate::commands::init::create_symlink;
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::shell::infer_shell;
+use crate::symlink::remove_symlink_dir;
+use crate::version::{installed_versions, Version};
+use log::debug;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error("Requested version {version} is not currently installed")]
+    VersionNotFound { version: InputVersion },
+    #[error("Can't infer shell!")]
+    CantInferShell,
+    #[error("Can't add the version to PATH: {source}")]
+    CantJoinPaths { source: std::env::JoinPathsError },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::CantInferShell => "CantInferShell",
+            Self::CantJoinPaths { .. } => "CantJoinPaths",
+        }
+    }
+}
+
+pub struct Shell {
+    pub version: InputVersion,
+    /// Keeps the inherited `GEM_HOME`, `GEM_PATH` and `RUBYOPT` instead of scoping them to the version.
+    pub preserve_env: bool,
+}
+
+impl crate::command::Command for Shell {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let version = self
+            .version
+            .to_version(&installed_versions(config)?)
+            .cloned()
+            .ok_or_else(|| FrumError::VersionNotFound {
+                version: self.version.clone(),
+            })?;
+        let program = shell_program().ok_or(FrumError::CantInferShell)?;
+        let version_dir = config.versions_dir().join(version.to_string());
+
+        let session = create_symlink(config, &version_dir)?;
+        debug!("Start {:?} with Ruby {}", program, version);
+        let status = subshell(&program, &session, &version.to_string())
+            .map(|mut command| {
+                if !self.preserve_env {
+                    scope_gem_env(&mut command, &version_dir, &version);
+                }
+                command
+            })
+            .and_then(|mut command| command.status().map_err(FrumError::IoError));
+        remove_symlink_dir(&session).ok();
+
+        // Pass on how the subshell exited, like the shell itself would
+        match status?.code() {
+            Some(0) => Ok(()),
+            code => std::process::exit(code.unwrap_or(1)),
+        }
+    }
+}
+
+/// The program of the user's shell, from `$SHELL` or the process tree.
+fn shell_program() -> Option<OsString> {
+    std::env::var_os("SHELL")
+        .filter(|shell| !shell.is_empty())
+        .or_else(|| {
+            let program = match infer_shell()?.as_clap_shell() {
+                clap::Shell::Bash => "bash",
+                clap::Shell::Zsh => "zsh",
+                clap::Shell::Fish => "fish",
+                clap::Shell::PowerShell => "pwsh",
+                clap::Shell::Elvish => "elvish",
+            };
+            Some(program.into())
+        })
+}
+
+/// Prepares `program` to run with the session symlink `session` first on `PATH`.
+/// `FRUM_SHELL_VERSION` makes `frum init` and `frum local` in the subshell keep `version`.
+fn subshell(
+    program: &OsString,
+    session: &Path,
+    version: &str,
+) -> Result<std::process::Command, FrumError> {
+    let binary_path = if cfg!(windows) {
+        session.to_path_buf()
+    } else {
+        session.join("bin")
+    };
+    let path = std::env::join_paths(std::iter::once(binary_path).chain(std::env::split_paths(
+        &std::env::var_os("PATH").unwrap_or_default(),
+    )))
+    .map_err(|source| FrumError::CantJoinPaths { source })?;
+    let mut command = std::process::Command::new(program);
+    command
+        .env("PATH", path)
+        .env("FRUM_MULTISHELL_PATH", session)
+        .env("FRUM_SHELL_VERSION", version);
+    Ok(command)
+}
+
+/// Points `GEM_HOME` and `GEM_PATH` at the gems of `version` in `version_dir`, and
+/// drops `RUBYOPT`, so that gems of another version or the system Ruby don't leak in.
+/// Both are left to Ruby's own default for versions whose gem directory isn't known.
+pub fn scope_gem_env(command: &mut std::process::Command, version_dir: &Path, version: &Version) {
+    match gem_home(version_dir, version) {
+        Some(gem_home) => {
+            command
+                .env("GEM_HOME", &gem_home)
+                .env("GEM_PATH", &gem_home);
+        }
+        None => {
+            command.env_remove("GEM_HOME").env_remove("GEM_PATH");
+        }
+    }
+    command.env_remove("RUBYOPT");
+}
+
+/// The gem directory of `version` in `version_dir`, if its ABI version is known.
+pub fn gem_home(version_dir: &Path, version: &Version) -> Option<PathBuf> {
+    let (major, minor) = version.major_minor()?;
+    Some(
+        version_dir
+            .join("lib")
+            .join("ruby")
+            .join("gems")
+            .join(format!("{}.{}.0", major, minor)),
+    )
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::{scope_gem_env, subshell};
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_subshell_puts_version_first_on_path() {
+        let session = tempdir().unwrap().path().join("session");
+
+        let output = subshell(&"sh".into(), &session, "2.7.2")
+            .unwrap()
+            .arg("-c")
+            .arg("echo \"$PATH\"; echo \"$FRUM_SHELL_VERSION\"")
+            .output()
+            .unwrap();
+
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let mut lines = stdout.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with(&format!("{}:", session.join("bin").display())));
+        assert_eq!(lines.next(), Some("2.7.2"));
+    }
+
+    #[test]
+    fn test_gem_env_is_scoped_to_version() {
+        let version_dir = tempdir().unwrap().path().join("3.2.2");
+        let mut command = subshell(&"sh".into(), &version_dir, "3.2.2").unwrap();
+        // What a shell with another version's gems active would pass on
+        command
+            .env("GEM_HOME", "/gems/2.7.0")
+            .env("GEM_PATH", "/gems/2.7.0:/var/lib/gems")
+            .env("RUBYOPT", "-rbundler/setup");
+
+        scope_gem_env(
+            &mut command,
+            &version_dir,
+            &Version::parse("3.2.2").unwrap(),
+        );
+        let output = command
+            .arg("-c")
+            .arg("echo \"$GEM_HOME\"; echo \"$GEM_PATH\"; echo \"${RUBYOPT-unset}\"")
+            .output()
+            .unwrap();
+
+        let gem_home = version_dir.join("lib/ruby/gems/3.2.0");
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            format!("{}\n{}\nunset\n", gem_home.display(), gem_home.display())
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use crate::command::ErrorKind;
+use crate::commands::cache::format_size;
+use crate::commands::install::archive;
 use crate::config::FrumConfig;
 use crate::input_version::InputVersion;
 use crate::outln;
@@ -26,10 +29,27 @@ pub enum FrumError {
     NotInstallableVersion { version: Version },
     #[error("We can't find the necessary environment variables to replace the Ruby version.")]
     FrumPathNotFound,
+    #[error("{} is read-only. Set FRUM_DIR to a directory you can write to, or run frum as the owner of this one, e.g. with sudo for a shared installation.", path.display())]
+    ReadOnlyFrumDir { path: PathBuf },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::FromUtf8Error(_) => "FromUtf8Error",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::NotInstallableVersion { .. } => "NotInstallableVersion",
+            Self::FrumPathNotFound => "FrumPathNotFound",
+            Self::ReadOnlyFrumDir { .. } => "ReadOnlyFrumDir",
+        }
+    }
 }
 
 pub struct Uninstall {
     pub version: InputVersion,
+    pub prune_cache: bool,
 }
 
 impl crate::command::Command for Uninstall {
@@ -38,12 +58,12 @@ impl crate::command::Command for Uninstall {
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
         let current_version = self.version.clone();
         let version = match current_version.clone() {
-            InputVersion::Full(Version::Semver(v)) => Version::Semver(v),
             InputVersion::Full(Version::System) => {
                 return Err(FrumError::NotInstallableVersion {
                     version: Version::System,
                 })
             }
+            InputVersion::Full(version) => version,
             _ => unreachable!(),
         };
         let installation_dir = PathBuf::from(&config.versions_dir()).join(version.to_string());
@@ -52,6 +72,11 @@ impl crate::command::Command for Uninstall {
                 version: current_version,
             });
         }
+        if !crate::config::is_writable(&config.versions_dir()) {
+            return Err(FrumError::ReadOnlyFrumDir {
+                path: config.versions_dir(),
+            });
+        }
         outln!(config#Info, "{} Uninstalling {}", "==>".green(), format!("Ruby {}", current_version).green());
         if symlink_exists(
             config
@@ -70,15 +95,60 @@ impl crate::command::Command for Uninstall {
         }
         if symlink_exists(config.default_version_dir(), &version)? {
             debug!("remove default alias symlink");
-            remove_symlink_dir(&config.default_version_dir())?;
+            remove_symlink_dir(config.default_version_dir())?;
         }
         debug!("remove dir");
         std::fs::remove_dir_all(&installation_dir)?;
+        if self.prune_cache {
+            let cached_archive = config.cache_dir().join(archive(&version));
+            if let Ok(metadata) = cached_archive.metadata() {
+                std::fs::remove_file(&cached_archive)?;
+                outln!(config#Info, "{} Removed {} from the cache, freeing {}", "==>".green(), archive(&version), format_size(metadata.len()));
+            }
+        }
         Ok(())
     }
 }
 
 fn symlink_exists(to: PathBuf, version: &Version) -> Result<bool, FrumError> {
-    Ok(std::fs::read_link(to)?.components().last()
+    Ok(std::fs::read_link(to)?.components().next_back()
         == Some(Component::Normal(OsStr::new(&version.to_string()))))
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::command::Command;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_uninstall_prunes_cached_archive() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_frum_path(base_dir.path().join("frum_path"));
+        for version in &["2.6.4", "2.7.2"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+            std::fs::write(
+                config.cache_dir().join(format!("ruby-{}.tar.xz", version)),
+                "archive",
+            )
+            .unwrap();
+        }
+        let version_dir = config.versions_dir().join("2.7.2");
+        crate::symlink::create_symlink_dir(&version_dir, config.default_version_dir()).unwrap();
+        crate::symlink::create_symlink_dir(&version_dir, config.frum_path.as_ref().unwrap())
+            .unwrap();
+
+        Uninstall {
+            version: InputVersion::Full(Version::parse("2.6.4").unwrap()),
+            prune_cache: true,
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert!(!config.versions_dir().join("2.6.4").exists());
+        assert!(!config.cache_dir().join("ruby-2.6.4.tar.xz").exists());
+        assert!(config.cache_dir().join("ruby-2.7.2.tar.xz").exists());
+    }
+}
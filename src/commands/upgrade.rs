@@ -0,0 +1,196 @@
+use crate::alias::{create_alias, find_aliases};
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::input_version::InputVersion;
+use crate::outln;
+use crate::version::{installed_versions, Version};
+use crate::version_file::{find_up, VERSION_FILE};
+use colored::Colorize;
+use log::debug;
+use std::io::prelude::*;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error("Can't list the remote versions: {source}")]
+    CantListRemoteVersions {
+        source: crate::remote_ruby_index::Error,
+    },
+    #[error("Can't find an installed version matching {version}")]
+    VersionNotFound { version: InputVersion },
+    #[error(transparent)]
+    InstallError(#[from] crate::commands::install::FrumError),
+    #[error(transparent)]
+    UninstallError(#[from] crate::commands::uninstall::FrumError),
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::CantListRemoteVersions { .. } => "CantListRemoteVersions",
+            Self::VersionNotFound { .. } => "VersionNotFound",
+            Self::InstallError(err) => err.kind(),
+            Self::UninstallError(err) => err.kind(),
+        }
+    }
+}
+
+pub struct Upgrade {
+    pub version: InputVersion,
+    pub repin: bool,
+    pub remove_old: bool,
+}
+
+impl crate::command::Command for Upgrade {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        let installed_versions = installed_versions(config)?;
+        let old_version = self
+            .version
+            .to_version(&installed_versions)
+            .ok_or_else(|| FrumError::VersionNotFound {
+                version: self.version.clone(),
+            })?
+            .clone();
+        let remote_versions = crate::remote_ruby_index::list_versions(config, false)
+            .map_err(|source| FrumError::CantListRemoteVersions { source })?;
+        let new_version = match newest_in_series(&old_version, &remote_versions) {
+            Some(new_version) if new_version > &old_version => new_version.clone(),
+            _ => {
+                outln!(config#Info, "Ruby {} is already the newest version", old_version);
+                return Ok(());
+            }
+        };
+
+        outln!(config#Info, "{} Upgrading {}", "==>".green(), format!("Ruby {} to {}", old_version, new_version).green());
+        crate::commands::install::Install {
+            version: Some(InputVersion::Full(new_version.clone())),
+            ..Default::default()
+        }
+        .apply(config)?;
+
+        if self.repin {
+            repin(
+                config,
+                &old_version,
+                &new_version,
+                &std::env::current_dir().unwrap(),
+            )?;
+        }
+
+        if self.remove_old {
+            crate::commands::uninstall::Uninstall {
+                version: InputVersion::Full(old_version),
+                prune_cache: false,
+            }
+            .apply(config)?;
+        }
+        Ok(())
+    }
+}
+
+/// The newest remote version in the series of `old_version`, such as 3.1.4 for 3.1.2,
+/// so that upgrading never moves to another minor version.
+fn newest_in_series<'a>(
+    old_version: &Version,
+    remote_versions: &'a [Version],
+) -> Option<&'a Version> {
+    let series = old_version.major_minor()?;
+    remote_versions
+        .iter()
+        .filter(|version| version.major_minor() == Some(series))
+        .max()
+}
+
+/// Points the aliases and the `.ruby-version` file that select `old_version` at `new_version`.
+fn repin(
+    config: &FrumConfig,
+    old_version: &Version,
+    new_version: &Version,
+    current_dir: &Path,
+) -> Result<(), FrumError> {
+    for alias in find_aliases(config)? {
+        if alias.s_ver() == old_version.to_string() {
+            debug!("Repin the alias {} to {}", alias.name(), new_version);
+            create_alias(config, alias.name(), new_version)?;
+        }
+    }
+
+    if let Some(version_file) = find_up(current_dir.to_path_buf(), VERSION_FILE) {
+        let pinned_version = std::fs::read_to_string(&version_file)?;
+        if pinned_version.trim() == old_version.to_string() {
+            debug!("Repin {} to {}", version_file.display(), new_version);
+            std::fs::write(&version_file, format!("{}\n", new_version))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newest_in_series, repin};
+    use crate::alias::{create_alias, find_aliases};
+    use crate::config::FrumConfig;
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_repin_moves_aliases_and_version_file() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let old_version = Version::parse("3.0.0").unwrap();
+        let new_version = Version::parse("3.0.1").unwrap();
+        let other_version = Version::parse("2.7.2").unwrap();
+        for version in &[&old_version, &new_version, &other_version] {
+            std::fs::create_dir_all(config.versions_dir().join(version.to_string())).unwrap();
+        }
+        create_alias(&config, "default", &old_version).unwrap();
+        create_alias(&config, "legacy", &other_version).unwrap();
+        let project_dir = tempdir().unwrap();
+        std::fs::write(project_dir.path().join(".ruby-version"), "3.0.0\n").unwrap();
+
+        repin(&config, &old_version, &new_version, project_dir.path()).unwrap();
+
+        let mut aliases = find_aliases(&config)
+            .unwrap()
+            .iter()
+            .map(|alias| (alias.name().to_string(), alias.s_ver().to_string()))
+            .collect::<Vec<_>>();
+        aliases.sort();
+        assert_eq!(
+            aliases,
+            vec![
+                ("default".to_string(), "3.0.1".to_string()),
+                ("legacy".to_string(), "2.7.2".to_string()),
+            ]
+        );
+        assert_eq!(
+            std::fs::read_to_string(project_dir.path().join(".ruby-version")).unwrap(),
+            "3.0.1\n"
+        );
+    }
+
+    #[test]
+    fn test_upgrade_stays_in_series() {
+        let remote_versions = ["3.0.6", "3.1.2", "3.1.4", "3.2.2"]
+            .iter()
+            .map(|version| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        let newest = |old_version: &str| {
+            newest_in_series(&Version::parse(old_version).unwrap(), &remote_versions)
+                .map(|version| version.to_string())
+        };
+
+        assert_eq!(newest("3.1.2"), Some("3.1.4".to_string()));
+        assert_eq!(newest("3.0.1"), Some("3.0.6".to_string()));
+        assert_eq!(newest("3.2.1"), Some("3.2.2".to_string()));
+        assert_eq!(newest("2.7.2"), None);
+    }
+}
@@ -1,8 +1,16 @@
+use crate::alias::{find_aliases, StoredAlias};
+use crate::command::ErrorKind;
+use crate::commands::cache::{disk_size, format_size};
+use crate::commands::install::InstalledBy;
 use crate::config::FrumConfig;
+use crate::eol::{eol_status, eol_status_on, EolStatus};
+use crate::json::JsonEnvelope;
 use crate::outln;
-use crate::version::{current_version, Version};
+use crate::version::{current_version, installed_versions, Version};
+use chrono::NaiveDate;
 use colored::Colorize;
-use log::debug;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -13,45 +21,651 @@ pub enum FrumError {
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     SemverError(#[from] semver::SemVerError),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error("Can't list the remote versions: {source}")]
+    CantListRemoteVersions {
+        source: crate::remote_ruby_index::Error,
+    },
+    #[error("Can't find the directory {}", dir.display())]
+    PrefixNotFound { dir: PathBuf },
 }
 
-pub struct Versions {}
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::SemverError(_) => "SemverError",
+            Self::VersionError(err) => err.kind(),
+            Self::CantListRemoteVersions { .. } => "CantListRemoteVersions",
+            Self::PrefixNotFound { .. } => "PrefixNotFound",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VersionsFormat {
+    List,
+    Table,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum VersionsSort {
+    Version,
+    /// Biggest first.
+    Size,
+}
+
+pub struct Versions {
+    pub outdated: bool,
+    pub stable_only: bool,
+    pub format: VersionsFormat,
+    pub current_only: bool,
+    /// Shows the disk size of each version in the list format.
+    pub sizes: bool,
+    pub sort: VersionsSort,
+    /// Lists the versions in this directory, such as the versions directory of rbenv,
+    /// instead of the installed ones.
+    pub prefix: Option<PathBuf>,
+    /// Lists only the versions obtained this way.
+    pub installed_by: Option<InstalledBy>,
+    /// Prints only how many versions would be listed.
+    pub count: bool,
+    /// Counts the versions available to install instead of the installed ones, with `count`.
+    pub remote: bool,
+    /// Prints the versions as JSON instead of the list or the table.
+    pub json: bool,
+}
+
+/// A version as printed by `versions --json`, for editors and other tools.
+#[derive(Serialize, Debug)]
+struct ListedVersion {
+    version: String,
+    current: bool,
+    aliases: Vec<String>,
+    /// The size on disk in bytes, with `--sizes` or `--sort size`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    /// The newest patch release of the series, with `--outdated`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    available: Option<String>,
+}
 
 impl crate::command::Command for Versions {
     type Error = FrumError;
 
     fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
-        for entry in config
-            .versions_dir()
-            .read_dir()
-            .map_err(FrumError::IoError)?
-        {
-            let entry = entry.map_err(FrumError::IoError)?;
-            if crate::version::is_dotfile(&entry) {
-                continue;
+        if self.current_only {
+            // Prints nothing outside of a frum shell, and `system` once it was selected
+            if let Some(version) = current_version(config).ok().flatten() {
+                outln!(config#Info, "{}", version);
             }
-
-            let path = entry.path();
-            let filename = path
-                .file_name()
-                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                .map_err(FrumError::IoError)?
-                .to_str()
-                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
-                .map_err(FrumError::IoError)?;
-            let version = Version::parse(filename).map_err(FrumError::SemverError)?;
-            let current_version = current_version(&config).ok().flatten();
-            debug!("current version: {}", current_version.clone().unwrap());
-            if let Some(current_version) = current_version {
-                if current_version == version {
-                    outln!(config#Info, "{} {}", "*".green(), version.to_string().green());
-                } else {
-                    outln!(config#Info, "{} {}", " ", version);
-                }
-            } else {
-                outln!(config#Info, "{} {}", " ", version);
+            return Ok(());
+        }
+        if self.count {
+            outln!(config#Info, "{}", self.count(config)?);
+            return Ok(());
+        }
+        let remote_versions = if self.outdated {
+            crate::remote_ruby_index::list_versions(config, false)
+                .map_err(|source| FrumError::CantListRemoteVersions { source })?
+        } else {
+            vec![]
+        };
+        let (versions_dir, mut versions) = self.listed_versions(config)?;
+        // Versions outside of frum are neither current nor aliased
+        let current = match self.prefix {
+            Some(_) => None,
+            None => current_version(config).ok().flatten(),
+        };
+        if self.sort == VersionsSort::Size {
+            versions = sort_by_size(&versions_dir, versions)?;
+        }
+        if self.json {
+            let aliases = match self.prefix {
+                Some(_) => vec![],
+                None => find_aliases(config)?,
             };
+            let with_sizes = self.sizes || self.sort == VersionsSort::Size;
+            let versions = listed_versions_json(
+                &versions_dir,
+                &versions,
+                current.as_ref(),
+                &aliases,
+                &remote_versions,
+                with_sizes,
+            )?;
+            let versions = JsonEnvelope::new(serde_json::json!({ "versions": versions }));
+            outln!(config#Info, "{}", versions);
+            return Ok(());
+        }
+        if self.format == VersionsFormat::Table {
+            let today = chrono::Local::now().naive_local().date();
+            let aliases = match self.prefix {
+                Some(_) => vec![],
+                None => find_aliases(config)?,
+            };
+            let rows = table_rows(&versions_dir, &versions, current.as_ref(), &aliases, today)?;
+            outln!(config#Info, "{}", render_table(&rows));
+            return Ok(());
+        }
+        let print = |version: &Version, size: Option<u64>| {
+            let is_current = current.as_ref() == Some(version);
+            print_version(config, version, is_current, &remote_versions, size)
+        };
+        if self.sort == VersionsSort::Size {
+            // Grouping by series would undo the order, so the sizes are listed flat
+            for version in &versions {
+                print(
+                    version,
+                    Some(disk_size(&versions_dir.join(version.to_string()))?),
+                );
+            }
+            return Ok(());
+        }
+        for (series, versions) in group_by_minor(&versions) {
+            match series {
+                Some((major, minor)) => {
+                    outln!(config#Info, "{}", format!("{}.{}", major, minor).bold())
+                }
+                None => outln!(config#Info, "{}", "other".bold()),
+            }
+            for version in versions {
+                let size = if self.sizes {
+                    Some(disk_size(&versions_dir.join(version.to_string()))?)
+                } else {
+                    None
+                };
+                print(version, size);
+            }
         }
         Ok(())
     }
 }
+
+impl Versions {
+    /// The directory the listed versions are in, and the versions left after the filters.
+    fn listed_versions(&self, config: &FrumConfig) -> Result<(PathBuf, Vec<Version>), FrumError> {
+        let (versions_dir, versions) = match &self.prefix {
+            Some(prefix) => (prefix.clone(), versions_in_prefix(config, prefix)?),
+            None => (config.versions_dir(), installed_versions(config)?),
+        };
+        let mut versions = versions
+            .into_iter()
+            .filter(|version| !self.stable_only || !version.is_prerelease())
+            .collect::<Vec<_>>();
+        if let Some(installed_by) = self.installed_by {
+            versions = filter_installed_by(&versions_dir, versions, installed_by);
+        }
+        Ok((versions_dir, versions))
+    }
+
+    /// How many versions are listed, or available to install with `remote`, without
+    /// reading their sizes or looking for newer patches.
+    fn count(&self, config: &FrumConfig) -> Result<usize, FrumError> {
+        if self.remote {
+            return Ok(
+                crate::remote_ruby_index::list_versions(config, !self.stable_only)
+                    .map_err(|source| FrumError::CantListRemoteVersions { source })?
+                    .len(),
+            );
+        }
+        Ok(self.listed_versions(config)?.1.len())
+    }
+}
+
+fn print_version(
+    config: &FrumConfig,
+    version: &Version,
+    is_current: bool,
+    remote_versions: &[Version],
+    size: Option<u64>,
+) {
+    let size = match size {
+        Some(size) => format!(" {}", format!("({})", format_size(size)).dimmed()),
+        None => String::new(),
+    };
+    let upgrade = match newer_patch(version, remote_versions) {
+        Some(upgrade) => format!("{}{}", size, format!(" ({} available)", upgrade).yellow()),
+        None => size,
+    };
+    let upgrade = match eol_status(version) {
+        EolStatus::Past { eol } => format!("{}{}", upgrade, format!(" (EOL since {})", eol).red()),
+        _ => upgrade,
+    };
+    if is_current {
+        outln!(config#Info, "{} {}{}", "*".green(), version.to_string().green(), upgrade);
+    } else {
+        outln!(config#Info, "{} {}{}", " ", version, upgrade);
+    }
+}
+
+/// The versions in `prefix`, read like the versions directory but skipping, with a
+/// warning, the directories that aren't named after a version.
+fn versions_in_prefix(config: &FrumConfig, prefix: &Path) -> Result<Vec<Version>, FrumError> {
+    if !prefix.is_dir() {
+        return Err(FrumError::PrefixNotFound {
+            dir: prefix.to_path_buf(),
+        });
+    }
+    let mut versions = vec![];
+    for entry in prefix.read_dir()? {
+        match Version::from_dir_entry(&entry?) {
+            Ok(Some(version)) => versions.push(version),
+            Ok(None) => {}
+            Err(crate::version::Error::InvalidVersionDir { name }) => {
+                outln!(config#Error, "{} Skipping {}: it isn't a Ruby version", "warning:".yellow().bold(), name);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    versions.sort();
+    Ok(versions)
+}
+
+/// The versions in `versions_dir` that were obtained as `installed_by` says.
+fn filter_installed_by(
+    versions_dir: &Path,
+    versions: Vec<Version>,
+    installed_by: InstalledBy,
+) -> Vec<Version> {
+    versions
+        .into_iter()
+        .filter(|version| InstalledBy::of(&versions_dir.join(version.to_string())) == installed_by)
+        .collect()
+}
+
+type Row = [String; 5];
+
+/// Builds a table row for each of `versions` in `versions_dir`, marking series past
+/// their end of life on `today`.
+fn table_rows(
+    versions_dir: &Path,
+    versions: &[Version],
+    current_version: Option<&Version>,
+    aliases: &[StoredAlias],
+    today: NaiveDate,
+) -> Result<Vec<Row>, FrumError> {
+    let mut rows = vec![];
+    for version in versions {
+        let version_dir = versions_dir.join(version.to_string());
+        let modified = version_dir.metadata()?.modified()?;
+        let mut names = aliases
+            .iter()
+            .filter(|alias| alias.s_ver() == version.to_string())
+            .map(|alias| alias.name())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        let marker = if current_version == Some(version) {
+            "*"
+        } else {
+            " "
+        };
+        rows.push([
+            format!("{} {}", marker, version),
+            chrono::DateTime::<chrono::Local>::from(modified)
+                .format("%Y-%m-%d")
+                .to_string(),
+            format_size(disk_size(&version_dir)?),
+            if names.is_empty() {
+                "-".to_string()
+            } else {
+                names.join(",")
+            },
+            eol_status_on(version, today).to_string(),
+        ]);
+    }
+    Ok(rows)
+}
+
+/// Describes each of `versions` in `versions_dir` for `versions --json`, with its
+/// size when `with_sizes` and its newest patch among `remote_versions`.
+fn listed_versions_json(
+    versions_dir: &Path,
+    versions: &[Version],
+    current_version: Option<&Version>,
+    aliases: &[StoredAlias],
+    remote_versions: &[Version],
+    with_sizes: bool,
+) -> Result<Vec<ListedVersion>, FrumError> {
+    let mut listed = vec![];
+    for version in versions {
+        let mut names = aliases
+            .iter()
+            .filter(|alias| alias.s_ver() == version.to_string())
+            .map(|alias| alias.name().to_string())
+            .collect::<Vec<_>>();
+        names.sort_unstable();
+        let size = if with_sizes {
+            Some(disk_size(&versions_dir.join(version.to_string()))?)
+        } else {
+            None
+        };
+        listed.push(ListedVersion {
+            version: version.to_string(),
+            current: current_version == Some(version),
+            aliases: names,
+            size,
+            available: newer_patch(version, remote_versions).map(|upgrade| upgrade.to_string()),
+        });
+    }
+    Ok(listed)
+}
+
+/// Lines up `rows` in columns under a header.
+fn render_table(rows: &[Row]) -> String {
+    let header: Row = [
+        "  VERSION".to_string(),
+        "INSTALLED".to_string(),
+        "SIZE".to_string(),
+        "ALIASES".to_string(),
+        "EOL".to_string(),
+    ];
+    let mut widths = [0; 5];
+    for row in std::iter::once(&header).chain(rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    std::iter::once(&header)
+        .chain(rows)
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Orders `versions` in `versions_dir` from the biggest on disk to the smallest,
+/// keeping the version order between versions of the same size.
+fn sort_by_size(versions_dir: &Path, versions: Vec<Version>) -> std::io::Result<Vec<Version>> {
+    let mut sized = versions
+        .into_iter()
+        .map(|version| {
+            let size = disk_size(&versions_dir.join(version.to_string()))?;
+            Ok((size, version))
+        })
+        .collect::<std::io::Result<Vec<_>>>()?;
+    sized.sort_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(sized.into_iter().map(|(_, version)| version).collect())
+}
+
+type Series<'a> = (Option<(u64, u64)>, Vec<&'a Version>);
+
+/// Groups sorted versions by their major.minor series, keeping the order.
+fn group_by_minor(versions: &[Version]) -> Vec<Series<'_>> {
+    let mut groups: Vec<Series<'_>> = vec![];
+    for version in versions {
+        match groups.last_mut() {
+            Some((series, group)) if *series == version.major_minor() => group.push(version),
+            _ => groups.push((version.major_minor(), vec![version])),
+        }
+    }
+    groups
+}
+
+/// Finds the newest stable release in the same major.minor series that is newer than `version`.
+fn newer_patch<'a>(version: &Version, remote_versions: &'a [Version]) -> Option<&'a Version> {
+    let series = version.major_minor()?;
+    remote_versions
+        .iter()
+        .filter(|remote| {
+            remote.major_minor() == Some(series) && !remote.is_prerelease() && *remote > version
+        })
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_newer_patch() {
+        let remote_versions = [
+            "2.6.5",
+            "2.6.6",
+            "2.7.0",
+            "2.7.1",
+            "2.7.2",
+            "3.0.0-preview1",
+            "3.0.0",
+            "3.1.0",
+        ]
+        .iter()
+        .map(|v| Version::parse(v).unwrap())
+        .collect::<Vec<_>>();
+        let upgrade = |installed: &str| {
+            newer_patch(&Version::parse(installed).unwrap(), &remote_versions)
+                .map(|v| v.to_string())
+        };
+
+        assert_eq!(upgrade("2.6.5"), Some("2.6.6".to_string()));
+        assert_eq!(upgrade("2.7.0"), Some("2.7.2".to_string()));
+        assert_eq!(upgrade("2.7.2"), None);
+        assert_eq!(upgrade("3.0.0"), None);
+        assert_eq!(upgrade("3.0.0-preview1"), Some("3.0.0".to_string()));
+    }
+
+    #[test]
+    fn test_group_by_minor() {
+        let versions = ["2.6.5", "2.7.0", "2.7.2", "3.0.0-preview1", "3.0.0"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect::<Vec<_>>();
+
+        let groups = group_by_minor(&versions)
+            .into_iter()
+            .map(|(series, versions)| {
+                (
+                    series,
+                    versions.iter().map(|v| v.to_string()).collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            groups,
+            vec![
+                (Some((2, 6)), vec!["2.6.5".to_string()]),
+                (Some((2, 7)), vec!["2.7.0".to_string(), "2.7.2".to_string()]),
+                (
+                    Some((3, 0)),
+                    vec!["3.0.0-preview1".to_string(), "3.0.0".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_installed_by() {
+        let versions_dir = tempdir().unwrap();
+        let manifests = [
+            ("2.7.8", None),
+            ("3.0.6", Some("version\t3.0.6\nfrom\t/opt/ruby\n")),
+            ("3.1.4", Some("installed-by\timported\n")),
+            ("3.2.2", Some("installed-by\tprebuilt\n")),
+            ("3.3.0", None),
+        ];
+        for (version, manifest) in &manifests {
+            let version_dir = versions_dir.path().join(version);
+            std::fs::create_dir_all(&version_dir).unwrap();
+            if let Some(manifest) = manifest {
+                std::fs::write(version_dir.join(".frum-prebuilt"), manifest).unwrap();
+            }
+        }
+        let versions = manifests
+            .iter()
+            .map(|(version, _)| Version::parse(version).unwrap())
+            .collect::<Vec<_>>();
+        let filter = |installed_by| {
+            filter_installed_by(versions_dir.path(), versions.clone(), installed_by)
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(filter(InstalledBy::Source), vec!["2.7.8", "3.3.0"]);
+        assert_eq!(filter(InstalledBy::Prebuilt), vec!["3.0.6", "3.2.2"]);
+        assert_eq!(filter(InstalledBy::Imported), vec!["3.1.4"]);
+    }
+
+    #[test]
+    fn test_count_versions() {
+        let config = FrumConfig {
+            offline: true,
+            ..FrumConfig::default().with_base_dir(tempdir().unwrap().path())
+        };
+        for version in &["2.7.8", "3.2.2", "3.3.0-preview1"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        std::fs::write(
+            config.versions_dir().join("3.2.2").join(".frum-prebuilt"),
+            "installed-by\tprebuilt\n",
+        )
+        .unwrap();
+        std::fs::write(
+            config.cache_dir().join("index.txt"),
+            "name\turl\tsha1\tsha256\tsha512
+ruby-3.1.4\thttps://cache.ruby-lang.org/pub/ruby/3.1/ruby-3.1.4.tar.gz\ta\tb\tc
+ruby-3.2.2\thttps://cache.ruby-lang.org/pub/ruby/3.2/ruby-3.2.2.tar.gz\ta\tb\tc
+ruby-3.3.0-preview1\thttps://cache.ruby-lang.org/pub/ruby/3.3/ruby-3.3.0-preview1.tar.gz\ta\tb\tc
+ruby-3.3.0\thttps://cache.ruby-lang.org/pub/ruby/3.3/ruby-3.3.0.tar.gz\ta\tb\tc
+",
+        )
+        .unwrap();
+        let count = |stable_only: bool, installed_by: Option<InstalledBy>, remote: bool| {
+            Versions {
+                outdated: false,
+                stable_only,
+                format: VersionsFormat::List,
+                current_only: false,
+                sizes: false,
+                sort: VersionsSort::Version,
+                prefix: None,
+                installed_by,
+                count: true,
+                remote,
+                json: false,
+            }
+            .count(&config)
+            .unwrap()
+        };
+
+        assert_eq!(count(false, None, false), 3);
+        assert_eq!(count(true, None, false), 2);
+        assert_eq!(count(false, Some(InstalledBy::Prebuilt), false), 1);
+        assert_eq!(count(false, None, true), 4);
+        assert_eq!(count(true, None, true), 3);
+    }
+
+    #[test]
+    fn test_table_shows_aliases_and_eol() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.6.10", "3.3.0"] {
+            let bin_dir = config.versions_dir().join(version).join("bin");
+            std::fs::create_dir_all(&bin_dir).unwrap();
+            std::fs::write(bin_dir.join("ruby"), "ruby").unwrap();
+        }
+        crate::symlink::create_symlink_dir(
+            config.versions_dir().join("3.3.0"),
+            config.aliases_dir().join("default"),
+        )
+        .unwrap();
+        let versions = installed_versions(&config).unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 14).unwrap();
+
+        let rows = table_rows(
+            &config.versions_dir(),
+            &versions,
+            None,
+            &find_aliases(&config).unwrap(),
+            today,
+        )
+        .unwrap();
+        let table = render_table(&rows);
+        let lines = table.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        for column in &["VERSION", "INSTALLED", "SIZE", "ALIASES", "EOL"] {
+            assert!(lines[0].contains(column));
+        }
+        assert!(lines[1].contains("2.6.10"));
+        assert!(lines[1].contains("4 B"));
+        assert!(lines[1].ends_with("2022-04-12 (EOL)"));
+        assert!(lines[2].contains("default"));
+        assert!(lines[2].ends_with("2027-03-31 (soon)"));
+
+        // Installing a gem only changes a nested directory
+        let gems_dir = config.versions_dir().join("2.6.10/lib/ruby/gems");
+        std::fs::create_dir_all(&gems_dir).unwrap();
+        std::fs::write(gems_dir.join("rake.gem"), "rake").unwrap();
+        let rows = table_rows(
+            &config.versions_dir(),
+            &versions,
+            None,
+            &find_aliases(&config).unwrap(),
+            today,
+        )
+        .unwrap();
+        assert!(render_table(&rows).lines().nth(1).unwrap().contains("8 B"));
+    }
+
+    #[test]
+    fn test_versions_json() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["3.2.2", "3.3.0"] {
+            std::fs::create_dir_all(config.versions_dir().join(version)).unwrap();
+        }
+        std::fs::write(config.versions_dir().join("3.2.2").join("ruby"), "ruby").unwrap();
+        crate::symlink::create_symlink_dir(
+            config.versions_dir().join("3.3.0"),
+            config.aliases_dir().join("default"),
+        )
+        .unwrap();
+        let versions = installed_versions(&config).unwrap();
+        let current = Version::parse("3.3.0").unwrap();
+        let remote_versions = [Version::parse("3.2.3").unwrap()];
+
+        let listed = listed_versions_json(
+            &config.versions_dir(),
+            &versions,
+            Some(&current),
+            &find_aliases(&config).unwrap(),
+            &remote_versions,
+            true,
+        )
+        .unwrap();
+        let json: serde_json::Value = serde_json::from_str(
+            &JsonEnvelope::new(serde_json::json!({ "versions": listed })).to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(json["schema"], crate::json::SCHEMA);
+        assert_eq!(
+            json["versions"],
+            serde_json::json!([
+                {
+                    "version": "3.2.2",
+                    "current": false,
+                    "aliases": [],
+                    "size": 4,
+                    "available": "3.2.3",
+                },
+                {
+                    "version": "3.3.0",
+                    "current": true,
+                    "aliases": ["default"],
+                    "size": 0,
+                },
+            ])
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use crate::command::ErrorKind;
+use crate::commands::shell::gem_home;
+use crate::config::FrumConfig;
+use crate::json::JsonEnvelope;
+use crate::outln;
+use crate::version::{current_version, installed_versions, Version};
+use colored::Colorize;
+use serde::Serialize;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FrumError {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    VersionError(#[from] crate::version::Error),
+    #[error(
+        "No Ruby version is used in this shell. Please run `frum local` or `frum global` first."
+    )]
+    NoCurrentVersion,
+    #[error("Ruby {version} doesn't have {binary}")]
+    BinaryNotFound { binary: String, version: Version },
+}
+
+impl ErrorKind for FrumError {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::VersionError(err) => err.kind(),
+            Self::NoCurrentVersion => "NoCurrentVersion",
+            Self::BinaryNotFound { .. } => "BinaryNotFound",
+        }
+    }
+}
+
+pub struct Which {
+    pub binary: String,
+    pub all: bool,
+    pub json: bool,
+    /// Also looks for the binary where gems install their executables.
+    pub gem: bool,
+    /// Prints the real path of the binary, with the symlinks of aliases and linked versions resolved.
+    pub resolve_symlinks: bool,
+}
+
+/// A binary as printed by `which --json`, for editors and other tools.
+#[derive(Serialize, Debug)]
+struct FoundBinary {
+    version: String,
+    bin: String,
+    path: PathBuf,
+}
+
+impl crate::command::Command for Which {
+    type Error = FrumError;
+
+    fn apply(&self, config: &FrumConfig) -> Result<(), Self::Error> {
+        if self.all {
+            let (found, missing) = binary_paths(config, &self.binary, self.gem)?;
+            let found = found
+                .into_iter()
+                .map(|(version, path)| Ok((version, self.real_path(path)?)))
+                .collect::<Result<Vec<_>, FrumError>>()?;
+            if self.json {
+                let found = found
+                    .into_iter()
+                    .map(|(version, path)| self.found(version, path))
+                    .collect::<Vec<_>>();
+                let found = JsonEnvelope::new(serde_json::json!({ "binaries": found }));
+                outln!(config#Info, "{}", found);
+                return Ok(());
+            }
+            for version in missing {
+                outln!(config#Error, "{} Ruby {} doesn't have {}", "warning:".yellow().bold(), version, self.binary);
+            }
+            for (version, path) in found {
+                outln!(config#Info, "{}\t{}", version, path.display());
+            }
+            return Ok(());
+        }
+
+        let resolved = self
+            .resolve(config)
+            .and_then(|(version, path)| Ok((version, self.real_path(path)?)));
+        match (resolved, self.json) {
+            (Ok((_, path)), false) => outln!(config#Info, "{}", path.display()),
+            (Ok((version, path)), true) => {
+                let found = JsonEnvelope::new(self.found(version, path));
+                outln!(config#Info, "{}", found);
+            }
+            (Err(err), true) => {
+                // Tools reading stdout get the error too, and the exit code still says it failed
+                let error = JsonEnvelope::new(serde_json::json!({ "error": err.to_string() }));
+                outln!(config#Info, "{}", error);
+                return Err(err);
+            }
+            (Err(err), false) => return Err(err),
+        }
+        Ok(())
+    }
+}
+
+impl Which {
+    /// The current version and the path to the binary in it.
+    fn resolve(&self, config: &FrumConfig) -> Result<(Version, PathBuf), FrumError> {
+        let version = current_version(config)?.ok_or(FrumError::NoCurrentVersion)?;
+        let path = match version {
+            Version::System => system_binary_path(config, &self.binary, std::env::var_os("PATH"))
+                .unwrap_or_default(),
+            _ if self.gem => {
+                gem_binary_path(config, &version, &self.binary, std::env::var_os("GEM_HOME"))
+                    .unwrap_or_default()
+            }
+            _ => binary_path(config, &version, &self.binary),
+        };
+        if !path.exists() {
+            return Err(FrumError::BinaryNotFound {
+                binary: self.binary.clone(),
+                version,
+            });
+        }
+        Ok((version, path))
+    }
+
+    /// `path` with every symlink resolved with `--resolve-symlinks`, or as it is.
+    fn real_path(&self, path: PathBuf) -> Result<PathBuf, FrumError> {
+        if self.resolve_symlinks {
+            Ok(std::fs::canonicalize(path)?)
+        } else {
+            Ok(path)
+        }
+    }
+
+    fn found(&self, version: Version, path: PathBuf) -> FoundBinary {
+        FoundBinary {
+            version: version.to_string(),
+            bin: self.binary.clone(),
+            path,
+        }
+    }
+}
+
+/// Where `binary` is in the installation of `version`, whether or not it exists.
+pub fn binary_path(config: &FrumConfig, version: &Version, binary: &str) -> PathBuf {
+    let version_dir = config.versions_dir().join(version.to_string());
+    if cfg!(windows) {
+        version_dir.join(binary)
+    } else {
+        version_dir.join("bin").join(binary)
+    }
+}
+
+/// Where the gem executable `binary` of `version` is, found without a rehash step by
+/// looking in the bin directory of the version, which is the default gem bindir, then
+/// in `$GEM_HOME/bin`, then in the bin directory of the gem home frum gives the version.
+pub fn gem_binary_path(
+    config: &FrumConfig,
+    version: &Version,
+    binary: &str,
+    gem_home_var: Option<std::ffi::OsString>,
+) -> Option<PathBuf> {
+    let version_dir = config.versions_dir().join(version.to_string());
+    let gem_homes = gem_home_var
+        .filter(|gem_home| !gem_home.is_empty())
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(gem_home(&version_dir, version));
+    std::iter::once(binary_path(config, version, binary))
+        .chain(gem_homes.map(|gem_home| gem_home.join("bin").join(binary)))
+        .find(|path| path.is_file())
+}
+
+/// Where `binary` is on `path` outside of frum, which is where the system Ruby comes from.
+fn system_binary_path(
+    config: &FrumConfig,
+    binary: &str,
+    path: Option<std::ffi::OsString>,
+) -> Option<PathBuf> {
+    let frum_dirs: Vec<_> = std::iter::once(config.base_dir())
+        .chain(config.multishells_dir().ok())
+        .collect();
+    std::env::split_paths(&path.unwrap_or_default())
+        .filter(|dir| !frum_dirs.iter().any(|frum_dir| dir.starts_with(frum_dir)))
+        .map(|dir| dir.join(binary))
+        .find(|path| path.is_file())
+}
+
+type BinaryPaths = (Vec<(Version, PathBuf)>, Vec<Version>);
+
+/// Splits the installed versions into the ones having `binary`, with its path, and the ones missing it.
+/// With `gem`, the gem executables of each version are looked for too.
+fn binary_paths(config: &FrumConfig, binary: &str, gem: bool) -> Result<BinaryPaths, FrumError> {
+    let mut found = vec![];
+    let mut missing = vec![];
+    for version in installed_versions(config)? {
+        let path = if gem {
+            gem_binary_path(config, &version, binary, std::env::var_os("GEM_HOME"))
+        } else {
+            Some(binary_path(config, &version, binary)).filter(|path| path.exists())
+        };
+        match path {
+            Some(path) => found.push((version, path)),
+            None => missing.push(version),
+        }
+    }
+    Ok((found, missing))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_paths, gem_binary_path, system_binary_path, JsonEnvelope, Which};
+    use crate::config::FrumConfig;
+    use crate::symlink::create_symlink_dir;
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_binary_paths_skip_versions_without_binary() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        for version in &["2.6.4", "2.7.2", "3.0.0"] {
+            std::fs::create_dir_all(config.versions_dir().join(version).join("bin")).unwrap();
+        }
+        for version in &["2.6.4", "3.0.0"] {
+            std::fs::write(
+                config.versions_dir().join(version).join("bin").join("ruby"),
+                "",
+            )
+            .unwrap();
+        }
+
+        let (found, missing) = binary_paths(&config, "ruby", false).unwrap();
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Version::parse("2.6.4").unwrap(),
+                    config.versions_dir().join("2.6.4").join("bin").join("ruby")
+                ),
+                (
+                    Version::parse("3.0.0").unwrap(),
+                    config.versions_dir().join("3.0.0").join("bin").join("ruby")
+                ),
+            ]
+        );
+        assert_eq!(missing, vec![Version::parse("2.7.2").unwrap()]);
+    }
+
+    #[test]
+    fn test_found_binary_as_json() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default().with_base_dir(base_dir.path());
+        let version_dir = config.versions_dir().join("3.2.2");
+        std::fs::create_dir_all(version_dir.join("bin")).unwrap();
+        std::fs::write(version_dir.join("bin").join("rake"), "").unwrap();
+        let config = config.with_frum_path(&version_dir);
+        let which = Which {
+            binary: "rake".to_string(),
+            all: false,
+            json: true,
+            gem: false,
+            resolve_symlinks: false,
+        };
+
+        let (version, path) = which.resolve(&config).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&JsonEnvelope::new(which.found(version, path)).to_string())
+                .unwrap();
+
+        assert_eq!(json["schema"], crate::json::SCHEMA);
+        assert_eq!(json["version"], "3.2.2");
+        assert_eq!(json["bin"], "rake");
+        assert_eq!(
+            json["path"].as_str().map(std::path::PathBuf::from),
+            Some(version_dir.join("bin").join("rake"))
+        );
+        assert!(Which {
+            binary: "rails".to_string(),
+            ..which
+        }
+        .resolve(&config)
+        .is_err());
+    }
+
+    #[test]
+    fn test_which_gem_finds_gem_executables() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default().with_base_dir(base_dir.path());
+        let version = Version::parse("3.2.2").unwrap();
+        let version_dir = config.versions_dir().join("3.2.2");
+        let gem_bin = version_dir
+            .join("lib")
+            .join("ruby")
+            .join("gems")
+            .join("3.2.0")
+            .join("bin");
+        std::fs::create_dir_all(version_dir.join("bin")).unwrap();
+        std::fs::create_dir_all(&gem_bin).unwrap();
+        std::fs::write(gem_bin.join("rubocop"), "").unwrap();
+        let config = config.with_frum_path(&version_dir);
+        let which = Which {
+            binary: "rubocop".to_string(),
+            all: false,
+            json: false,
+            gem: true,
+            resolve_symlinks: false,
+        };
+
+        assert_eq!(which.resolve(&config).unwrap().1, gem_bin.join("rubocop"));
+        assert!(Which {
+            gem: false,
+            ..which
+        }
+        .resolve(&config)
+        .is_err());
+
+        let gem_home = tempdir().unwrap();
+        std::fs::create_dir_all(gem_home.path().join("bin")).unwrap();
+        std::fs::write(gem_home.path().join("bin").join("rubocop"), "").unwrap();
+        assert_eq!(
+            gem_binary_path(&config, &version, "rubocop", Some(gem_home.path().into())),
+            Some(gem_home.path().join("bin").join("rubocop"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_symlinks_of_aliased_version() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default().with_base_dir(base_dir.path());
+        let linked_dir = tempdir().unwrap();
+        let real_dir = linked_dir.path().join("3.2.2");
+        std::fs::create_dir_all(real_dir.join("bin")).unwrap();
+        std::fs::write(real_dir.join("bin").join("ruby"), "").unwrap();
+        let version_dir = config.versions_dir().join("3.2.2");
+        create_symlink_dir(&real_dir, &version_dir).unwrap();
+        let alias_dir = config.aliases_dir().join("lts");
+        create_symlink_dir(&version_dir, &alias_dir).unwrap();
+        let config = config.with_frum_path(&alias_dir);
+        let which = Which {
+            binary: "ruby".to_string(),
+            all: false,
+            json: false,
+            gem: false,
+            resolve_symlinks: true,
+        };
+
+        let (_, path) = which.resolve(&config).unwrap();
+        assert_eq!(path, version_dir.join("bin").join("ruby"));
+        assert_eq!(
+            which.real_path(path).unwrap(),
+            std::fs::canonicalize(&real_dir)
+                .unwrap()
+                .join("bin")
+                .join("ruby")
+        );
+    }
+
+    #[test]
+    fn test_system_binary_path_skips_frum_dirs() {
+        let base_dir = tempdir().unwrap();
+        let config = FrumConfig::default().with_base_dir(base_dir.path());
+        let frum_bin = config.versions_dir().join("3.2.2").join("bin");
+        let system_bin = tempdir().unwrap();
+        for dir in &[frum_bin.as_path(), system_bin.path()] {
+            std::fs::create_dir_all(dir).unwrap();
+            std::fs::write(dir.join("ruby"), "").unwrap();
+        }
+        let path = std::env::join_paths(&[frum_bin, system_bin.path().to_path_buf()]).unwrap();
+
+        assert_eq!(
+            system_binary_path(&config, "ruby", Some(path.clone())),
+            Some(system_bin.path().join("ruby"))
+        );
+        assert_eq!(system_binary_path(&config, "rails", Some(path)), None);
+    }
+}
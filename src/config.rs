@@ -1,28 +1,132 @@
 use crate::log::LogLevel;
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 #[derive(Debug)]
 pub struct FrumConfig {
     pub base_dir: Option<PathBuf>,
+    pub versions_dir: Option<PathBuf>,
+    pub aliases_dir: Option<PathBuf>,
+    pub cache_dir: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
     pub ruby_build_mirror: reqwest::Url,
+    /// More mirrors to install from, of which the fastest is picked along with `ruby_build_mirror`.
+    pub alternative_mirrors: Vec<reqwest::Url>,
+    /// Where the list of versions is fetched from, when it isn't `index.txt` on the mirror.
+    pub mirror_index: Option<reqwest::Url>,
     pub log_level: LogLevel,
+    /// The file that gets a timestamped copy of all output, whatever the log level.
+    pub log_file: Option<PathBuf>,
     pub frum_path: Option<PathBuf>,
+    pub offline: bool,
+    pub timings: bool,
+    /// Prints errors as JSON, with the kind of error, for programs running frum.
+    pub json_errors: bool,
+    pub install_if_missing: bool,
+    /// The shell used when the one frum runs in can't be inferred, from `FRUM_SHELL`.
+    pub assume_shell: Option<String>,
+    pub shell_version: Option<crate::input_version::InputVersion>,
+    /// Where builds and registered versions are staged before they land in the versions,
+    /// from `FRUM_TMPDIR`.
+    pub tmpdir: Option<PathBuf>,
+    /// Where the session symlinks go instead of the directory of the user in
+    /// `XDG_RUNTIME_DIR` or the temp directory.
+    pub multishells_dir: Option<PathBuf>,
 }
 
 impl Default for FrumConfig {
     fn default() -> Self {
         Self {
-            base_dir: std::env::var("FRUM_DIR").map(std::path::PathBuf::from).ok(),
+            base_dir: std::env::var("FRUM_DIR").map(|dir| expand_path(&dir)).ok(),
+            versions_dir: std::env::var("FRUM_VERSIONS_DIR")
+                .map(|dir| expand_path(&dir))
+                .ok(),
+            aliases_dir: std::env::var("FRUM_ALIASES_DIR")
+                .map(|dir| expand_path(&dir))
+                .ok(),
+            cache_dir: xdg_dir(std::env::var_os("XDG_CACHE_HOME")),
+            config_dir: xdg_dir(std::env::var_os("XDG_CONFIG_HOME")),
             ruby_build_mirror: reqwest::Url::parse("https://cache.ruby-lang.org/pub/ruby").unwrap(),
+            alternative_mirrors: vec![],
+            mirror_index: None,
             log_level: LogLevel::default(),
+            log_file: None,
             frum_path: std::env::var("FRUM_MULTISHELL_PATH")
                 .map(std::path::PathBuf::from)
                 .ok(),
+            offline: false,
+            timings: false,
+            json_errors: false,
+            install_if_missing: std::env::var("FRUM_INSTALL_IF_MISSING")
+                .map(|value| value == "1" || value == "true")
+                .unwrap_or(false),
+            assume_shell: std::env::var("FRUM_SHELL")
+                .ok()
+                .filter(|shell| !shell.is_empty()),
+            shell_version: std::env::var("FRUM_SHELL_VERSION")
+                .ok()
+                .and_then(|version| version.parse().ok()),
+            tmpdir: std::env::var("FRUM_TMPDIR")
+                .ok()
+                .filter(|dir| !dir.is_empty())
+                .map(|dir| expand_path(&dir)),
+            multishells_dir: None,
         }
     }
 }
 
 impl FrumConfig {
+    pub fn with_base_dir(self, base_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            base_dir: Some(base_dir.into()),
+            ..self
+        }
+    }
+
+    /// Uses `frum_path` as the version symlink of the shell session, like `FRUM_MULTISHELL_PATH`.
+    pub fn with_frum_path(self, frum_path: impl Into<PathBuf>) -> Self {
+        Self {
+            frum_path: Some(frum_path.into()),
+            ..self
+        }
+    }
+
+    pub fn with_multishells_dir(self, multishells_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            multishells_dir: Some(multishells_dir.into()),
+            ..self
+        }
+    }
+
+    pub fn with_mirror(self, ruby_build_mirror: reqwest::Url) -> Self {
+        Self {
+            ruby_build_mirror,
+            ..self
+        }
+    }
+
+    pub fn with_mirror_index(self, mirror_index: reqwest::Url) -> Self {
+        Self {
+            mirror_index: Some(mirror_index),
+            ..self
+        }
+    }
+
+    pub fn with_log_level(self, log_level: LogLevel) -> Self {
+        Self { log_level, ..self }
+    }
+
+    /// The URL of the list of versions, which is next to the archives unless `--mirror-index` says otherwise.
+    pub fn index_url(&self) -> reqwest::Url {
+        self.mirror_index.clone().unwrap_or_else(|| {
+            reqwest::Url::parse(&format!(
+                "{}/index.txt",
+                self.ruby_build_mirror.as_str().trim_end_matches('/')
+            ))
+            .unwrap()
+        })
+    }
+
     pub fn base_dir(&self) -> std::path::PathBuf {
         ensure_dir_exists((self.base_dir.clone()).unwrap_or_else(|| {
             dirs::home_dir()
@@ -32,7 +136,11 @@ impl FrumConfig {
     }
 
     pub fn versions_dir(&self) -> std::path::PathBuf {
-        ensure_dir_exists(self.base_dir().join("versions"))
+        ensure_dir_exists(
+            self.versions_dir
+                .clone()
+                .unwrap_or_else(|| self.base_dir().join("versions")),
+        )
     }
 
     pub fn default_version_dir(&self) -> std::path::PathBuf {
@@ -40,7 +148,196 @@ impl FrumConfig {
     }
 
     pub fn aliases_dir(&self) -> std::path::PathBuf {
-        ensure_dir_exists(self.base_dir().join("aliases"))
+        ensure_dir_exists(
+            self.aliases_dir
+                .clone()
+                .unwrap_or_else(|| self.base_dir().join("aliases")),
+        )
+    }
+
+    /// The directory that builds and registered versions are staged in, `versions/.downloads`
+    /// unless `FRUM_TMPDIR` moves it, e.g. to a tmpfs or a roomier volume.
+    pub fn temp_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(
+            self.tmpdir
+                .clone()
+                .unwrap_or_else(|| self.versions_dir().join(".downloads")),
+        )
+    }
+
+    /// The downloads and the remote version list, in `$XDG_CACHE_HOME/frum` if it is set.
+    pub fn cache_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(
+            self.cache_dir
+                .clone()
+                .unwrap_or_else(|| self.base_dir().join("cache")),
+        )
+    }
+
+    /// The user's `frum.toml`, in `$XDG_CONFIG_HOME/frum` if it is set. It applies
+    /// wherever a project doesn't have its own.
+    pub fn config_file(&self) -> std::path::PathBuf {
+        self.config_dir
+            .clone()
+            .unwrap_or_else(|| self.base_dir())
+            .join(crate::project_config::PROJECT_CONFIG_FILE)
+    }
+
+    /// The gems to install into every Ruby frum builds, one per line like rbenv-default-gems
+    /// reads them. It is next to `frum.toml`.
+    pub fn default_gems_file(&self) -> std::path::PathBuf {
+        self.config_dir
+            .clone()
+            .unwrap_or_else(|| self.base_dir())
+            .join("default-gems")
+    }
+
+    /// The last global versions selected, for `frum global --rollback`.
+    pub fn global_history_file(&self) -> std::path::PathBuf {
+        self.base_dir().join("global-history")
+    }
+
+    /// The directory holding dependencies built for Ruby, such as OpenSSL.
+    pub fn sidecars_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(self.base_dir().join("sidecars"))
+    }
+
+    /// An empty directory that the session symlink points at for the system Ruby, so that
+    /// `PATH` falls through to it while the current version still reads as `system`.
+    pub fn system_dir(&self) -> std::path::PathBuf {
+        ensure_dir_exists(self.base_dir().join("system"))
+    }
+
+    /// The directory holding one version symlink per shell session created by `frum init`.
+    /// Fails when the directory isn't private to the current user, since anyone able to
+    /// write to it could point a session at a Ruby of their own.
+    pub fn multishells_dir(&self) -> std::io::Result<std::path::PathBuf> {
+        let dir = self
+            .multishells_dir
+            .clone()
+            .unwrap_or_else(|| multishells_dir_in(std::env::var_os("XDG_RUNTIME_DIR")));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            std::fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(&dir)?;
+            check_private_dir(&dir)?;
+        }
+        #[cfg(windows)]
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+}
+
+/// Checks that `dir` is a real directory, owned by the current user and closed to
+/// everyone else, which a directory somebody else created in a shared temp directory isn't.
+#[cfg(unix)]
+fn check_private_dir(dir: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = dir.symlink_metadata()?;
+    let problem = if !metadata.file_type().is_dir() {
+        "isn't a directory"
+    } else if metadata.uid() != unsafe { libc::getuid() } {
+        "is owned by another user"
+    } else if metadata.mode() & 0o077 != 0 {
+        "is accessible to other users"
+    } else {
+        return Ok(());
+    };
+    Err(std::io::Error::new(
+        std::io::ErrorKind::PermissionDenied,
+        format!("{} {}", dir.display(), problem),
+    ))
+}
+
+/// Where the session symlinks of the current user go: the per-user `runtime_dir` when
+/// it is an absolute path, or otherwise a directory of the user in the temp directory,
+/// which other users may share.
+fn multishells_dir_in(runtime_dir: Option<OsString>) -> PathBuf {
+    match runtime_dir.map(PathBuf::from) {
+        Some(runtime_dir) if runtime_dir.is_absolute() => runtime_dir.join("frum_multishells"),
+        _ => std::env::temp_dir().join(user_multishells_dir_name()),
+    }
+}
+
+#[cfg(unix)]
+fn user_multishells_dir_name() -> String {
+    format!("frum_multishells_{}", unsafe { libc::getuid() })
+}
+
+/// The temp directory is already per user on Windows.
+#[cfg(windows)]
+fn user_multishells_dir_name() -> String {
+    "frum_multishells".to_string()
+}
+
+/// Expands a leading `~` and `$VAR` or `${VAR}` references, which are taken
+/// literally when a path comes from a config file or a quoted value instead of the shell.
+/// Undefined variables are kept as they are.
+pub fn expand_path(path: &str) -> PathBuf {
+    let mut expanded = String::new();
+    let mut rest = path;
+    if rest == "~" || rest.starts_with("~/") {
+        if let Some(home_dir) = dirs::home_dir() {
+            expanded.push_str(home_dir.to_str().unwrap());
+            rest = &rest[1..];
+        }
+    }
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let variable = &rest[start + 1..];
+        let (name, len) = match variable.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = variable
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(variable.len());
+                (&variable[..end], end)
+            }
+        };
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[start..start + 1 + len]),
+        }
+        rest = &variable[len..];
+    }
+    expanded.push_str(rest);
+    PathBuf::from(expanded)
+}
+
+/// `$XDG_*_HOME/frum`, as long as the variable is an absolute path as the
+/// XDG Base Directory specification requires.
+fn xdg_dir(home: Option<OsString>) -> Option<PathBuf> {
+    let home = PathBuf::from(home?);
+    if home.is_absolute() {
+        Some(home.join("frum"))
+    } else {
+        None
+    }
+}
+
+/// Whether frum can create files in `dir`, or in the closest existing directory above it
+/// when it doesn't exist yet. A directory nobody may write to counts as read-only even for
+/// root, since it was made so on purpose, e.g. for a shared installation.
+pub fn is_writable(dir: &std::path::Path) -> bool {
+    let dir = match dir.ancestors().find(|dir| dir.is_dir()) {
+        Some(dir) => dir,
+        None => return true,
+    };
+    match dir.metadata() {
+        Ok(metadata) if metadata.permissions().readonly() => false,
+        _ => match tempfile::tempfile_in(dir) {
+            Ok(_) => true,
+            Err(err) => !matches!(
+                err.kind(),
+                std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::ReadOnlyFilesystem
+            ),
+        },
     }
 }
 
@@ -48,3 +345,180 @@ fn ensure_dir_exists<T: AsRef<std::path::Path>>(path: T) -> T {
     std::fs::create_dir_all(path.as_ref()).ok();
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{expand_path, multishells_dir_in, xdg_dir, FrumConfig, LogLevel};
+    use crate::command::Command;
+    use crate::commands::global::Global;
+    use crate::input_version::InputVersion;
+    use crate::version::Version;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_builder_chain() {
+        let base_dir = tempdir().unwrap();
+        let mirror = reqwest::Url::parse("https://mirror.example.com/ruby").unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(base_dir.path())
+            .with_frum_path("/tmp/frum_session")
+            .with_mirror(mirror.clone())
+            .with_log_level(LogLevel::Quiet);
+
+        assert_eq!(config.base_dir(), base_dir.path());
+        assert_eq!(config.versions_dir(), base_dir.path().join("versions"));
+        assert_eq!(
+            config.frum_path.as_deref(),
+            Some(std::path::Path::new("/tmp/frum_session"))
+        );
+        assert_eq!(config.ruby_build_mirror, mirror);
+        assert_eq!(config.log_level, LogLevel::Quiet);
+        assert!(!config.offline);
+    }
+
+    #[test]
+    fn test_expand_path() {
+        let home_dir = dirs::home_dir().unwrap();
+        let home = std::env::var("HOME").unwrap();
+
+        assert_eq!(expand_path("~/rubies"), home_dir.join("rubies"));
+        assert_eq!(expand_path("~"), home_dir);
+        assert_eq!(
+            expand_path("$HOME/rubies"),
+            std::path::Path::new(&home).join("rubies")
+        );
+        assert_eq!(
+            expand_path("${HOME}/rubies"),
+            std::path::Path::new(&home).join("rubies")
+        );
+        assert_eq!(
+            expand_path("/opt/$FRUM_UNDEFINED_VARIABLE/~"),
+            std::path::Path::new("/opt/$FRUM_UNDEFINED_VARIABLE/~")
+        );
+    }
+
+    #[test]
+    fn test_xdg_cache_and_config_dirs() {
+        let base_dir = tempdir().unwrap();
+        let xdg_dir_path = tempdir().unwrap();
+        let config = FrumConfig {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            cache_dir: xdg_dir(Some(xdg_dir_path.path().join("cache").into())),
+            config_dir: xdg_dir(Some(xdg_dir_path.path().join("config").into())),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.cache_dir(),
+            xdg_dir_path.path().join("cache").join("frum")
+        );
+        assert_eq!(
+            config.config_file(),
+            xdg_dir_path
+                .path()
+                .join("config")
+                .join("frum")
+                .join("frum.toml")
+        );
+        assert_eq!(config.versions_dir(), base_dir.path().join("versions"));
+
+        assert_eq!(xdg_dir(Some("relative/cache".into())), None);
+        let config = FrumConfig {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            cache_dir: xdg_dir(None),
+            config_dir: xdg_dir(None),
+            ..Default::default()
+        };
+        assert_eq!(config.cache_dir(), base_dir.path().join("cache"));
+        assert_eq!(config.config_file(), base_dir.path().join("frum.toml"));
+    }
+
+    #[test]
+    fn test_override_versions_dir() {
+        let base_dir = tempdir().unwrap();
+        let versions_dir = tempdir().unwrap();
+        let config = FrumConfig {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            versions_dir: Some(versions_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        std::fs::create_dir_all(versions_dir.path().join("2.6.4")).unwrap();
+
+        Global {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_link(base_dir.path().join("aliases").join("default")).unwrap(),
+            versions_dir.path().join("2.6.4")
+        );
+        assert!(!base_dir.path().join("versions").exists());
+    }
+
+    #[test]
+    fn test_override_aliases_dir() {
+        let base_dir = tempdir().unwrap();
+        let aliases_dir = tempdir().unwrap();
+        let config = FrumConfig {
+            base_dir: Some(base_dir.path().to_path_buf()),
+            aliases_dir: Some(aliases_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        std::fs::create_dir_all(base_dir.path().join("versions").join("2.6.4")).unwrap();
+
+        Global {
+            version: Some(InputVersion::Full(Version::parse("2.6.4").unwrap())),
+            ..Default::default()
+        }
+        .apply(&config)
+        .unwrap();
+
+        assert_eq!(
+            std::fs::read_link(aliases_dir.path().join("default")).unwrap(),
+            base_dir.path().join("versions").join("2.6.4")
+        );
+        assert!(!base_dir.path().join("aliases").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_multishells_dir_is_per_user() {
+        let uid = unsafe { libc::getuid() };
+        assert_eq!(
+            multishells_dir_in(Some("/run/user/1000".into())),
+            std::path::PathBuf::from("/run/user/1000/frum_multishells")
+        );
+        for runtime_dir in &[None, Some("relative".into())] {
+            assert_eq!(
+                multishells_dir_in(runtime_dir.clone()),
+                std::env::temp_dir().join(format!("frum_multishells_{}", uid))
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_multishells_dir_must_be_private() {
+        use std::os::unix::fs::PermissionsExt;
+        let base_dir = tempdir().unwrap();
+        let multishells_dir = base_dir.path().join("multishells");
+        let config = FrumConfig::default().with_multishells_dir(&multishells_dir);
+
+        assert_eq!(config.multishells_dir().unwrap(), multishells_dir);
+        std::fs::set_permissions(&multishells_dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(config.multishells_dir().is_err());
+
+        let real_dir = base_dir.path().join("real");
+        std::fs::create_dir(&real_dir).unwrap();
+        std::fs::set_permissions(&real_dir, std::fs::Permissions::from_mode(0o700)).unwrap();
+        let linked_dir = base_dir.path().join("linked");
+        std::os::unix::fs::symlink(&real_dir, &linked_dir).unwrap();
+        assert!(FrumConfig::default()
+            .with_multishells_dir(&linked_dir)
+            .multishells_dir()
+            .is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::version::Version;
+use chrono::{Duration, NaiveDate};
+
+const EOL_DATES: &str = include_str!("eol.txt");
+
+/// How long before its end of life a series counts as approaching it.
+const APPROACHING_DAYS: i64 = 180;
+
+/// Where the minor series of a version stands relative to its end of life.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EolStatus {
+    Supported { eol: NaiveDate },
+    Approaching { eol: NaiveDate },
+    Past { eol: NaiveDate },
+    Unknown,
+}
+
+impl EolStatus {
+    /// A warning to show when the version is picked, if its series is past or near its end of life.
+    pub fn warning(&self, version: &Version) -> Option<String> {
+        match self {
+            Self::Past { eol } => Some(format!(
+                "Ruby {} reached its end of life on {} and no longer gets security fixes",
+                version, eol
+            )),
+            Self::Approaching { eol } => Some(format!(
+                "Ruby {} reaches its end of life on {}",
+                version, eol
+            )),
+            Self::Supported { .. } | Self::Unknown => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EolStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Supported { eol } => write!(f, "{}", eol),
+            Self::Approaching { eol } => write!(f, "{} (soon)", eol),
+            Self::Past { eol } => write!(f, "{} (EOL)", eol),
+            Self::Unknown => write!(f, "-"),
+        }
+    }
+}
+
+/// The end-of-life date of the minor series of `version`, if it is known.
+pub fn eol_date(version: &Version) -> Option<NaiveDate> {
+    let (major, minor) = version.major_minor()?;
+    let series = format!("{}.{}", major, minor);
+    EOL_DATES
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('\t'))
+        .find(|(line_series, _)| *line_series == series)
+        .and_then(|(_, date)| NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok())
+}
+
+pub fn eol_status(version: &Version) -> EolStatus {
+    eol_status_on(version, chrono::Local::now().naive_local().date())
+}
+
+/// The end-of-life status of `version` as of `today`.
+pub fn eol_status_on(version: &Version, today: NaiveDate) -> EolStatus {
+    match eol_date(version) {
+        Some(eol) if eol <= today => EolStatus::Past { eol },
+        Some(eol) if eol - today <= Duration::days(APPROACHING_DAYS) => {
+            EolStatus::Approaching { eol }
+        }
+        Some(eol) => EolStatus::Supported { eol },
+        None => EolStatus::Unknown,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(version: &str, today: &str) -> EolStatus {
+        eol_status_on(
+            &Version::parse(version).unwrap(),
+            NaiveDate::parse_from_str(today, "%Y-%m-%d").unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_eol_status() {
+        let eol = NaiveDate::from_ymd_opt(2027, 3, 31).unwrap();
+
+        assert_eq!(status("3.3.0", "2026-01-01"), EolStatus::Supported { eol });
+        assert_eq!(
+            status("3.3.5", "2026-12-01"),
+            EolStatus::Approaching { eol }
+        );
+        assert_eq!(status("3.3.0", "2027-03-31"), EolStatus::Past { eol });
+        assert_eq!(status("4.9.0", "2026-01-01"), EolStatus::Unknown);
+        assert_eq!(status("system", "2026-01-01"), EolStatus::Unknown);
+    }
+
+    #[test]
+    fn test_warning() {
+        let version = Version::parse("2.6.10").unwrap();
+
+        assert!(status("2.6.10", "2026-10-14")
+            .warning(&version)
+            .unwrap()
+            .contains("2022-04-12"));
+        assert_eq!(status("3.4.0", "2026-10-14").warning(&version), None);
+    }
+}
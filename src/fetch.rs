@@ -0,0 +1,211 @@
+use crate::command::ErrorKind;
+use log::debug;
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::{StatusCode, Url};
+use std::io::Read;
+use std::time::Duration;
+use thiserror::Error;
+
+/// How many times a conditional request is sent before giving up on a flaky server.
+const ATTEMPTS: u32 = 3;
+/// How long to wait before the first retry. Each retry waits twice as long as the previous one.
+const BACKOFF: Duration = Duration::from_millis(500);
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    #[error(transparent)]
+    HttpError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't read {url} as a local path")]
+    InvalidFileUrl { url: Url },
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::InvalidFileUrl { .. } => "InvalidFileUrl",
+        }
+    }
+}
+
+/// The body of a fetched URL and its size, if known.
+pub struct Fetched {
+    pub body: Box<dyn Read + Send>,
+    pub content_length: Option<u64>,
+}
+
+/// What a server sent to identify a response, so that it can later be asked whether it changed.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Reads validators stored as `ETag: ...` and `Last-Modified: ...` lines.
+    pub fn parse(value: &str) -> Self {
+        let mut validators = Self::default();
+        for line in value.lines() {
+            match line.split_once(": ") {
+                Some(("ETag", etag)) => validators.etag = Some(etag.to_string()),
+                Some(("Last-Modified", date)) => validators.last_modified = Some(date.to_string()),
+                _ => {}
+            }
+        }
+        validators
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+impl std::fmt::Display for Validators {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(etag) = &self.etag {
+            writeln!(f, "ETag: {}", etag)?;
+        }
+        if let Some(date) = &self.last_modified {
+            writeln!(f, "Last-Modified: {}", date)?;
+        }
+        Ok(())
+    }
+}
+
+/// The outcome of a conditional fetch.
+pub enum Conditional {
+    Modified {
+        fetched: Fetched,
+        validators: Validators,
+    },
+    NotModified,
+    NotFound,
+}
+
+/// Fetches `url` unless it is unchanged since the response `validators` came from,
+/// retrying with backoff when the server can't be reached or fails.
+/// `file://` URLs are always read again.
+pub fn fetch_if_changed(url: &Url, validators: &Validators) -> Result<Conditional, Error> {
+    if url.scheme() == "file" {
+        return Ok(match fetch(url)? {
+            Some(fetched) => Conditional::Modified {
+                fetched,
+                validators: Validators::default(),
+            },
+            None => Conditional::NotFound,
+        });
+    }
+
+    let client = reqwest::blocking::Client::new();
+    let response = send_with_retry(|| {
+        let mut request = client.get(url.clone());
+        if let Some(etag) = &validators.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(date) = &validators.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, date);
+        }
+        request
+    })?;
+    match response.status() {
+        StatusCode::NOT_MODIFIED => return Ok(Conditional::NotModified),
+        StatusCode::NOT_FOUND => return Ok(Conditional::NotFound),
+        _ => {}
+    }
+    let response = response.error_for_status()?;
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+    let validators = Validators {
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+    };
+    Ok(Conditional::Modified {
+        validators,
+        fetched: Fetched {
+            content_length: response.content_length(),
+            body: Box::new(response),
+        },
+    })
+}
+
+fn send_with_retry(
+    request: impl Fn() -> reqwest::blocking::RequestBuilder,
+) -> reqwest::Result<reqwest::blocking::Response> {
+    let mut attempt = 1;
+    loop {
+        match request().send() {
+            Ok(response) if !response.status().is_server_error() || attempt == ATTEMPTS => {
+                return Ok(response)
+            }
+            Err(err) if !(err.is_connect() || err.is_timeout()) || attempt == ATTEMPTS => {
+                return Err(err)
+            }
+            Ok(response) => debug!("Retry after {}", response.status()),
+            Err(err) => debug!("Retry after {}", err),
+        }
+        std::thread::sleep(BACKOFF * 2u32.pow(attempt - 1));
+        attempt += 1;
+    }
+}
+
+/// Fetches `url`, reading `file://` URLs from the filesystem so that a mirror
+/// can be a local directory. Returns `None` when nothing exists at `url`.
+pub fn fetch(url: &Url) -> Result<Option<Fetched>, Error> {
+    if url.scheme() == "file" {
+        let path = url
+            .to_file_path()
+            .map_err(|_| Error::InvalidFileUrl { url: url.clone() })?;
+        debug!("Read {}", path.display());
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::IoError(err)),
+        };
+        return Ok(Some(Fetched {
+            content_length: Some(file.metadata()?.len()),
+            body: Box::new(file),
+        }));
+    }
+
+    let response = reqwest::blocking::get(url.clone())?;
+    if response.status() == 404 {
+        return Ok(None);
+    }
+    let response = response.error_for_status()?;
+    Ok(Some(Fetched {
+        content_length: response.content_length(),
+        body: Box::new(response),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_fetch_file_url() {
+        let mirror_dir = tempdir().unwrap();
+        std::fs::write(mirror_dir.path().join("index.txt"), "ruby-3.0.0").unwrap();
+        let mirror = Url::from_directory_path(mirror_dir.path()).unwrap();
+
+        let mut fetched = fetch(&mirror.join("index.txt").unwrap()).unwrap().unwrap();
+        let mut body = String::new();
+        fetched.body.read_to_string(&mut body).unwrap();
+
+        assert_eq!(body, "ruby-3.0.0");
+        assert_eq!(fetched.content_length, Some(10));
+        assert!(fetch(&mirror.join("missing.txt").unwrap())
+            .unwrap()
+            .is_none());
+    }
+}
@@ -1,11 +1,17 @@
 use crate::version::Version;
 use std::str::FromStr;
 
-#[derive(Clone, Debug)]
+/// A version given by the user, which may leave out the minor or patch number.
+#[derive(Clone, Debug, PartialEq)]
 pub enum InputVersion {
     Major(u64),
     MajorMinor(u64, u64),
     Full(Version),
+    /// The newest release, given as `latest`.
+    Latest,
+    /// A requirement written like in a Gemfile, such as `~> 3.1` or `>= 3.0, < 3.3`,
+    /// kept with how it was written.
+    Requirement(String, semver::VersionReq),
 }
 
 impl InputVersion {
@@ -22,11 +28,15 @@ impl InputVersion {
     pub fn matches(&self, version: &Version) -> bool {
         match (self, version) {
             (Self::Full(a), b) => a == b,
-            (_, Version::System) => false,
+            (_, Version::Dev(_)) | (_, Version::System) => false,
+            (Self::Latest, Version::Semver(_)) => true,
             (Self::Major(major), Version::Semver(other)) => *major == other.major,
             (Self::MajorMinor(major, minor), Version::Semver(other)) => {
                 *major == other.major && *minor == other.minor
             }
+            (Self::Requirement(_, requirement), Version::Semver(other)) => {
+                requirement.matches(other)
+            }
         }
     }
 }
@@ -37,28 +47,153 @@ impl std::fmt::Display for InputVersion {
             Self::Full(x) => x.fmt(f),
             Self::Major(major) => write!(f, "{}.x.x", major),
             Self::MajorMinor(major, minor) => write!(f, "{}.{}.x", major, minor),
+            Self::Latest => write!(f, "latest"),
+            Self::Requirement(requirement, _) => f.write_str(requirement),
         }
     }
 }
 
+/// Parses full versions as well as partial ones such as `3`, `3.1` or `3.1.x` and
+/// `latest`, so that what `Display` prints parses back to the same version.
 impl FromStr for InputVersion {
     type Err = semver::SemVerError;
     fn from_str(s: &str) -> Result<InputVersion, Self::Err> {
-        match Version::parse(s) {
+        if s.trim() == "latest" {
+            return Ok(Self::Latest);
+        }
+        if s.trim().starts_with(['~', '>', '<', '=']) {
+            if let Some(requirement) = parse_requirement(s.trim()) {
+                return Ok(Self::Requirement(s.trim().to_string(), requirement));
+            }
+        }
+        match Version::parse(s.trim()) {
             Ok(v) => Ok(Self::Full(v)),
             Err(e) => {
-                let mut parts = s.trim().split('.');
-                match (next_of::<u64, _>(&mut parts), next_of::<u64, _>(&mut parts)) {
-                    (Some(major), None) => Ok(Self::Major(major)),
-                    (Some(major), Some(minor)) => Ok(Self::MajorMinor(major, minor)),
-                    _ => Err(e),
-                }
+                let parts = s.trim().split('.').collect::<Vec<_>>();
+                let number = |part: &str| part.parse::<u64>().ok();
+                let partial = match parts.as_slice() {
+                    [major] | [major, "x"] | [major, "x", "x"] => number(major).map(Self::Major),
+                    [major, minor] | [major, minor, "x"] => number(major)
+                        .zip(number(minor))
+                        .map(|(major, minor)| Self::MajorMinor(major, minor)),
+                    _ => None,
+                };
+                partial.ok_or(e)
             }
         }
     }
 }
 
-fn next_of<'a, T: FromStr, It: Iterator<Item = &'a str>>(i: &mut It) -> Option<T> {
-    let x = i.next()?;
-    T::from_str(x).ok()
+/// Parses a requirement written like in a Gemfile, such as `~> 3.1`, where `~> 3.1`
+/// means `>= 3.1, < 4` and `~> 3.1.2` means `>= 3.1.2, < 3.2`.
+fn parse_requirement(requirement: &str) -> Option<semver::VersionReq> {
+    let mut comparators = vec![];
+    for comparator in requirement.split(',') {
+        let comparator = comparator.trim();
+        let operator_len = comparator
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(comparator.len());
+        let (operator, version) = comparator.split_at(operator_len);
+        let numbers = version
+            .split('.')
+            .map(|number| number.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        if numbers.len() > 3 {
+            return None;
+        }
+        let full = |numbers: &[u64]| {
+            let mut numbers = numbers.to_vec();
+            numbers.resize(3, 0);
+            format!("{}.{}.{}", numbers[0], numbers[1], numbers[2])
+        };
+        match operator.trim() {
+            "~>" => {
+                // The last number may go up, so the one before it is bumped for the upper bound
+                let mut upper = numbers[..numbers.len().max(2) - 1].to_vec();
+                *upper.last_mut()? += 1;
+                comparators.push(format!(">={}", full(&numbers)));
+                comparators.push(format!("<{}", full(&upper)));
+            }
+            operator @ (">=" | ">" | "<=" | "<" | "=") => {
+                comparators.push(format!("{}{}", operator, full(&numbers)))
+            }
+            _ => return None,
+        }
+    }
+    semver::VersionReq::parse(&comparators.join(", ")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_round_trips() {
+        for version in &[
+            InputVersion::Major(3),
+            InputVersion::MajorMinor(3, 1),
+            InputVersion::Full(Version::parse("3.1.2").unwrap()),
+            InputVersion::Full(Version::parse("3.0.0-preview1").unwrap()),
+            InputVersion::Full(Version::Dev("1a2b3c".to_string())),
+            InputVersion::Full(Version::System),
+            InputVersion::Latest,
+        ] {
+            assert_eq!(
+                &InputVersion::from_str(&version.to_string()).unwrap(),
+                version
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_partial_versions() {
+        assert_eq!(InputVersion::from_str("3"), Ok(InputVersion::Major(3)));
+        assert_eq!(
+            InputVersion::from_str(" 3.1 "),
+            Ok(InputVersion::MajorMinor(3, 1))
+        );
+        assert_eq!(
+            InputVersion::from_str("3.1.x"),
+            Ok(InputVersion::MajorMinor(3, 1))
+        );
+        assert!(InputVersion::from_str("3.1.2.4").is_err());
+        assert!(InputVersion::from_str("3.one").is_err());
+        assert_eq!(InputVersion::from_str("latest"), Ok(InputVersion::Latest));
+        assert!(InputVersion::from_str("newest").is_err());
+    }
+
+    #[test]
+    fn test_requirements() {
+        let versions = [
+            "2.7.8",
+            "3.0.6",
+            "3.1.2",
+            "3.1.4",
+            "3.2.2",
+            "3.3.0-preview1",
+            "4.0.0",
+        ]
+        .iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect::<Vec<_>>();
+        let best_match = |requirement: &str| {
+            InputVersion::from_str(requirement)
+                .unwrap()
+                .to_version(&versions)
+                .map(|version| version.to_string())
+        };
+
+        assert_eq!(best_match("~> 3.1"), Some("3.2.2".to_string()));
+        assert_eq!(best_match("~> 3.1.2"), Some("3.1.4".to_string()));
+        assert_eq!(best_match("~>3"), Some("3.2.2".to_string()));
+        assert_eq!(best_match(">= 3.0, < 3.2"), Some("3.1.4".to_string()));
+        assert_eq!(best_match("= 2.7.8"), Some("2.7.8".to_string()));
+        assert_eq!(best_match("~> 4.1"), None);
+        assert_eq!(
+            InputVersion::from_str(" ~> 3.1 ").unwrap().to_string(),
+            "~> 3.1"
+        );
+        assert!(InputVersion::from_str("~> 3.x").is_err());
+        assert!(InputVersion::from_str("!= 3.1").is_err());
+    }
 }
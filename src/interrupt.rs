@@ -0,0 +1,67 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+static GUARDS: AtomicUsize = AtomicUsize::new(0);
+
+/// The exit code of a process stopped by Ctrl-C.
+pub const EXIT_CODE: i32 = 130;
+
+/// Records SIGINT and SIGTERM instead of dying until it is dropped, so that
+/// an installation can stop its build and clean up after itself.
+pub struct InterruptGuard {
+    _private: (),
+}
+
+/// Starts recording interrupts. Nested guards share the same handler, which
+/// is removed when the last one is dropped.
+pub fn catch() -> InterruptGuard {
+    if GUARDS.fetch_add(1, Ordering::SeqCst) == 0 {
+        INTERRUPTED.store(false, Ordering::SeqCst);
+        set_handlers(true);
+    }
+    InterruptGuard { _private: () }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        if GUARDS.fetch_sub(1, Ordering::SeqCst) == 1 {
+            set_handlers(false);
+        }
+    }
+}
+
+/// The flag set when an interrupt arrives while a guard is alive.
+pub fn flag() -> &'static AtomicBool {
+    &INTERRUPTED
+}
+
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+#[cfg(unix)]
+extern "C" fn record_interrupt(_: libc::c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// The handlers in place before the first guard, restored after the last one.
+#[cfg(unix)]
+static PREVIOUS_HANDLERS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+#[cfg(unix)]
+fn set_handlers(catch: bool) {
+    for (signal, previous) in [libc::SIGINT, libc::SIGTERM]
+        .iter()
+        .zip(PREVIOUS_HANDLERS.iter())
+    {
+        if catch {
+            let handler = record_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            previous.store(unsafe { libc::signal(*signal, handler) }, Ordering::SeqCst);
+        } else {
+            unsafe { libc::signal(*signal, previous.load(Ordering::SeqCst)) };
+        }
+    }
+}
+
+#[cfg(windows)]
+fn set_handlers(_catch: bool) {}
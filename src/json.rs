@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+/// The version of the JSON frum prints, given as `schema` in every JSON output.
+///
+/// It is bumped when a field is renamed or removed or changes its meaning, so that
+/// editors and other tools can tell whether they understand the output. New fields
+/// don't bump it, so readers should ignore the fields they don't know.
+pub const SCHEMA: u32 = 1;
+
+/// A JSON output: the fields of `T` next to `schema`.
+///
+/// `T` has to serialize to an object, so lists are printed under a field of their own.
+#[derive(Serialize, Debug)]
+pub struct JsonEnvelope<T> {
+    schema: u32,
+    #[serde(flatten)]
+    data: T,
+}
+
+impl<T: Serialize> JsonEnvelope<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            schema: SCHEMA,
+            data,
+        }
+    }
+
+    pub fn to_string_pretty(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl<T: Serialize> std::fmt::Display for JsonEnvelope<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&serde_json::to_string(self).map_err(|_| std::fmt::Error)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Output {
+        version: &'static str,
+    }
+
+    #[test]
+    fn test_envelope_has_current_schema() {
+        let json: serde_json::Value =
+            serde_json::from_str(&JsonEnvelope::new(Output { version: "3.2.2" }).to_string())
+                .unwrap();
+
+        assert_eq!(json["schema"], SCHEMA);
+        assert_eq!(json["schema"], 1);
+        assert_eq!(json["version"], "3.2.2");
+    }
+}
@@ -1,17 +1,17 @@
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone, Default)]
 pub enum LogLevel {
     Quiet,
     Error,
+    #[default]
     Info,
 }
 
-impl Default for LogLevel {
-    fn default() -> Self {
-        Self::Info
-    }
-}
-
 impl LogLevel {
+    /// The names `--log-level` accepts, from the quietest.
+    pub const NAMES: &'static [&'static str] = &["quiet", "error", "info"];
+
     pub fn is_writable(&self, level: &Self) -> bool {
         use std::cmp::Ordering;
         matches!(self.cmp(level), Ordering::Greater | Ordering::Equal)
@@ -27,6 +27,61 @@ impl LogLevel {
             Box::from(std::io::sink())
         }
     }
+
+    /// Like `write`, but also appends everything written to `log_file` with a
+    /// timestamp, including what the level keeps off the console.
+    pub fn write_logged(&self, level: &Self, log_file: Option<&Path>) -> Box<dyn std::io::Write> {
+        match log_file {
+            Some(log_file) => Box::from(LoggedWriter {
+                console: self.write(level),
+                log_file: log_file.to_path_buf(),
+                level: level.clone(),
+                written: vec![],
+            }),
+            None => self.write(level),
+        }
+    }
+}
+
+/// Writes to the console and keeps what was written to append it to the log file once done.
+struct LoggedWriter {
+    console: Box<dyn std::io::Write>,
+    log_file: PathBuf,
+    level: LogLevel,
+    written: Vec<u8>,
+}
+
+impl std::io::Write for LoggedWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.console.write_all(buf)?;
+        self.written.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.console.flush()
+    }
+}
+
+impl Drop for LoggedWriter {
+    fn drop(&mut self) {
+        let timestamp = chrono::Local::now().format("%Y-%m-%dT%H:%M:%S%.3f");
+        let level: &str = self.level.clone().into();
+        let escape_codes = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+        let written = String::from_utf8_lossy(&self.written);
+        let lines = escape_codes
+            .replace_all(&written, "")
+            .lines()
+            .map(|line| format!("{} {:<5} {}\n", timestamp, level, line))
+            .collect::<String>();
+        // Logging must not get in the way of the command itself
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.log_file)
+            .and_then(|mut file| std::io::Write::write_all(&mut file, lines.as_bytes()))
+            .ok();
+    }
 }
 
 impl From<LogLevel> for &'static str {
@@ -45,17 +100,103 @@ impl std::str::FromStr for LogLevel {
     fn from_str(s: &str) -> Result<LogLevel, Self::Err> {
         match s {
             "quiet" => Ok(Self::Quiet),
-            "info" | "all" => Ok(Self::Info),
+            "info" => Ok(Self::Info),
             "error" => Ok(Self::Error),
-            level => Err(format!("I don't know the log level of {:?}", level)),
+            level => Err(format!(
+                "I don't know the log level of {:?}, expected one of: {}",
+                level,
+                Self::NAMES.join(", ")
+            )),
         }
     }
 }
 
+/// Whether to color the output. `--no-color` wins over everything, then the
+/// `NO_COLOR` and `CLICOLOR_FORCE` conventions. Otherwise output is colored only
+/// when both stdout and stderr are terminals, so that nothing piped gets escape codes.
+pub fn color_enabled(
+    no_color: bool,
+    env: impl Fn(&str) -> Option<String>,
+    terminals: bool,
+) -> bool {
+    if no_color || env("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+    match env("CLICOLOR_FORCE") {
+        Some(force) if force != "0" => true,
+        _ => terminals,
+    }
+}
+
 #[macro_export]
 macro_rules! outln {
     ($config:ident#$level:path, $($expr:expr),+) => {{
         use $crate::log::LogLevel::*;
-        writeln!($config.log_level.write(&$level), $($expr),+).expect("Can't write output");
+        writeln!($config.log_level.write_logged(&$level, $config.log_file.as_deref()), $($expr),+).expect("Can't write output");
     }}
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_cli_accepts_the_same_log_levels() {
+        for name in LogLevel::NAMES {
+            let log_level = LogLevel::from_str(name).unwrap();
+            assert_eq!(<&str>::from(log_level), *name);
+            assert!(crate::cli::build_cli()
+                .get_matches_from_safe(vec!["frum", "--log-level", name, "versions"])
+                .is_ok());
+        }
+
+        let err = crate::cli::build_cli()
+            .get_matches_from_safe(vec!["frum", "--log-level", "verbose", "versions"])
+            .unwrap_err();
+        assert_eq!(err.kind, clap::ErrorKind::InvalidValue);
+        assert!(err
+            .message
+            .contains("[possible values: error, info, quiet]"));
+        assert!(LogLevel::from_str("verbose").is_err());
+    }
+
+    #[test]
+    fn test_log_file_gets_output_hidden_by_level() {
+        let log_dir = tempfile::tempdir().unwrap();
+        let log_file = log_dir.path().join("frum.log");
+        let config = crate::config::FrumConfig {
+            log_level: LogLevel::Quiet,
+            log_file: Some(log_file.clone()),
+            ..Default::default()
+        };
+
+        crate::outln!(config#Info, "{} Installing {}", "==>", colored::Colorize::green("3.2.2"));
+        crate::outln!(config#Error, "error: multi\nline");
+
+        let logged = std::fs::read_to_string(&log_file).unwrap();
+        let lines = logged.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].ends_with(" info  ==> Installing 3.2.2"));
+        assert!(lines[1].ends_with(" error error: multi"));
+        assert!(lines[2].ends_with(" error line"));
+        assert!(lines[0].starts_with(|c: char| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_color_only_on_terminals_by_default() {
+        let no_env = |_: &str| None;
+        let forced = |name: &str| (name == "CLICOLOR_FORCE").then(|| "1".to_string());
+
+        assert!(color_enabled(false, no_env, true));
+        assert!(!color_enabled(false, no_env, false));
+        assert!(!color_enabled(true, no_env, true));
+        assert!(color_enabled(false, forced, false));
+        assert!(!color_enabled(true, forced, false));
+        assert!(!color_enabled(
+            false,
+            |name| (name == "NO_COLOR").then(|| "1".to_string()),
+            true
+        ));
+    }
+}
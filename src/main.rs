@@ -1,13 +1,23 @@
 mod alias;
 mod archive;
+mod checkpoint;
+mod checksums;
 mod cli;
 mod command;
 mod commands;
 mod config;
+mod eol;
+mod fetch;
 mod input_version;
+mod interrupt;
+mod json;
+mod mirror;
+mod project_config;
 mod remote_ruby_index;
 mod shell;
+mod sidecar;
 mod symlink;
+mod timings;
 mod version;
 mod version_file;
 
@@ -21,67 +31,292 @@ fn main() {
     env_logger::init();
     let matches = cli::build_cli().get_matches();
 
+    colored::control::set_override(log::color_enabled(
+        matches.is_present("no-color"),
+        |name| std::env::var(name).ok(),
+        atty::is(atty::Stream::Stdout) && atty::is(atty::Stream::Stderr),
+    ));
+
     let mut config = config::FrumConfig::default();
     if let Some(log_level) = matches.value_of("log-level") {
         config.log_level = log::LogLevel::from_str(log_level).expect("invalid log level")
     }
-    if let Some(ruby_build_mirror) = matches.value_of("ruby-build-mirror") {
-        config.ruby_build_mirror =
-            reqwest::Url::parse(ruby_build_mirror).expect("invalid ruby build mirror")
+    if let Some(mirrors) = matches.values_of("ruby-build-mirror") {
+        let mut mirrors = mirrors
+            .map(|mirror| reqwest::Url::parse(mirror).expect("invalid ruby build mirror"))
+            .collect::<Vec<_>>();
+        config.ruby_build_mirror = mirrors.remove(0);
+        config.alternative_mirrors = mirrors;
+    }
+    if let Some(mirror_index) = matches.value_of("mirror-index") {
+        config.mirror_index = Some(reqwest::Url::parse(mirror_index).expect("invalid mirror index"))
+    };
+    config.log_file = matches.value_of("log-file").map(config::expand_path);
+    config.offline = matches.is_present("offline");
+    config.timings = matches.is_present("timings");
+    config.json_errors = matches.is_present("json");
+    if let Some(shell) = matches.value_of("assume-shell") {
+        config.assume_shell = Some(shell.to_string())
     };
     if let Some(base_dir) = matches.value_of("base-dir") {
-        config.base_dir = Some(std::path::PathBuf::from(base_dir))
+        config.base_dir = Some(config::expand_path(base_dir))
     };
     match matches.subcommand() {
-        ("init", _) => commands::init::Init {}.call(&config),
-        ("versions", _) => commands::versions::Versions {}.call(&config),
+        ("init", Some(sub_matches)) => commands::init::Init {
+            path_only: sub_matches.is_present("path-only"),
+        }
+        .call(&config),
+        ("versions", Some(sub_matches)) => commands::versions::Versions {
+            outdated: sub_matches.is_present("outdated"),
+            stable_only: sub_matches.is_present("stable-only"),
+            format: match sub_matches.value_of("format") {
+                Some("table") => commands::versions::VersionsFormat::Table,
+                _ => commands::versions::VersionsFormat::List,
+            },
+            current_only: sub_matches.is_present("current-only"),
+            sizes: sub_matches.is_present("sizes"),
+            sort: match sub_matches.value_of("sort") {
+                Some("size") => commands::versions::VersionsSort::Size,
+                _ => commands::versions::VersionsSort::Version,
+            },
+            prefix: sub_matches.value_of("prefix").map(config::expand_path),
+            installed_by: match sub_matches.value_of("installed-by") {
+                Some("source") => Some(commands::install::InstalledBy::Source),
+                Some("prebuilt") => Some(commands::install::InstalledBy::Prebuilt),
+                Some("imported") => Some(commands::install::InstalledBy::Imported),
+                _ => None,
+            },
+            count: sub_matches.is_present("count"),
+            remote: sub_matches.is_present("remote"),
+            json: sub_matches.is_present("json"),
+        }
+        .call(&config),
         ("global", Some(sub_matches)) => commands::global::Global {
-            version: input_version::InputVersion::from_str(
-                sub_matches.value_of("version").unwrap(),
-            )
-            .expect("invalid version"),
+            version: version_of(sub_matches),
+            unset: sub_matches.is_present("unset"),
+            rollback: sub_matches.is_present("rollback"),
         }
         .call(&config),
         ("local", Some(sub_matches)) => commands::local::Local {
-            version: sub_matches.value_of("version").map(|version| {
-                input_version::InputVersion::from_str(version).expect("invalid version")
-            }),
+            version: version_of(sub_matches),
+            install_if_missing: sub_matches.is_present("install-if-missing"),
+            yes: sub_matches.is_present("yes"),
+            quiet: sub_matches.is_present("quiet"),
+            print: sub_matches.is_present("print"),
         }
         .call(&config),
         ("install", Some(sub_matches)) => {
             if sub_matches.is_present("list") {
-                commands::install_list::InstallList {}.call(&config);
+                commands::install_list::InstallList {
+                    include_prereleases: sub_matches.is_present("include-prereleases"),
+                    channel: channel_of(sub_matches),
+                    major: sub_matches.value_of("major").map(|major| {
+                        input_version::InputVersion::Major(
+                            major.parse().expect("invalid major version"),
+                        )
+                    }),
+                    minor: sub_matches.value_of("minor").map(|minor| {
+                        input_version::InputVersion::from_str(minor).expect("invalid version")
+                    }),
+                    filter: sub_matches.value_of("filter").map(String::from),
+                    limit: sub_matches
+                        .value_of("limit")
+                        .map(|limit| limit.parse().expect("invalid limit")),
+                    json: sub_matches.is_present("json"),
+                }
+                .call(&config);
                 return;
             }
-            commands::install::Install {
-                version: sub_matches.value_of("version").map(|version| {
-                    input_version::InputVersion::from_str(version).expect("invalid version")
-                }),
-                configure_opts: match sub_matches.values_of("configure_opts") {
-                    Some(opts) => opts.map(|opt| opt.to_string()).collect(),
-                    None => Vec::new(),
-                },
+            let configure_opts: Vec<String> = match sub_matches.values_of("configure_opts") {
+                Some(opts) => opts.map(|opt| opt.to_string()).collect(),
+                None => Vec::new(),
+            };
+            let extra_versions = sub_matches
+                .values_of("also")
+                .map(|versions| {
+                    versions
+                        .map(|version| {
+                            input_version::InputVersion::from_str(version).expect("invalid version")
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let install = commands::install::Install {
+                version: version_of(sub_matches),
+                configure_opts,
+                verify_signature: sub_matches.is_present("verify-signature"),
+                signature_key: sub_matches
+                    .value_of("signature-key")
+                    .map(std::path::PathBuf::from),
+                checksum_file: sub_matches
+                    .value_of("checksum-file")
+                    .map(config::expand_path),
+                dry_run: sub_matches.is_present("dry-run"),
+                print_url: sub_matches.is_present("print-url"),
+                only_download: sub_matches.is_present("only-download"),
+                source: sub_matches.value_of("source").map(String::from),
+                prefix_strip: sub_matches
+                    .value_of("prefix-strip")
+                    .map(|components| components.parse().expect("invalid prefix-strip")),
+                patches: sub_matches
+                    .values_of("patch")
+                    .map(|patches| patches.map(String::from).collect())
+                    .unwrap_or_default(),
+                prebuilt: sub_matches.value_of("from").map(std::path::PathBuf::from),
+                resume: sub_matches.is_present("resume"),
+                json: sub_matches.is_present("json"),
+                openssl_version: sub_matches.value_of("openssl-version").map(String::from),
+                dependencies_only: sub_matches.is_present("dependencies-only"),
+                post_install: sub_matches
+                    .values_of("post-install")
+                    .map(|commands| commands.map(String::from).collect())
+                    .unwrap_or_default(),
+                include_prereleases: sub_matches.is_present("include-prereleases"),
+                channel: channel_of(sub_matches),
+                force: sub_matches.is_present("force"),
+                batch_jobs: None,
+                quiet_build: sub_matches.is_present("quiet-build"),
+                use_ccache: sub_matches.is_present("use-ccache"),
+                tmpdir: sub_matches.value_of("tmpdir").map(config::expand_path),
+                verify_build: sub_matches.is_present("verify-build"),
+            };
+            if extra_versions.is_empty() {
+                install.call(&config);
+                return;
+            }
+            commands::install::BatchInstall {
+                installs: std::iter::once(install.version.clone())
+                    .chain(extra_versions.into_iter().map(Some))
+                    .map(|version| commands::install::Install {
+                        version,
+                        ..install.clone()
+                    })
+                    .collect(),
+                parallel: sub_matches
+                    .value_of("parallel")
+                    .map(|parallel| parallel.parse().expect("invalid parallel"))
+                    .unwrap_or(1),
             }
             .call(&config);
         }
         ("uninstall", Some(sub_matches)) => {
             commands::uninstall::Uninstall {
-                version: input_version::InputVersion::from_str(
-                    sub_matches.value_of("version").unwrap(),
+                version: version_of(sub_matches).unwrap(),
+                prune_cache: sub_matches.is_present("prune-cache"),
+            }
+            .call(&config);
+        }
+        ("shell", Some(sub_matches)) => commands::shell::Shell {
+            version: version_of(sub_matches).unwrap(),
+            preserve_env: sub_matches.is_present("preserve-env"),
+        }
+        .call(&config),
+        ("which", Some(sub_matches)) => commands::which::Which {
+            binary: sub_matches.value_of("binary").unwrap().to_string(),
+            all: sub_matches.is_present("all"),
+            json: sub_matches.is_present("json"),
+            gem: sub_matches.is_present("gem"),
+            resolve_symlinks: sub_matches.is_present("resolve-symlinks"),
+        }
+        .call(&config),
+        ("bundle", Some(sub_matches)) => commands::bundle::Bundle {
+            args: sub_matches
+                .values_of("args")
+                .map(|args| args.map(String::from).collect())
+                .unwrap_or_default(),
+            auto_install: sub_matches.is_present("auto-install"),
+        }
+        .call(&config),
+        ("migrate", Some(sub_matches)) => commands::migrate::Migrate {
+            from: match sub_matches.value_of("from") {
+                Some("rvm") => commands::migrate::VersionManager::Rvm,
+                Some("asdf") => commands::migrate::VersionManager::Asdf,
+                _ => commands::migrate::VersionManager::Rbenv,
+            },
+            root: sub_matches.value_of("root").map(std::path::PathBuf::from),
+            dry_run: sub_matches.is_present("dry-run"),
+        }
+        .call(&config),
+        ("export", Some(sub_matches)) => commands::export::Export {
+            file: sub_matches.value_of("file").map(std::path::PathBuf::from),
+        }
+        .call(&config),
+        ("import", Some(sub_matches)) => commands::import::Import {
+            file: std::path::PathBuf::from(sub_matches.value_of("file").unwrap()),
+            dry_run: sub_matches.is_present("dry-run"),
+        }
+        .call(&config),
+        ("repair", _) => commands::repair::Repair {}.call(&config),
+        ("prune", Some(sub_matches)) => commands::prune::Prune {
+            dangling: sub_matches.is_present("dangling"),
+        }
+        .call(&config),
+        ("reinstall", Some(sub_matches)) => commands::reinstall::Reinstall {
+            all: sub_matches.is_present("all"),
+            only_source: sub_matches.is_present("only-source"),
+        }
+        .call(&config),
+        ("alias", Some(sub_matches)) => commands::alias::Alias {
+            resolve: sub_matches.value_of("resolve").map(String::from),
+            rename: sub_matches.values_of("rename").map(|mut names| {
+                (
+                    names.next().unwrap().to_string(),
+                    names.next().unwrap().to_string(),
                 )
-                .expect("invalid version"),
+            }),
+            force: sub_matches.is_present("force"),
+        }
+        .call(&config),
+        ("upgrade", Some(sub_matches)) => {
+            commands::upgrade::Upgrade {
+                version: version_of(sub_matches).unwrap(),
+                repin: !sub_matches.is_present("no-repin"),
+                remove_old: sub_matches.is_present("remove-old"),
             }
             .call(&config);
         }
+        ("cache", Some(sub_matches)) => commands::cache::Cache {
+            action: match sub_matches.subcommand() {
+                ("clear", Some(clear_matches)) => commands::cache::CacheAction::Clear {
+                    older_than: clear_matches.value_of("older-than").map(|older_than| {
+                        commands::cache::parse_duration(older_than).expect("invalid duration")
+                    }),
+                },
+                _ => commands::cache::CacheAction::List,
+            },
+        }
+        .call(&config),
         ("completions", Some(sub_matches)) => {
             commands::completions::Completions {
                 shell: sub_matches
                     .value_of("shell")
-                    .map(|shell| clap::Shell::from_str(shell).expect("invalid shell")),
+                    .and_then(commands::completions::shell_arg),
                 list: sub_matches.is_present("list"),
+                starts_with: sub_matches.value_of("starts-with").map(String::from),
+                check: sub_matches.is_present("check"),
+                output_dir: sub_matches
+                    .value_of("output-dir")
+                    .map(std::path::PathBuf::from),
+                dynamic_off: sub_matches.is_present("dynamic-off"),
+                shell_version: sub_matches.value_of("shell-version").map(String::from),
             }
             .call(&config);
         }
         _ => (),
     };
 }
+
+/// The `--channel` of `install`, which clap has checked against `Channel::NAMES`.
+fn channel_of(sub_matches: &clap::ArgMatches) -> Option<version::Channel> {
+    sub_matches
+        .value_of("channel")
+        .map(|channel| channel.parse().expect("invalid channel"))
+}
+
+/// The version argument of a subcommand. clap has made sure that it is present
+/// unless the command supports inferring the version.
+fn version_of(sub_matches: &clap::ArgMatches) -> Option<input_version::InputVersion> {
+    sub_matches
+        .value_of("version")
+        .map(|version| input_version::InputVersion::from_str(version).expect("invalid version"))
+}
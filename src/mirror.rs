@@ -0,0 +1,140 @@
+use crate::commands::install::package_url;
+use crate::config::FrumConfig;
+use crate::version::Version;
+use log::debug;
+use reqwest::Url;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long a mirror gets to answer the probe before it is left out.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The mirror picked for each list of mirrors, so that they are probed once per run
+/// even when several versions are installed.
+static CHOSEN_MIRRORS: Mutex<Vec<(Vec<Url>, Url)>> = Mutex::new(Vec::new());
+
+/// The mirror to download `version` from. When alternative mirrors are given,
+/// they are probed along with `--ruby-build-mirror` and the fastest one having
+/// the archive is used. The first mirror is used without probing with `--offline`,
+/// and when no mirror answers.
+pub fn select_mirror(config: &FrumConfig, version: &Version) -> Url {
+    if config.alternative_mirrors.is_empty() || config.offline {
+        return config.ruby_build_mirror.clone();
+    }
+    let mirrors = std::iter::once(&config.ruby_build_mirror)
+        .chain(&config.alternative_mirrors)
+        .cloned()
+        .collect::<Vec<_>>();
+    let mut chosen_mirrors = CHOSEN_MIRRORS.lock().unwrap_or_else(|err| err.into_inner());
+    if let Some((_, mirror)) = chosen_mirrors
+        .iter()
+        .find(|(candidates, _)| candidates == &mirrors)
+    {
+        return mirror.clone();
+    }
+    let mirror = fastest(&mirrors, version).unwrap_or_else(|| mirrors[0].clone());
+    debug!("Use the mirror {}", mirror);
+    chosen_mirrors.push((mirrors, mirror.clone()));
+    mirror
+}
+
+/// The mirror answering the probe for the archive of `version` the fastest, if any does.
+fn fastest(mirrors: &[Url], version: &Version) -> Option<Url> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .ok()?;
+    let client = &client;
+    let latencies = std::thread::scope(|scope| {
+        let probes = mirrors
+            .iter()
+            .map(|mirror| package_url(mirror.clone(), version))
+            .map(|url| scope.spawn(move || probe(client, &url)))
+            .collect::<Vec<_>>();
+        probes
+            .into_iter()
+            .map(|probe| probe.join().ok().flatten())
+            .collect::<Vec<_>>()
+    });
+    mirrors
+        .iter()
+        .zip(latencies)
+        .filter_map(|(mirror, latency)| Some((latency?, mirror)))
+        .min_by_key(|(latency, _)| *latency)
+        .map(|(_, mirror)| mirror.clone())
+}
+
+/// How long it took to learn that `url` exists, or `None` if it doesn't or the mirror didn't answer.
+fn probe(client: &reqwest::blocking::Client, url: &Url) -> Option<Duration> {
+    let started = Instant::now();
+    if url.scheme() == "file" {
+        return url.to_file_path().ok()?.exists().then(|| started.elapsed());
+    }
+    let response = client.head(url.clone()).send().ok()?;
+    debug!(
+        "{} answered {} in {:?}",
+        url,
+        response.status(),
+        started.elapsed()
+    );
+    response.status().is_success().then(|| started.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, Write};
+    use tempfile::tempdir;
+
+    /// Answers every request with `status` after `delay`.
+    fn serve_after(delay: Duration, status: &'static str) -> Url {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let mirror = Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                std::thread::spawn(move || {
+                    let mut request = std::io::BufReader::new(stream.try_clone().unwrap());
+                    let mut line = String::new();
+                    while request.read_line(&mut line).unwrap_or(0) > 2 {
+                        line.clear();
+                    }
+                    std::thread::sleep(delay);
+                    write!(
+                        stream,
+                        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        status
+                    )
+                    .ok();
+                });
+            }
+        });
+        mirror
+    }
+
+    #[test]
+    fn test_select_fastest_mirror() {
+        let version = Version::parse("3.2.2").unwrap();
+        let slow = serve_after(Duration::from_millis(500), "200 OK");
+        let fast = serve_after(Duration::from_millis(0), "200 OK");
+        let missing = serve_after(Duration::from_millis(0), "404 Not Found");
+        let config = FrumConfig {
+            alternative_mirrors: vec![missing, fast.clone()],
+            ..FrumConfig::default()
+                .with_base_dir(tempdir().unwrap().path())
+                .with_mirror(slow.clone())
+        };
+
+        assert_eq!(select_mirror(&config, &version), fast);
+        assert_eq!(
+            select_mirror(
+                &FrumConfig {
+                    offline: true,
+                    ..config
+                },
+                &version
+            ),
+            slow
+        );
+    }
+}
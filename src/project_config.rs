@@ -0,0 +1,267 @@
+use crate::command::ErrorKind;
+use crate::version_file::find_up;
+use log::debug;
+use std::path::PathBuf;
+use thiserror::Error;
+
+pub const PROJECT_CONFIG_FILE: &str = "frum.toml";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("Can't parse {path:?} at line {line}: {message}")]
+    ParseError {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::IoError(_) => "IoError",
+            Self::ParseError { .. } => "ParseError",
+        }
+    }
+}
+
+/// Build options pinned by a project in its `frum.toml`.
+///
+/// Only the `[build]` table is read, e.g.
+///
+/// ```toml
+/// [build]
+/// configure_opts = ["--disable-install-doc"]
+/// openssl_dir = "/opt/openssl"
+/// jobs = 4
+/// ccache = true
+/// ```
+#[derive(Debug, Default, PartialEq)]
+pub struct ProjectConfig {
+    pub configure_opts: Vec<String>,
+    pub openssl_dir: Option<PathBuf>,
+    pub jobs: Option<usize>,
+    /// Builds through ccache like `install --use-ccache`.
+    pub ccache: bool,
+}
+
+impl ProjectConfig {
+    /// Reads the nearest `frum.toml` from `search_dir` or its parents.
+    pub fn find(search_dir: PathBuf) -> Result<Option<Self>, Error> {
+        let path = match find_up(search_dir, PROJECT_CONFIG_FILE) {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        Self::load(path)
+    }
+
+    /// Reads the `frum.toml` at `path`, if there is one.
+    pub fn load(path: PathBuf) -> Result<Option<Self>, Error> {
+        let value = match std::fs::read_to_string(&path) {
+            Ok(value) => value,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::IoError(err)),
+        };
+        debug!("Use build options in {}", path.display());
+        Self::parse(&value)
+            .map(Some)
+            .map_err(|(line, message)| Error::ParseError {
+                path,
+                line,
+                message,
+            })
+    }
+
+    fn parse(value: &str) -> Result<Self, (usize, String)> {
+        let mut project_config = Self::default();
+        let mut table = String::new();
+        let mut lines = value.lines().enumerate();
+        while let Some((index, line)) = lines.next() {
+            let line_number = index + 1;
+            let mut line = strip_comment(line).trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with('[') {
+                table = line
+                    .strip_prefix('[')
+                    .and_then(|line| line.strip_suffix(']'))
+                    .ok_or_else(|| (line_number, "invalid table header".to_string()))?
+                    .trim()
+                    .to_string();
+                continue;
+            }
+            // Arrays may span several lines
+            if line.contains("= [") || line.contains("=[") {
+                while !line.ends_with(']') {
+                    let (_, next_line) = lines
+                        .next()
+                        .ok_or_else(|| (line_number, "unterminated array".to_string()))?;
+                    line.push(' ');
+                    line.push_str(strip_comment(next_line).trim());
+                }
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| (line_number, "expected `key = value`".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+            if table != "build" {
+                continue;
+            }
+            match key {
+                "configure_opts" => {
+                    project_config.configure_opts =
+                        parse_string_array(value).map_err(|message| (line_number, message))?
+                }
+                "openssl_dir" => {
+                    project_config.openssl_dir = Some(PathBuf::from(
+                        parse_string(value).map_err(|message| (line_number, message))?,
+                    ))
+                }
+                "jobs" => {
+                    project_config.jobs = Some(
+                        value
+                            .parse::<usize>()
+                            .map_err(|_| (line_number, format!("invalid jobs: {}", value)))?,
+                    )
+                }
+                "ccache" => {
+                    project_config.ccache = value
+                        .parse::<bool>()
+                        .map_err(|_| (line_number, format!("invalid ccache: {}", value)))?
+                }
+                _ => debug!("Ignore unknown build option {}", key),
+            }
+        }
+        Ok(project_config)
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_string(value: &str) -> Result<String, String> {
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .ok_or_else(|| format!("expected a string: {}", value))?;
+    let mut string = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                _ => return Err(format!("invalid escape in {}", value)),
+            }
+        } else {
+            string.push(c);
+        }
+    }
+    Ok(string)
+}
+
+fn parse_string_array(value: &str) -> Result<Vec<String>, String> {
+    let inner = value
+        .strip_prefix('[')
+        .and_then(|value| value.strip_suffix(']'))
+        .ok_or_else(|| format!("expected an array: {}", value))?;
+    let mut items = vec![];
+    let mut item = String::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in inner.chars() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && in_string {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        } else if c == ',' && !in_string {
+            if !item.trim().is_empty() {
+                items.push(parse_string(item.trim())?);
+            }
+            item.clear();
+            continue;
+        }
+        item.push(c);
+    }
+    if !item.trim().is_empty() {
+        items.push(parse_string(item.trim())?);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_build_table() {
+        let project_config = ProjectConfig::parse(indoc::indoc!(
+            r#"
+            # pinned build options
+            [build]
+            configure_opts = [
+                "--disable-install-doc", # docs take too long
+                "--with-jemalloc",
+            ]
+            openssl_dir = "/opt/openssl"
+            jobs = 4
+            ccache = true
+
+            [other]
+            jobs = "ignored"
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            project_config,
+            ProjectConfig {
+                configure_opts: vec![
+                    "--disable-install-doc".to_string(),
+                    "--with-jemalloc".to_string()
+                ],
+                openssl_dir: Some(PathBuf::from("/opt/openssl")),
+                jobs: Some(4),
+                ccache: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_find_reports_invalid_line() {
+        let project_dir = tempdir().unwrap();
+        std::fs::write(
+            project_dir.path().join(PROJECT_CONFIG_FILE),
+            "[build]\njobs = many\n",
+        )
+        .unwrap();
+
+        match ProjectConfig::find(project_dir.path().to_path_buf()) {
+            Err(Error::ParseError { line, .. }) => assert_eq!(line, 2),
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+}
@@ -1,5 +1,46 @@
-use crate::version::Version;
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::fetch::{Conditional, Validators};
+use crate::version::{Channel, Version};
+use log::debug;
+use std::io::Read;
+use std::time::SystemTime;
+use thiserror::Error;
 
+const INDEX_FILE: &str = "index.txt";
+/// The ETag and Last-Modified headers the cached index was served with.
+const VALIDATORS_FILE: &str = "index.txt.validators";
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    #[error(transparent)]
+    HttpError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    FetchError(#[from] crate::fetch::Error),
+    #[error("Can't find the remote version list at {url}")]
+    IndexNotFound { url: reqwest::Url },
+    #[error(
+        "The remote version list isn't cached yet. Please run the command once without --offline."
+    )]
+    NotCached,
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::FetchError(err) => err.kind(),
+            Self::IndexNotFound { .. } => "IndexNotFound",
+            Self::NotCached => "NotCached",
+        }
+    }
+}
+
+#[allow(dead_code)]
 pub struct IndexedRubyVersion {
     pub version: Version,
     pub url: String,
@@ -8,16 +49,85 @@ pub struct IndexedRubyVersion {
     pub sha521: String,
 }
 
-pub fn list(base_url: &reqwest::Url) -> Result<Vec<IndexedRubyVersion>, reqwest::Error> {
-    let value =
-        reqwest::blocking::get(format!("{}/index.txt", base_url.as_str()).as_str())?.text()?;
+/// Fetches the remote index and caches it, or reads the cached one when `--offline` is set.
+/// The cached index is reused without downloading it again when the server says it is unchanged.
+pub fn list(config: &FrumConfig) -> Result<Vec<IndexedRubyVersion>, Error> {
+    let cache_path = config.cache_dir().join(INDEX_FILE);
+    if config.offline {
+        let value = std::fs::read_to_string(&cache_path).map_err(|err| match err.kind() {
+            std::io::ErrorKind::NotFound => Error::NotCached,
+            _ => Error::IoError(err),
+        })?;
+        return Ok(parse(&value));
+    }
+
+    let url = config.index_url();
+    let validators_path = config.cache_dir().join(VALIDATORS_FILE);
+    let validators = if cache_path.exists() {
+        Validators::parse(&std::fs::read_to_string(&validators_path).unwrap_or_default())
+    } else {
+        Validators::default()
+    };
+    match crate::fetch::fetch_if_changed(&url, &validators)? {
+        Conditional::NotModified => {
+            debug!("{} is unchanged, using the cached index", url);
+            let value = std::fs::read_to_string(&cache_path)?;
+            std::fs::File::options()
+                .write(true)
+                .open(&cache_path)?
+                .set_modified(SystemTime::now())?;
+            Ok(parse(&value))
+        }
+        Conditional::NotFound => Err(Error::IndexNotFound { url }),
+        Conditional::Modified {
+            mut fetched,
+            validators,
+        } => {
+            let mut value = String::new();
+            fetched.body.read_to_string(&mut value)?;
+            std::fs::write(&cache_path, &value)?;
+            if validators.is_empty() {
+                std::fs::remove_file(&validators_path).ok();
+            } else {
+                std::fs::write(&validators_path, validators.to_string())?;
+            }
+            Ok(parse(&value))
+        }
+    }
+}
+
+/// Lists the remote versions, leaving out previews and release candidates unless `include_prereleases` is set.
+pub fn list_versions(
+    config: &FrumConfig,
+    include_prereleases: bool,
+) -> Result<Vec<Version>, Error> {
+    Ok(list(config)?
+        .drain(..)
+        .map(|x| x.version)
+        .filter(|version| include_prereleases || !version.is_prerelease())
+        .collect())
+}
+
+/// Lists the remote versions in `channel`.
+pub fn list_channel(config: &FrumConfig, channel: Channel) -> Result<Vec<Version>, Error> {
+    Ok(list(config)?
+        .drain(..)
+        .map(|x| x.version)
+        .filter(|version| version.channel() == channel)
+        .collect())
+}
+
+pub fn parse(value: &str) -> Vec<IndexedRubyVersion> {
     let re = regex::Regex::new(r"(\S+)\s+(\S+)\s+(\S+)\s+(\S+)\s+(\S+)").unwrap();
     let mut versions = vec![];
     for (index, line) in value.split('\n').enumerate() {
         if line.is_empty() || index == 0 {
             continue;
         }
-        let cap = re.captures(line).unwrap();
+        let cap = match re.captures(line) {
+            Some(cap) => cap,
+            None => continue,
+        };
         if cap
             .get(1)
             .map_or("".to_string(), |m| m.as_str().to_string())
@@ -47,5 +157,177 @@ pub fn list(base_url: &reqwest::Url) -> Result<Vec<IndexedRubyVersion>, reqwest:
                 .map_or("".to_string(), |m| m.as_str().to_string()),
         })
     }
-    Ok(versions)
+    versions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const INDEX: &str = "name\turl\tsha1\tsha256\tsha512
+ruby-2.7.1\thttps://cache.ruby-lang.org/pub/ruby/2.7/ruby-2.7.1.tar.gz\ta\tb\tc
+ruby-3.0.0-preview1\thttps://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.0-preview1.tar.gz\ta\tb\tc
+ruby-3.0.0\thttps://cache.ruby-lang.org/pub/ruby/3.0/ruby-3.0.0.tar.gz\ta\tb\tc
+";
+
+    #[test]
+    fn test_offline_list_without_cache() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        assert!(matches!(list(&config), Err(Error::NotCached)));
+    }
+
+    #[test]
+    fn test_offline_list_with_cache() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        std::fs::write(config.cache_dir().join(INDEX_FILE), INDEX).unwrap();
+        let versions = list(&config)
+            .unwrap()
+            .drain(..)
+            .map(|x| x.version.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(versions, vec!["2.7.1", "3.0.0-preview1", "3.0.0"]);
+    }
+
+    #[test]
+    fn test_list_versions_filters_prereleases() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        std::fs::write(config.cache_dir().join(INDEX_FILE), INDEX).unwrap();
+        let versions = |include_prereleases| {
+            list_versions(&config, include_prereleases)
+                .unwrap()
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(versions(false), vec!["2.7.1", "3.0.0"]);
+        assert_eq!(versions(true), vec!["2.7.1", "3.0.0-preview1", "3.0.0"]);
+    }
+
+    #[test]
+    fn test_mirror_index_is_independent_of_mirror() {
+        let tarball_mirror = reqwest::Url::parse("http://127.0.0.1:1/ruby").unwrap();
+        let index_url = crate::commands::install::tests::serve_once(INDEX.into(), INDEX.len())
+            .join("index.txt")
+            .unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(tarball_mirror.clone())
+            .with_mirror_index(index_url.clone());
+
+        let versions = list(&config).unwrap();
+        assert_eq!(versions.len(), 3);
+        assert_eq!(config.index_url(), index_url);
+        assert_eq!(
+            crate::commands::install::package_url(
+                config.ruby_build_mirror.clone(),
+                &versions[0].version
+            )
+            .as_str(),
+            "http://127.0.0.1:1/ruby/2.7/ruby-2.7.1.tar.xz"
+        );
+        assert_eq!(
+            FrumConfig::default()
+                .with_mirror(tarball_mirror)
+                .index_url()
+                .as_str(),
+            "http://127.0.0.1:1/ruby/index.txt"
+        );
+    }
+
+    #[test]
+    fn test_list_channels() {
+        let config = FrumConfig {
+            base_dir: Some(tempdir().unwrap().path().to_path_buf()),
+            offline: true,
+            ..Default::default()
+        };
+        let index = format!(
+            "{}{}",
+            INDEX,
+            "ruby-3.1.0-rc1\t-\ta\tb\tc\nruby-3.4.0-dev\t-\ta\tb\tc\nruby-3.4.0-snapshot.20241201\t-\ta\tb\tc\n"
+        );
+        std::fs::write(config.cache_dir().join(INDEX_FILE), index).unwrap();
+        let channel = |channel| {
+            list_channel(&config, channel)
+                .unwrap()
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(channel(Channel::Stable), vec!["2.7.1", "3.0.0"]);
+        assert_eq!(
+            channel(Channel::Preview),
+            vec!["3.0.0-preview1", "3.1.0-rc1"]
+        );
+        assert_eq!(
+            channel(Channel::Dev),
+            vec!["3.4.0-dev", "3.4.0-snapshot.20241201"]
+        );
+    }
+
+    #[test]
+    fn test_unchanged_index_is_reused() {
+        use std::io::{BufRead, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let config = FrumConfig::default()
+            .with_base_dir(tempdir().unwrap().path())
+            .with_mirror(
+                reqwest::Url::parse(&format!("http://{}", listener.local_addr().unwrap())).unwrap(),
+            );
+        let server = std::thread::spawn(move || {
+            let mut conditions = vec![];
+            for response in &[
+                format!(
+                    "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    INDEX.len(),
+                    INDEX
+                ),
+                "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string(),
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = std::io::BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while request.read_line(&mut line).unwrap() > 2 {
+                    if line.to_lowercase().starts_with("if-none-match:") {
+                        conditions.push(line.trim().to_string());
+                    }
+                    line.clear();
+                }
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+            conditions
+        });
+        let versions = || {
+            list(&config)
+                .unwrap()
+                .drain(..)
+                .map(|x| x.version.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(versions(), vec!["2.7.1", "3.0.0-preview1", "3.0.0"]);
+        let cache_path = config.cache_dir().join(INDEX_FILE);
+        let refreshed_at = std::fs::metadata(&cache_path).unwrap().modified().unwrap();
+        assert_eq!(versions(), vec!["2.7.1", "3.0.0-preview1", "3.0.0"]);
+
+        assert_eq!(server.join().unwrap(), vec!["if-none-match: \"v1\""]);
+        assert_eq!(std::fs::read_to_string(&cache_path).unwrap(), INDEX);
+        assert!(std::fs::metadata(&cache_path).unwrap().modified().unwrap() >= refreshed_at);
+    }
 }
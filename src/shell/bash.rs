@@ -18,7 +18,7 @@ impl Shell for Bash {
             r#"
                 __frumcd() {
                     \cd "$@" || return $?
-                    frum --log-level quiet local
+                    frum --log-level quiet local --quiet
                 }
 
                 alias cd=__frumcd
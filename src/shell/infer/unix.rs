@@ -16,13 +16,13 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
     let mut pid = Some(std::process::id());
     let mut visited = 0;
 
-    while pid != None && visited < MAX_ITERATIONS {
+    while pid.is_some() && visited < MAX_ITERATIONS {
         let process_info = get_process_info(pid.unwrap()).ok()?;
         let binary = process_info
             .command
             .trim_start_matches('-')
             .split('/')
-            .last()
+            .next_back()
             .expect("Can't read file name of process tree");
 
         match binary {
@@ -64,7 +64,7 @@ fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
         .next()
         .ok_or_else(|| Error::from(ErrorKind::NotFound))??;
 
-    let mut parts = line.trim().split_whitespace();
+    let mut parts = line.split_whitespace();
     let ppid = parts
         .next()
         .expect("Can't read the ppid from ps, should be the first item in the table");
@@ -73,7 +73,7 @@ fn get_process_info(pid: u32) -> std::io::Result<ProcessInfo> {
         .expect("Can't read the command from ps, should be the second item in the table");
 
     Ok(ProcessInfo {
-        parent_pid: u32::from_str_radix(ppid, 10).ok(),
+        parent_pid: ppid.parse::<u32>().ok(),
         command: command.into(),
     })
 }
@@ -85,7 +85,7 @@ mod tests {
 
     #[test]
     fn test_get_process_info() {
-        let subprocess = Command::new("bash")
+        let mut subprocess = Command::new("bash")
             .stdin(Stdio::piped())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -93,6 +93,8 @@ mod tests {
             .expect("Can't execute command");
         let process_info = get_process_info(subprocess.id());
         let parent_pid = process_info.ok().and_then(|x| x.parent_pid);
+        subprocess.kill().expect("Can't kill subprocess");
+        subprocess.wait().expect("Can't wait for subprocess");
         assert_eq!(parent_pid, Some(std::process::id()));
     }
 }
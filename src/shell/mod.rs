@@ -2,6 +2,7 @@ pub mod bash;
 pub mod fish;
 pub mod infer;
 pub mod powershell;
+#[cfg(windows)]
 pub mod windows_command;
 pub mod zsh;
 
@@ -11,6 +12,7 @@ use std::path::Path;
 pub use bash::Bash;
 pub use fish::Fish;
 pub use powershell::PowerShell;
+#[cfg(windows)]
 pub use windows_command::WindowsCommand;
 pub use zsh::Zsh;
 pub trait Shell: Debug {
@@ -36,8 +38,72 @@ pub fn infer_shell() -> Option<Box<dyn Shell>> {
     infer::unix::infer_shell()
 }
 
+/// The shell frum runs in, inferred from the process tree, then from the login shell in
+/// `SHELL`, then taken from `--assume-shell` or `FRUM_SHELL`, which is for CI where frum
+/// isn't started by an interactive shell.
+pub fn infer_shell_or_assumed(config: &crate::config::FrumConfig) -> Option<Box<dyn Shell>> {
+    or_assumed(
+        infer_shell(),
+        std::env::var("SHELL").ok().as_deref(),
+        config.assume_shell.as_deref(),
+    )
+}
+
+fn or_assumed(
+    inferred: Option<Box<dyn Shell>>,
+    login_shell: Option<&str>,
+    assumed: Option<&str>,
+) -> Option<Box<dyn Shell>> {
+    inferred
+        .or_else(|| shell_named(Path::new(login_shell?).file_name()?.to_str()?))
+        .or_else(|| shell_named(assumed?))
+}
+
+/// The shell called `name` in `AVAILABLE_SHELLS`, or named after its binary, such as `pwsh`.
+pub fn shell_named(name: &str) -> Option<Box<dyn Shell>> {
+    match name {
+        "sh" | "bash" => Some(Box::from(Bash)),
+        "zsh" => Some(Box::from(Zsh)),
+        "fish" => Some(Box::from(Fish)),
+        "powershell" | "pwsh" => Some(Box::from(PowerShell)),
+        #[cfg(windows)]
+        "cmd" => Some(Box::from(WindowsCommand)),
+        _ => None,
+    }
+}
+
 impl From<Box<dyn Shell>> for clap::Shell {
     fn from(shell: Box<dyn Shell>) -> clap::Shell {
         shell.as_clap_shell()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assumed_shell_when_inference_fails() {
+        let config = crate::config::FrumConfig {
+            assume_shell: Some("bash".to_string()),
+            ..Default::default()
+        };
+
+        let shell = or_assumed(None, None, config.assume_shell.as_deref()).unwrap();
+        assert!(matches!(shell.as_clap_shell(), clap::Shell::Bash));
+        assert!(matches!(
+            or_assumed(Some(Box::from(Zsh)), None, Some("bash"))
+                .unwrap()
+                .as_clap_shell(),
+            clap::Shell::Zsh
+        ));
+        assert!(matches!(
+            or_assumed(None, Some("/usr/bin/fish"), Some("bash"))
+                .unwrap()
+                .as_clap_shell(),
+            clap::Shell::Fish
+        ));
+        assert!(or_assumed(None, Some("/bin/tcsh"), Some("tcsh")).is_none());
+        assert!(or_assumed(None, None, None).is_none());
+    }
+}
@@ -24,7 +24,7 @@ impl Shell for PowerShell {
             function Set-LocationWithFrum {
                 param($path)
                 Set-Location $path
-                If (Test-Path .ruby-version) { & frum --log-level quiet local }
+                If (Test-Path .ruby-version) { & frum --log-level quiet local --quiet }
             }
             Set-Alias cd_with_frum Set-LocationWithFrum -Force
             Remove-Item alias:\cd
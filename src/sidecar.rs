@@ -0,0 +1,320 @@
+use crate::archive::{extract::Error as ExtractError, extract::Extract, tar_gz::TarGz};
+use crate::command::ErrorKind;
+use crate::config::FrumConfig;
+use crate::outln;
+use colored::Colorize;
+use log::debug;
+use reqwest::Url;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use thiserror::Error;
+
+const OPENSSL_MIRROR: &str = "https://www.openssl.org/source/";
+/// Written into a prefix once `make install_sw` has finished, so that a prefix left
+/// behind by an interrupted build isn't mistaken for a usable OpenSSL.
+pub(crate) const COMPLETE_MARKER: &str = ".frum-complete";
+
+/// Keeps Ruby versions installed in parallel from building the same OpenSSL twice.
+static OPENSSL_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    #[error(transparent)]
+    HttpError(#[from] reqwest::Error),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    FetchError(#[from] crate::fetch::Error),
+    #[error("Can't extract the file: {source:?}")]
+    ExtractError { source: ExtractError },
+    #[error("Can't find OpenSSL {version}")]
+    OpenSslNotFound { version: String },
+    #[error("The downloaded archive is empty")]
+    TarIsEmpty,
+    #[error("Can't build OpenSSL: {stderr}")]
+    CantBuildOpenSsl { stderr: String },
+    #[error("Can't find the published SHA-256 of OpenSSL {version}")]
+    ChecksumNotFound { version: String },
+    #[error("The SHA-256 of OpenSSL {version} is {actual}, but {expected} was published")]
+    ChecksumMismatch {
+        version: String,
+        expected: String,
+        actual: String,
+    },
+    #[error("OpenSSL {version} isn't built yet. Please run the command once without --offline.")]
+    NotCached { version: String },
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::HttpError(_) => "HttpError",
+            Self::IoError(_) => "IoError",
+            Self::FetchError(err) => err.kind(),
+            Self::ExtractError { .. } => "ExtractError",
+            Self::OpenSslNotFound { .. } => "OpenSslNotFound",
+            Self::TarIsEmpty => "TarIsEmpty",
+            Self::CantBuildOpenSsl { .. } => "CantBuildOpenSsl",
+            Self::ChecksumNotFound { .. } => "ChecksumNotFound",
+            Self::ChecksumMismatch { .. } => "ChecksumMismatch",
+            Self::NotCached { .. } => "NotCached",
+        }
+    }
+}
+
+/// The prefix a specific OpenSSL version is installed into.
+pub fn openssl_prefix(config: &FrumConfig, version: &str) -> PathBuf {
+    config.sidecars_dir().join(format!("openssl-{}", version))
+}
+
+/// Returns the prefix of the requested OpenSSL version, building it first
+/// unless a previous Ruby installation has already done so.
+pub fn install_openssl(config: &FrumConfig, version: &str) -> Result<PathBuf, Error> {
+    let mirror = Url::parse(OPENSSL_MIRROR).expect("invalid OpenSSL mirror");
+    install_openssl_from(config, version, &mirror)
+}
+
+fn install_openssl_from(
+    config: &FrumConfig,
+    version: &str,
+    mirror: &Url,
+) -> Result<PathBuf, Error> {
+    let _lock = OPENSSL_LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    let prefix = openssl_prefix(config, version);
+    if prefix.join(COMPLETE_MARKER).exists() {
+        debug!("Use the cached OpenSSL in {}", prefix.display());
+        return Ok(prefix);
+    }
+    if config.offline {
+        return Err(Error::NotCached {
+            version: version.to_string(),
+        });
+    }
+    if prefix.exists() {
+        debug!("Remove the incomplete OpenSSL in {}", prefix.display());
+        std::fs::remove_dir_all(&prefix)?;
+    }
+
+    let not_found = || Error::OpenSslNotFound {
+        version: version.to_string(),
+    };
+    let archive = format!("openssl-{}.tar.gz", version);
+    let url = mirror.join(&archive).map_err(|_| not_found())?;
+    let checksum_url = mirror
+        .join(&format!("{}.sha256", archive))
+        .map_err(|_| not_found())?;
+    let expected = published_sha256(&checksum_url)?.ok_or_else(|| Error::ChecksumNotFound {
+        version: version.to_string(),
+    })?;
+    outln!(config#Info, "{} Downloading {}", "==>".green(), url.as_str().green());
+    let mut fetched = crate::fetch::fetch(&url)?.ok_or_else(not_found)?;
+
+    let temp_dir = tempfile::TempDir::new_in(config.sidecars_dir())?;
+    let archive_path = temp_dir.path().join(&archive);
+    std::io::copy(
+        &mut fetched.body,
+        &mut std::fs::File::create(&archive_path)?,
+    )?;
+    let actual = crate::checksums::sha256(&archive_path)?;
+    if actual != expected {
+        return Err(Error::ChecksumMismatch {
+            version: version.to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    let source_root = temp_dir.path().join("source");
+    TarGz::new(std::fs::File::open(&archive_path)?)
+        .extract_into(&source_root)
+        .map_err(|source| Error::ExtractError { source })?;
+    let source_dir = std::fs::read_dir(&source_root)?
+        .next()
+        .ok_or(Error::TarIsEmpty)??
+        .path();
+
+    outln!(config#Info, "{} Building {}", "==>".green(), format!("OpenSSL {}", version).green());
+    if let Err(err) = build_openssl(&source_dir, &prefix) {
+        std::fs::remove_dir_all(&prefix).ok();
+        return Err(err);
+    }
+    std::fs::write(prefix.join(COMPLETE_MARKER), "")?;
+    Ok(prefix)
+}
+
+/// The SHA-256 published at `url`, either as a bare digest or in the
+/// `SHA2-256(<archive>)= <digest>` form newer releases use.
+fn published_sha256(url: &Url) -> Result<Option<String>, Error> {
+    let mut fetched = match crate::fetch::fetch(url)? {
+        Some(fetched) => fetched,
+        None => return Ok(None),
+    };
+    let mut body = String::new();
+    fetched.body.read_to_string(&mut body)?;
+    Ok(body
+        .split_whitespace()
+        .find(|token| token.len() == 64 && token.chars().all(|c| c.is_ascii_hexdigit()))
+        .map(str::to_ascii_lowercase))
+}
+
+fn build_openssl(source_dir: &Path, prefix: &Path) -> Result<(), Error> {
+    debug!("./config --prefix={}", prefix.display());
+    let config = Command::new("sh")
+        .arg("config")
+        .arg(format!("--prefix={}", prefix.display()))
+        .arg(format!("--openssldir={}", prefix.join("ssl").display()))
+        .arg("shared")
+        .current_dir(source_dir)
+        .output()?;
+    if !config.status.success() {
+        return Err(Error::CantBuildOpenSsl {
+            stderr: format!("config failed: {}", String::from_utf8_lossy(&config.stderr)),
+        });
+    }
+    debug!("make -j {}", num_cpus::get());
+    let make = Command::new("make")
+        .arg("-j")
+        .arg(num_cpus::get().to_string())
+        .current_dir(source_dir)
+        .output()?;
+    if !make.status.success() {
+        return Err(Error::CantBuildOpenSsl {
+            stderr: format!("make failed: {}", String::from_utf8_lossy(&make.stderr)),
+        });
+    }
+    debug!("make install_sw");
+    let make_install = Command::new("make")
+        .arg("install_sw")
+        .current_dir(source_dir)
+        .output()?;
+    if !make_install.status.success() {
+        return Err(Error::CantBuildOpenSsl {
+            stderr: format!(
+                "make install_sw failed: {}",
+                String::from_utf8_lossy(&make_install.stderr)
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const STUB_CONFIG: &str = indoc::indoc!(
+        r#"
+        for arg in "$@"; do
+            case "$arg" in
+                --prefix=*) prefix="${arg#--prefix=}" ;;
+            esac
+        done
+        printf 'all:\n\ttrue\ninstall_sw:\n\tmkdir -p %s/lib\n\ttouch %s/lib/libssl.a\n' "$prefix" "$prefix" > Makefile
+        "#
+    );
+
+    /// A mirror directory holding an OpenSSL tarball whose `config` is `STUB_CONFIG`,
+    /// next to the SHA-256 published for it.
+    fn stub_openssl_mirror(version: &str, sha256: Option<&str>) -> (tempfile::TempDir, Url) {
+        let mirror_dir = tempdir().unwrap();
+        let archive_path = mirror_dir
+            .path()
+            .join(format!("openssl-{}.tar.gz", version));
+        let encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&archive_path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut builder = tar::Builder::new(encoder);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(STUB_CONFIG.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder
+            .append_data(
+                &mut header,
+                format!("openssl-{}/config", version),
+                STUB_CONFIG.as_bytes(),
+            )
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        let sha256 = sha256
+            .map(str::to_string)
+            .unwrap_or_else(|| crate::checksums::sha256(&archive_path).unwrap());
+        std::fs::write(
+            mirror_dir
+                .path()
+                .join(format!("openssl-{}.tar.gz.sha256", version)),
+            format!("SHA2-256(openssl-{}.tar.gz)= {}\n", version, sha256),
+        )
+        .unwrap();
+        let mirror = Url::from_directory_path(mirror_dir.path()).unwrap();
+        (mirror_dir, mirror)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_build_openssl_into_prefix() {
+        let source_dir = tempdir().unwrap();
+        let prefix = tempdir().unwrap().path().join("openssl-1.1.1k");
+        std::fs::write(source_dir.path().join("config"), STUB_CONFIG).unwrap();
+
+        build_openssl(source_dir.path(), &prefix).expect("Can't build OpenSSL");
+
+        assert!(prefix.join("lib").join("libssl.a").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_install_openssl_replaces_incomplete_prefix() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let (_mirror_dir, mirror) = stub_openssl_mirror("1.1.1k", None);
+        let prefix = openssl_prefix(&config, "1.1.1k");
+        std::fs::create_dir_all(prefix.join("include")).unwrap();
+
+        assert_eq!(
+            install_openssl_from(&config, "1.1.1k", &mirror).unwrap(),
+            prefix
+        );
+        assert!(prefix.join("lib").join("libssl.a").exists());
+        assert!(prefix.join(COMPLETE_MARKER).exists());
+        assert!(!prefix.join("include").exists());
+    }
+
+    #[test]
+    fn test_install_openssl_checks_sha256() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let (_mirror_dir, mirror) = stub_openssl_mirror("1.1.1k", Some(&"0".repeat(64)));
+
+        assert!(matches!(
+            install_openssl_from(&config, "1.1.1k", &mirror),
+            Err(Error::ChecksumMismatch { expected, .. }) if expected == "0".repeat(64)
+        ));
+        assert!(!openssl_prefix(&config, "1.1.1k").exists());
+    }
+
+    #[test]
+    fn test_install_openssl_uses_cache() {
+        let config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        let prefix = openssl_prefix(&config, "1.1.1k");
+        std::fs::create_dir_all(&prefix).unwrap();
+        std::fs::write(prefix.join(COMPLETE_MARKER), "").unwrap();
+
+        assert_eq!(install_openssl(&config, "1.1.1k").unwrap(), prefix);
+    }
+
+    #[test]
+    fn test_install_openssl_offline_without_cache() {
+        let mut config = FrumConfig::default().with_base_dir(tempdir().unwrap().path());
+        config.offline = true;
+        std::fs::create_dir_all(openssl_prefix(&config, "1.1.1k")).unwrap();
+
+        assert!(matches!(
+            install_openssl(&config, "1.1.1k"),
+            Err(Error::NotCached { .. })
+        ));
+    }
+}
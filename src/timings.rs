@@ -0,0 +1,69 @@
+use crate::json::JsonEnvelope;
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// How long the phases of a command took, in the order they ran.
+#[derive(Default)]
+pub struct Timings<'a> {
+    phases: Vec<(&'static str, Duration)>,
+    /// Told about every phase as it starts, to show the progress of the command.
+    on_phase: Option<Box<dyn Fn(&'static str) + Send + 'a>>,
+}
+
+impl<'a> Timings<'a> {
+    /// Calls `on_phase` with the name of every phase as it starts.
+    pub fn with_progress(on_phase: impl Fn(&'static str) + Send + 'a) -> Self {
+        Self {
+            phases: vec![],
+            on_phase: Some(Box::new(on_phase)),
+        }
+    }
+
+    /// Runs `f` and records how long it took as `phase`.
+    pub fn measure<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        if let Some(on_phase) = &self.on_phase {
+            on_phase(phase);
+        }
+        let started = Instant::now();
+        let result = f();
+        self.phases.push((phase, started.elapsed()));
+        result
+    }
+
+    pub fn phases(&self) -> &[(&'static str, Duration)] {
+        &self.phases
+    }
+
+    /// Formats the phases as a JSON object of seconds, keeping their order.
+    pub fn to_json(&self) -> String {
+        JsonEnvelope::new(TimingsJson { timings: self }).to_string()
+    }
+}
+
+#[derive(Serialize)]
+struct TimingsJson<'a> {
+    timings: &'a Timings<'a>,
+}
+
+impl Serialize for Timings<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.phases.len()))?;
+        for (phase, duration) in &self.phases {
+            map.serialize_entry(phase, &duration.as_secs_f64())?;
+        }
+        map.end()
+    }
+}
+
+impl std::fmt::Display for Timings<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (phase, duration)) in self.phases().iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:<12} {:.2}s", phase, duration.as_secs_f64())?;
+        }
+        Ok(())
+    }
+}
@@ -1,16 +1,47 @@
+use crate::command::ErrorKind;
 use crate::config::FrumConfig;
 use log::debug;
 use std::str::FromStr;
 use thiserror::Error;
 
-#[derive(Debug, PartialEq, PartialOrd, Eq, Ord, Clone)]
+/// A Ruby version. Versions are ordered so that listings are the same on every run:
+/// semver versions numerically, then builds from source alphabetically by their hash,
+/// then `system`.
+#[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Version {
     Semver(semver::Version),
+    /// A build from source, named after a hash of the source as `dev-<hash>`.
+    Dev(String),
     System,
 }
 
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Where each kind of version goes, whatever the order of the variants
+        let rank = |version: &Self| match version {
+            Self::Semver(_) => 0,
+            Self::Dev(_) => 1,
+            Self::System => 2,
+        };
+        match (self, other) {
+            (Self::Semver(a), Self::Semver(b)) => a.cmp(b),
+            (Self::Dev(a), Self::Dev(b)) => a.cmp(b),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 fn start_with_number(s: &str) -> bool {
-    s.chars().next().map(|x| x.is_digit(10)).unwrap_or(false)
+    s.chars()
+        .next()
+        .map(|x| x.is_ascii_digit())
+        .unwrap_or(false)
 }
 
 pub fn is_dotfile(dir: &std::fs::DirEntry) -> bool {
@@ -27,30 +58,146 @@ impl Version {
         debug!("{}", trimed_lowercased);
         if lowercased == "system" {
             Ok(Self::System)
+        } else if let Some(hash) = lowercased.strip_prefix("dev-") {
+            Ok(Self::Dev(hash.to_string()))
         } else if start_with_number(trimed_lowercased) {
-            Ok(Self::Semver(semver::Version::parse(&trimed_lowercased)?))
+            Ok(Self::Semver(semver::Version::parse(trimed_lowercased)?))
         } else {
-            unreachable!()
+            Err(semver::SemVerError::ParseError(format!(
+                "{} is not a Ruby version",
+                version_str.as_ref()
+            )))
+        }
+    }
+
+    pub fn is_prerelease(&self) -> bool {
+        match self {
+            Self::Semver(version) => version.is_prerelease(),
+            Self::Dev(_) => true,
+            Self::System => false,
+        }
+    }
+
+    pub fn channel(&self) -> Channel {
+        match self {
+            Self::Semver(version) if !version.is_prerelease() => Channel::Stable,
+            Self::Semver(version) => {
+                let is_snapshot = version.pre.iter().any(|identifier| {
+                    let identifier = identifier.to_string();
+                    identifier.starts_with("dev") || identifier.starts_with("snapshot")
+                });
+                if is_snapshot {
+                    Channel::Dev
+                } else {
+                    Channel::Preview
+                }
+            }
+            Self::Dev(_) => Channel::Dev,
+            Self::System => Channel::Stable,
+        }
+    }
+
+    /// The major and minor numbers of a semver version, e.g. `(3, 0)` for 3.0.1.
+    pub fn major_minor(&self) -> Option<(u64, u64)> {
+        match self {
+            Self::Semver(version) => Some((version.major, version.minor)),
+            Self::Dev(_) | Self::System => None,
         }
     }
 
+    /// The version installed in an entry of the versions directory, or `None` for
+    /// dotfiles such as `.downloads`, which frum uses internally.
+    pub fn from_dir_entry(entry: &std::fs::DirEntry) -> Result<Option<Self>, Error> {
+        if is_dotfile(entry) {
+            return Ok(None);
+        }
+        let file_name = entry.file_name();
+        let invalid = || Error::InvalidVersionDir {
+            name: file_name.to_string_lossy().to_string(),
+        };
+        let version = file_name
+            .to_str()
+            .ok_or_else(invalid)
+            .and_then(|name| Self::parse(name).map_err(|_| invalid()))?;
+        Ok(Some(version))
+    }
+
     pub fn installation_path(
         &self,
         config: &crate::config::FrumConfig,
     ) -> Option<std::path::PathBuf> {
         match self {
-            v @ Self::Semver(_) => Some(config.versions_dir().join(v.to_string())),
+            v @ (Self::Semver(_) | Self::Dev(_)) => Some(config.versions_dir().join(v.to_string())),
             Self::System => None,
         }
     }
 }
 
+/// How finished a release is, for listing and resolving versions within one.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Channel {
+    Stable,
+    /// Previews and release candidates, such as 3.4.0-preview1 or 3.4.0-rc1.
+    Preview,
+    /// Nightly snapshots and builds from source.
+    Dev,
+}
+
+impl Channel {
+    /// The names `--channel` accepts.
+    pub const NAMES: &'static [&'static str] = &["stable", "preview", "dev"];
+}
+
+impl FromStr for Channel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Channel, Self::Err> {
+        match s {
+            "stable" => Ok(Self::Stable),
+            "preview" => Ok(Self::Preview),
+            "dev" => Ok(Self::Dev),
+            channel => Err(format!(
+                "I don't know the channel {:?}, expected one of: {}",
+                channel,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     #[error("frum path doesn't exist")]
     EnvNotFound,
     #[error(transparent)]
     SemverError(#[from] semver::SemVerError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error("{name} in the versions directory isn't a Ruby version")]
+    InvalidVersionDir { name: String },
+}
+
+impl ErrorKind for Error {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::EnvNotFound => "EnvNotFound",
+            Self::SemverError(_) => "SemverError",
+            Self::IoError(_) => "IoError",
+            Self::InvalidVersionDir { .. } => "InvalidVersionDir",
+        }
+    }
+}
+
+pub fn installed_versions(config: &FrumConfig) -> Result<Vec<Version>, Error> {
+    let mut versions = vec![];
+    for entry in config.versions_dir().read_dir()? {
+        if let Some(version) = Version::from_dir_entry(&entry?)? {
+            versions.push(version);
+        }
+    }
+    versions.sort();
+    Ok(versions)
 }
 
 pub fn current_version(config: &FrumConfig) -> Result<Option<Version>, Error> {
@@ -88,6 +235,7 @@ impl std::fmt::Display for Version {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Semver(semver) => write!(f, "{}", semver),
+            Self::Dev(hash) => write!(f, "dev-{}", hash),
             Self::System => write!(f, "system"),
         }
     }
@@ -104,7 +252,95 @@ impl PartialEq<semver::Version> for Version {
     fn eq(&self, other: &semver::Version) -> bool {
         match self {
             Self::Semver(v) => v == other,
-            Self::System => false,
+            Self::Dev(_) | Self::System => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Error, Version};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_from_dir_entry() {
+        let versions_dir = tempdir().unwrap();
+        for name in &["2.7.2", ".downloads", "not-a-version"] {
+            std::fs::create_dir(versions_dir.path().join(name)).unwrap();
         }
+        let version_of = |name: &str| {
+            let entry = versions_dir
+                .path()
+                .read_dir()
+                .unwrap()
+                .map(Result::unwrap)
+                .find(|entry| entry.file_name() == name)
+                .unwrap();
+            Version::from_dir_entry(&entry)
+        };
+
+        assert_eq!(
+            version_of("2.7.2").unwrap(),
+            Some(Version::parse("2.7.2").unwrap())
+        );
+        assert_eq!(version_of(".downloads").unwrap(), None);
+        assert!(matches!(
+            version_of("not-a-version"),
+            Err(Error::InvalidVersionDir { name }) if name == "not-a-version"
+        ));
+    }
+
+    #[test]
+    fn test_is_prerelease() {
+        assert!(!Version::parse("3.0.0").unwrap().is_prerelease());
+        assert!(Version::parse("3.0.0-preview1").unwrap().is_prerelease());
+        assert!(Version::parse("ruby-2.7.0-rc2").unwrap().is_prerelease());
+        assert!(!Version::System.is_prerelease());
+        assert!(Version::parse("dev-1a2b3c").unwrap().is_prerelease());
+    }
+
+    #[test]
+    fn test_mixed_versions_order() {
+        let mut versions = [
+            "system",
+            "dev-f00d",
+            "3.0.0",
+            "2.10.0",
+            "dev-1a2b3c",
+            "3.0.0-preview1",
+            "2.7.2",
+        ]
+        .iter()
+        .map(|version| Version::parse(version).unwrap())
+        .collect::<Vec<_>>();
+
+        versions.sort();
+
+        assert_eq!(
+            versions
+                .iter()
+                .map(|version| version.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "2.7.2",
+                "2.10.0",
+                "3.0.0-preview1",
+                "3.0.0",
+                "dev-1a2b3c",
+                "dev-f00d",
+                "system"
+            ]
+        );
+        assert!(Version::parse("dev-0").unwrap() > Version::parse("10.0.0").unwrap());
+    }
+
+    #[test]
+    fn test_major_minor() {
+        assert_eq!(Version::parse("3.0.1").unwrap().major_minor(), Some((3, 0)));
+        assert_eq!(
+            Version::parse("ruby-2.7.0-rc2").unwrap().major_minor(),
+            Some((2, 7))
+        );
+        assert_eq!(Version::System.major_minor(), None);
     }
 }
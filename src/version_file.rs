@@ -5,7 +5,7 @@ use std::io::Read;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-const VERSION_FILE: &str = ".ruby-version";
+pub const VERSION_FILE: &str = ".ruby-version";
 
 pub fn get_user_version_for_directory(path: PathBuf) -> Option<InputVersion> {
     let version_file_path = find_up(path, VERSION_FILE)?;
@@ -43,7 +43,7 @@ pub fn get_user_version_for_file(path: PathBuf) -> Option<InputVersion> {
 
 pub fn find_up(search_dir: PathBuf, file_name: &str) -> Option<PathBuf> {
     for dir in each_dir(search_dir) {
-        let path = dir.join(&file_name);
+        let path = dir.join(file_name);
         if path.exists() {
             return Some(path);
         }
@@ -11,6 +11,13 @@ e2e_test!(init, |dir| {
     eq_re!("frum --log-level quiet local", stdout);
 });
 
+e2e_test!(init_path_only, |dir| {
+    let stdout = dir.command().arg("init").arg("--path-only").stdout();
+    eq!("1", stdout.lines().count().to_string());
+    eq_re!("PATH", stdout);
+    eq_re!("aliases.default", stdout);
+});
+
 e2e_test!(use_installed_version, |dir| {
     dir.command().arg("install").arg("2.7.0").output();
     dir.command().arg("local").arg("2.7.0").output();
@@ -26,6 +33,24 @@ e2e_test!(use_not_installed_version, |dir| {
     );
 });
 
+e2e_test!(json_errors, |dir| {
+    let output = dir
+        .command()
+        .arg("--json")
+        .arg("local")
+        .arg("2.7.0")
+        .cmd()
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"]["kind"], "VersionNotFound");
+    assert_eq!(
+        error["error"]["message"],
+        "Requested version 2.7.0 is not currently installed"
+    );
+});
+
 e2e_test!(uninstall_installed_version, |dir| {
     dir.command().arg("install").arg("2.7.0").output();
     assert!(dir.path().join("versions").join("2.7.0").exists());
@@ -85,3 +110,165 @@ e2e_test!(use_configure_opts, |dir| {
     eq_re!("--disable-werror", configure_opts);
     eq_re!("--with-openssl-dir", configure_opts);
 });
+
+e2e_test!(suggest_mistyped_subcommand, |dir| {
+    eq_re!(
+        "The subcommand 'instal' wasn't recognized\n\tDid you mean 'install'\\?",
+        dir.command().arg("instal").stderr()
+    );
+    eq_re!(
+        "Did you mean 'uninstall'\\?",
+        dir.command().arg("unistall").stderr()
+    );
+});
+
+#[cfg(unix)]
+e2e_test!(install_quiet_build_from_file_mirror, |dir| {
+    let mirror = dir.path().join("mirror");
+    let source_dir = mirror.join("ruby-2.6.4");
+    std::fs::create_dir_all(&source_dir).unwrap();
+    std::fs::create_dir_all(mirror.join("2.6")).unwrap();
+    std::fs::write(
+        source_dir.join("configure"),
+        "echo checking for gcc... yes\nprefix=\"${1#--prefix=}\"\nprintf 'all:\\n\\t@echo compiling\\ninstall:\\n\\tmkdir -p $(DESTDIR)%s/bin\\n\\ttouch $(DESTDIR)%s/bin/ruby\\n' \"$prefix\" \"$prefix\" > Makefile\n",
+    )
+    .unwrap();
+    std::process::Command::new("tar")
+        .arg("-cJf")
+        .arg(mirror.join("2.6").join("ruby-2.6.4.tar.xz"))
+        .arg("-C")
+        .arg(&mirror)
+        .arg("ruby-2.6.4")
+        .status()
+        .unwrap();
+
+    let stdout = dir
+        .command()
+        .arg("--ruby-build-mirror")
+        .arg(format!("file://{}", mirror.display()))
+        .arg("install")
+        .arg("--quiet-build")
+        .arg("2.6.4")
+        .stdout();
+    eq_re!("Downloading", stdout);
+    eq_re!("Building Ruby 2.6.4", stdout);
+    assert!(!stdout.contains("compiling"));
+    let build_log =
+        std::fs::read_to_string(dir.path().join("cache").join("ruby-2.6.4-build.log")).unwrap();
+    eq_re!("checking for gcc", build_log);
+    eq_re!("compiling", build_log);
+    assert!(dir
+        .path()
+        .join("versions")
+        .join("2.6.4")
+        .join("bin")
+        .join("ruby")
+        .exists());
+});
+
+e2e_test!(install_print_url_resolves_partial_version, |dir| {
+    let mirror = dir.path().join("mirror");
+    std::fs::create_dir_all(&mirror).unwrap();
+    std::fs::write(
+        mirror.join("index.txt"),
+        "name\turl\tsha1\tsha256\tsha512\nruby-2.7.1\t-\ta\tb\tc\nruby-2.7.2\t-\ta\tb\tc\nruby-3.0.0\t-\ta\tb\tc\n",
+    )
+    .unwrap();
+
+    let stdout = dir
+        .command()
+        .arg("--ruby-build-mirror")
+        .arg(format!("file://{}", mirror.display()))
+        .arg("install")
+        .arg("--print-url")
+        .arg("2.7")
+        .stdout();
+    assert_eq!(
+        stdout.trim(),
+        format!("file://{}/2.7/ruby-2.7.2.tar.xz", mirror.display())
+    );
+    assert!(!dir.path().join("versions").join("2.7.2").exists());
+});
+
+e2e_test!(versions_current_only_prints_active_version, |dir| {
+    eq!(
+        "",
+        dir.command().arg("versions").arg("--current-only").stdout()
+    );
+    dir.create_dir("versions/3.0.0/bin");
+    dir.command().arg("local").arg("3.0.0").output();
+    eq!(
+        "3.0.0\n",
+        dir.command().arg("versions").arg("--current-only").stdout()
+    );
+});
+
+e2e_test!(versions_sorted_by_size, |dir| {
+    for version in &["3.3.0", "3.4.1", "3.4.2"] {
+        dir.create_dir(format!("versions/{}/bin", version));
+    }
+    dir.create_dir("versions/3.4.2/lib");
+    dir.create("versions/3.3.0/bin/ruby", &"x".repeat(2048));
+    dir.create("versions/3.4.1/bin/ruby", "ruby");
+    dir.create("versions/3.4.2/bin/ruby", &"x".repeat(3 * 1024 * 1024));
+    dir.create("versions/3.4.2/lib/libruby.so", &"x".repeat(512 * 1024));
+
+    eq!(
+        "3.3\n  3.3.0 (2.0 KiB)\n3.4\n  3.4.1 (4 B)\n  3.4.2 (3.5 MiB)\n",
+        dir.command().arg("versions").arg("--sizes").stdout()
+    );
+    eq!(
+        "  3.4.2 (3.5 MiB)\n  3.3.0 (2.0 KiB)\n  3.4.1 (4 B)\n",
+        dir.command()
+            .arg("versions")
+            .arg("--sort")
+            .arg("size")
+            .stdout()
+    );
+});
+
+e2e_test!(versions_in_prefix, |dir| {
+    dir.create_dir("versions/3.4.1");
+    for name in &["3.3.1", "3.4.0", "jruby-9.4.3.0"] {
+        dir.create_dir(format!("rbenv/versions/{}/bin", name));
+    }
+
+    let output = dir
+        .command()
+        .arg("versions")
+        .arg("--prefix")
+        .arg(dir.path().join("rbenv").join("versions"))
+        .output();
+    eq!(
+        "3.3\n  3.3.1\n3.4\n  3.4.0\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+    eq_re!(
+        "Skipping jruby-9.4.3.0",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    dir.command()
+        .arg("versions")
+        .arg("--prefix")
+        .arg(dir.path().join("missing"))
+        .assert_err();
+});
+
+e2e_test!(piped_output_has_no_colors, |dir| {
+    let stderr = dir.command().arg("local").arg("2.0.0").stderr();
+    eq_re!("Requested version 2.0.0", stderr);
+    assert!(!stderr.contains('\u{1b}'));
+
+    let mut forced = dir.command();
+    forced.cmd().env("CLICOLOR_FORCE", "1");
+    assert!(forced.arg("local").arg("2.0.0").stderr().contains('\u{1b}'));
+
+    let mut no_color = dir.command();
+    no_color.cmd().env("CLICOLOR_FORCE", "1");
+    assert!(!no_color
+        .arg("--no-color")
+        .arg("local")
+        .arg("2.0.0")
+        .stderr()
+        .contains('\u{1b}'));
+});
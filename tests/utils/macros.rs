@@ -3,8 +3,8 @@ macro_rules! e2e_test {
     ($name:ident, $fun:expr) => {
         #[test]
         fn $name() {
-            let (dir, _) = crate::utils::setup(stringify!($name));
-            let fun: fn(crate::utils::Dir) = $fun;
+            let (dir, _) = $crate::utils::setup(stringify!($name));
+            let fun: fn($crate::utils::Dir) = $fun;
             fun(dir);
         }
     };
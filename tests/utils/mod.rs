@@ -71,7 +71,7 @@ impl Dir {
         let dir = env::temp_dir()
             .join(TEST_DIR)
             .join(name)
-            .join(&format!("{}", id));
+            .join(format!("{}", id));
         if dir.exists() {
             nice_err(&dir, fs::remove_dir_all(&dir));
         }
@@ -479,7 +479,7 @@ fn set_frum_path_env(root: &Path, dir: &Path) -> (PathBuf, PathBuf) {
     let env_cmd: String = match String::from_utf8_lossy(
         &Command::new(root.join(format!("../frum{}", env::consts::EXE_SUFFIX)))
             .arg("init")
-            .env("FRUM_PATH", &dir)
+            .env("FRUM_PATH", dir)
             .output()
             .unwrap()
             .stdout,
@@ -501,7 +501,7 @@ fn set_frum_path_env(root: &Path, dir: &Path) -> (PathBuf, PathBuf) {
     #[cfg(windows)]
     let path = regex::Regex::new(r#"[^_]PATH = "(.+)""#).unwrap();
 
-    return (
+    (
         frum_multishell_path
             .captures(env_cmd.as_str())
             .unwrap()
@@ -511,5 +511,5 @@ fn set_frum_path_env(root: &Path, dir: &Path) -> (PathBuf, PathBuf) {
             .unwrap()
             .get(1)
             .map_or(PathBuf::from(""), |m| PathBuf::from(m.as_str())),
-    );
+    )
 }